@@ -0,0 +1,439 @@
+use crate::{
+    lexer::PklToken,
+    parser::{parse_pkl_recovering, PklStatement},
+};
+use logos::Logos;
+use std::{fmt, ops::Range};
+
+/// A lossless concrete syntax tree of a Pkl source.
+///
+/// Unlike the AST, it keeps every token of the source, spaces, newlines and comments included,
+/// so that the source can be rewritten without losing its formatting.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cst<'a> {
+    pub source: &'a str,
+    /// The module node, its children cover the whole source.
+    pub root: CstNode<'a>,
+}
+
+/// A node of the [`Cst`], grouping the tokens and nodes it is made of.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CstNode<'a> {
+    pub kind: CstNodeKind<'a>,
+    pub span: Range<usize>,
+    pub children: Vec<CstElement<'a>>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum CstElement<'a> {
+    Node(CstNode<'a>),
+    Token(CstToken<'a>),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CstNodeKind<'a> {
+    /// The whole module.
+    Module,
+    /// A module level property, with its name.
+    Property(&'a str),
+    Import,
+    ModuleClause,
+    Amends,
+    Extends,
+    /// The annotations of the module.
+    ModuleAnnotations,
+    /// An object body, braces included.
+    ObjectBody,
+    /// A member of an object body, with its name, or its key for entries like `["birds"] { ... }`.
+    Member(Option<&'a str>),
+    /// A statement that could not be parsed.
+    Error,
+}
+
+/// A token of the source, spaces and comments included.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CstToken<'a> {
+    /// The lexed token, `None` for tabs, which the lexer skips, and for invalid input.
+    pub token: Option<PklToken<'a>>,
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+impl<'a> CstToken<'a> {
+    /// Whether the token is a space, a newline or a comment.
+    pub fn is_trivia(&self) -> bool {
+        match &self.token {
+            Some(PklToken::Space)
+            | Some(PklToken::NewLine)
+            | Some(PklToken::LineComment(_))
+            | Some(PklToken::DocComment(_))
+            | Some(PklToken::MultilineComment(_)) => true,
+            Some(_) => false,
+            None => self.text.chars().all(char::is_whitespace),
+        }
+    }
+
+    pub fn is_newline(&self) -> bool {
+        matches!(self.token, Some(PklToken::NewLine))
+    }
+}
+
+impl<'a> CstElement<'a> {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            CstElement::Node(node) => node.span.clone(),
+            CstElement::Token(token) => token.span.clone(),
+        }
+    }
+}
+
+impl<'a> CstNode<'a> {
+    fn new(kind: CstNodeKind<'a>, children: Vec<CstElement<'a>>) -> Self {
+        let start = children.first().map_or(0, |child| child.span().start);
+        let end = children.last().map_or(start, |child| child.span().end);
+
+        Self {
+            kind,
+            span: start..end,
+            children,
+        }
+    }
+
+    /// Returns all the tokens of the node, in source order.
+    pub fn tokens(&self) -> Vec<&CstToken<'a>> {
+        let mut tokens = Vec::new();
+        for child in self.children.iter() {
+            match child {
+                CstElement::Node(node) => tokens.extend(node.tokens()),
+                CstElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+
+    /// Returns the child nodes of the node.
+    pub fn nodes(&self) -> impl Iterator<Item = &CstNode<'a>> {
+        self.children.iter().filter_map(|child| match child {
+            CstElement::Node(node) => Some(node),
+            CstElement::Token(_) => None,
+        })
+    }
+
+    /// Returns the object body directly in this node, if any,
+    /// the last one for a chain of amended objects.
+    pub fn body(&self) -> Option<&CstNode<'a>> {
+        self.nodes()
+            .filter(|node| node.kind == CstNodeKind::ObjectBody)
+            .last()
+    }
+}
+
+impl<'a> Cst<'a> {
+    /// Returns the statement nodes of the module.
+    pub fn statements(&self) -> impl Iterator<Item = &CstNode<'a>> {
+        self.root.nodes()
+    }
+}
+
+/// Writes back the exact source of the tree.
+impl fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in self.root.tokens() {
+            write!(f, "{}", token.text)?;
+        }
+        Ok(())
+    }
+}
+
+/// Builds the lossless syntax tree of `source`.
+///
+/// Statements that cannot be parsed are kept in [`CstNodeKind::Error`] nodes.
+pub fn parse_cst(source: &str) -> Cst<'_> {
+    let tokens = lex_all(source);
+    let (statements, _) = parse_pkl_recovering(&mut PklToken::lexer(source));
+
+    let mut children = Vec::new();
+    let mut index = 0;
+
+    for statement in statements {
+        let span = statement.span();
+
+        while index < tokens.len() && tokens[index].span.start < span.start {
+            children.push(CstElement::Token(tokens[index].clone()));
+            index += 1;
+        }
+
+        let start = index;
+        while index < tokens.len() && tokens[index].span.start < span.end {
+            index += 1;
+        }
+
+        let kind = match statement {
+            PklStatement::Constant { name, .. } => CstNodeKind::Property(name),
+            PklStatement::Import(_, _, _) | PklStatement::GlobImport(_, _, _) => {
+                CstNodeKind::Import
+            }
+            PklStatement::ModuleClause(_, _) => CstNodeKind::ModuleClause,
+            PklStatement::Amends(_, _) => CstNodeKind::Amends,
+            PklStatement::Extends(_, _) => CstNodeKind::Extends,
+            PklStatement::ModuleAnnotations(_, _) => CstNodeKind::ModuleAnnotations,
+            PklStatement::Error(_) => CstNodeKind::Error,
+        };
+
+        let mut position = start;
+        let statement_children = build_elements(&tokens[..index], &mut position);
+        children.push(CstElement::Node(CstNode::new(kind, statement_children)));
+    }
+
+    children.extend(tokens[index..].iter().cloned().map(CstElement::Token));
+
+    let mut root = CstNode::new(CstNodeKind::Module, children);
+    root.span = 0..source.len();
+
+    Cst { source, root }
+}
+
+/// Lexes the whole source, tabs and invalid input included.
+fn lex_all(source: &str) -> Vec<CstToken<'_>> {
+    let mut tokens = Vec::new();
+    let mut lexer = PklToken::lexer(source);
+    let mut end = 0;
+
+    while let Some(token) = lexer.next() {
+        let span = lexer.span();
+        if end < span.start {
+            // bytes skipped by the lexer
+            tokens.push(CstToken {
+                token: None,
+                text: &source[end..span.start],
+                span: end..span.start,
+            });
+        }
+
+        tokens.push(CstToken {
+            token: token.ok(),
+            text: lexer.slice(),
+            span: span.clone(),
+        });
+        end = span.end;
+    }
+
+    if end < source.len() {
+        tokens.push(CstToken {
+            token: None,
+            text: &source[end..],
+            span: end..source.len(),
+        });
+    }
+
+    tokens
+}
+
+/// Builds the elements of the tokens starting at `position`,
+/// grouping object bodies into nodes.
+fn build_elements<'a>(tokens: &[CstToken<'a>], position: &mut usize) -> Vec<CstElement<'a>> {
+    let mut elements = Vec::new();
+
+    while let Some(token) = tokens.get(*position) {
+        match token.token {
+            Some(PklToken::OpenBrace) => {
+                elements.push(CstElement::Node(build_body(tokens, position)))
+            }
+            _ => {
+                elements.push(CstElement::Token(token.clone()));
+                *position += 1;
+            }
+        }
+    }
+
+    elements
+}
+
+/// Builds an object body node, starting at its opening brace.
+///
+/// Members are delimited by newlines and commas, outside of parentheses and nested bodies.
+fn build_body<'a>(tokens: &[CstToken<'a>], position: &mut usize) -> CstNode<'a> {
+    let mut children = vec![CstElement::Token(tokens[*position].clone())];
+    *position += 1;
+
+    while let Some(token) = tokens.get(*position) {
+        match token.token {
+            Some(PklToken::CloseBrace) => {
+                children.push(CstElement::Token(token.clone()));
+                *position += 1;
+                break;
+            }
+            Some(PklToken::Comma) => {
+                children.push(CstElement::Token(token.clone()));
+                *position += 1;
+            }
+            _ if token.is_trivia() => {
+                children.push(CstElement::Token(token.clone()));
+                *position += 1;
+            }
+            _ => children.push(CstElement::Node(build_member(tokens, position))),
+        }
+    }
+
+    CstNode::new(CstNodeKind::ObjectBody, children)
+}
+
+/// Builds an object member node, up to the end of its line or the next comma.
+///
+/// Annotations on the lines preceding the member are part of it.
+fn build_member<'a>(tokens: &[CstToken<'a>], position: &mut usize) -> CstNode<'a> {
+    let mut children = Vec::new();
+    let mut name = None;
+    let mut parens = 0;
+    let mut annotations = AnnotationTracker::default();
+    // whether only annotations have been seen so far
+    let mut in_annotations = true;
+    // whether the key of an entry, e.g. `["birds"]`, is expected
+    let mut in_key = false;
+
+    while let Some(token) = tokens.get(*position) {
+        match token.token {
+            Some(PklToken::NewLine) | Some(PklToken::Comma) if parens == 0 && !in_annotations => {
+                break
+            }
+            Some(PklToken::CloseBrace) => break,
+            Some(PklToken::OpenBrace) => {
+                let body = CstElement::Node(build_body(tokens, position));
+                let is_annotation = annotations.visit(&body);
+                in_annotations &= is_annotation;
+                children.push(body);
+
+                // `a {} b = 1`, a new member can follow an object on the same line
+                let next = tokens[*position..]
+                    .iter()
+                    .find(|token| !token.is_trivia() || token.is_newline());
+                if !is_annotation && next.is_some_and(|token| starts_member(&token.token)) {
+                    break;
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let element = CstElement::Token(token.clone());
+        let is_annotation = annotations.visit(&element);
+        in_annotations &= is_annotation || token.is_trivia();
+
+        match token.token {
+            Some(PklToken::OpenParen) | Some(PklToken::FunctionCall(_)) => parens += 1,
+            Some(PklToken::CloseParen) => parens -= 1,
+            Some(PklToken::Identifier(id)) | Some(PklToken::IllegalIdentifier(id))
+                if name.is_none() && !is_annotation =>
+            {
+                name = Some(id)
+            }
+            Some(PklToken::OpenBracket) if name.is_none() && !is_annotation => in_key = true,
+            Some(PklToken::String(key)) if in_key => {
+                name = Some(key);
+                in_key = false;
+            }
+            _ => {}
+        }
+
+        children.push(element);
+        *position += 1;
+    }
+
+    CstNode::new(CstNodeKind::Member(name), children)
+}
+
+fn starts_member(token: &Option<PklToken>) -> bool {
+    matches!(
+        token,
+        Some(PklToken::Identifier(_))
+            | Some(PklToken::IllegalIdentifier(_))
+            | Some(PklToken::OpenBracket)
+            | Some(PklToken::Local)
+            | Some(PklToken::Hidden)
+            | Some(PklToken::Fixed)
+            | Some(PklToken::Const)
+    )
+}
+
+/// Tells apart the annotations of a declaration, e.g. `@Deprecated { message = "..." }`,
+/// from the rest of its elements.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AnnotationTracker {
+    #[default]
+    Outside,
+    /// After a `@` or a `.` of the annotation name.
+    ExpectName,
+    InName,
+    /// After the spaces following the annotation name, which may be followed by a body.
+    AfterName,
+}
+
+impl AnnotationTracker {
+    /// Returns whether `element`, the next element of a declaration, is part of an annotation.
+    pub(crate) fn visit(&mut self, element: &CstElement) -> bool {
+        let token = match element {
+            CstElement::Node(_) => {
+                let is_body = matches!(self, Self::InName | Self::AfterName);
+                *self = Self::Outside;
+                return is_body;
+            }
+            CstElement::Token(token) => token,
+        };
+
+        match (&token.token, *self) {
+            (Some(PklToken::At), _) => *self = Self::ExpectName,
+            (
+                Some(PklToken::Identifier(_)) | Some(PklToken::IllegalIdentifier(_)),
+                Self::ExpectName,
+            ) => *self = Self::InName,
+            (Some(PklToken::Dot), Self::InName) => *self = Self::ExpectName,
+            (_, Self::InName | Self::AfterName) if token.is_trivia() && !token.is_newline() => {
+                *self = Self::AfterName;
+                return false;
+            }
+            _ => {
+                *self = Self::Outside;
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "/// A bird\n@Deprecated { message = \"old\" }\nmodule birds\n\nimport \"pkl:math\" as m\n\n\
+        bird {\n\tname = \"Pigeon\" // inline\n  [\"key\"] = 1, local x = 2\n  /* block */\n}\n\
+        broken = {\n$\n";
+
+    #[test]
+    fn display_writes_back_the_source() {
+        assert_eq!(parse_cst(SOURCE).to_string(), SOURCE);
+        assert_eq!(parse_cst("").to_string(), "");
+        assert_eq!(parse_cst("a = 1 \t\n\n").to_string(), "a = 1 \t\n\n");
+    }
+
+    #[test]
+    fn statement_nodes_cover_their_tokens() {
+        let cst = parse_cst(SOURCE);
+        let kinds: Vec<_> = cst.statements().map(|node| node.kind).collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                CstNodeKind::ModuleAnnotations,
+                CstNodeKind::ModuleClause,
+                CstNodeKind::Import,
+                CstNodeKind::Property("bird"),
+                CstNodeKind::Error,
+            ]
+        );
+        for node in cst.statements() {
+            let text: String = node.tokens().iter().map(|token| token.text).collect();
+            assert_eq!(text, &SOURCE[node.span.clone()]);
+        }
+    }
+}
@@ -0,0 +1,389 @@
+use crate::error::{line_col, ErrorInfo, PklError, PklWarning};
+use logos::Span;
+use std::fmt::Write;
+
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// Width of a tab once rendered.
+const TAB_WIDTH: usize = 4;
+
+/// Renders a [`PklError`] as a compiler-style report:
+///
+/// ```text
+/// error[ConstraintViolation]: Cannot amend `bird.name`, it is declared as fixed
+///  --> birds.pkl:6:10
+///   |
+/// 2 |   fixed name = "Pigeon"
+///   |                -------- `bird.name` is declared fixed here
+/// ...
+/// 6 |   name = "Parrot"
+///   |          ^^^^^^^^
+///   |
+///   = help: remove the `fixed` modifier to allow amending the property
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DiagnosticRenderer {
+    /// Whether to color the report with ANSI escape codes.
+    pub color: bool,
+}
+
+/// The annotated lines of one source in the report.
+struct Snippet<'m> {
+    name: &'m str,
+    source: &'m str,
+    /// The line and column of its first annotation.
+    location: (usize, usize),
+    annotations: Vec<Annotation<'m>>,
+}
+
+impl Snippet<'_> {
+    /// Returns every annotated line, as (line number, annotation index), sorted.
+    fn lines(&self) -> Vec<(usize, usize)> {
+        let mut lines: Vec<(usize, usize)> = self
+            .annotations
+            .iter()
+            .enumerate()
+            .flat_map(|(i, annotation)| {
+                let (first, _) = line_col(self.source, annotation.span.start);
+                let (last, _) =
+                    line_col(self.source, annotation.span.end.max(annotation.span.start));
+                if last - first > 1 {
+                    vec![(first, i), (last, i)]
+                } else {
+                    (first..=last).map(|line| (line, i)).collect()
+                }
+            })
+            .collect();
+        lines.sort();
+
+        lines
+    }
+}
+
+/// A span to underline in the report.
+struct Annotation<'m> {
+    span: Span,
+    message: Option<&'m str>,
+    is_primary: bool,
+}
+
+impl DiagnosticRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables or disables ANSI colors.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Renders `error`, `source` being the source its spans refer to
+    /// unless the error carries its own, as errors from imported modules do.
+    pub fn render(&self, error: &PklError, source: &str) -> String {
+        self.render_info("error", RED, error.kind(), error.info(), source)
+    }
+
+    /// Renders `warning`, `source` being the source its spans refer to
+    /// unless the warning carries its own.
+    pub fn render_warning(&self, warning: &PklWarning, source: &str) -> String {
+        self.render_info("warning", YELLOW, warning.kind(), warning.info(), source)
+    }
+
+    fn render_info(
+        &self,
+        severity: &str,
+        severity_color: &'static str,
+        kind: &str,
+        info: &ErrorInfo,
+        source: &str,
+    ) -> String {
+        let mut out = String::new();
+        let source = info.source.as_deref().unwrap_or(source);
+
+        let (line, column) = info
+            .location
+            .unwrap_or_else(|| line_col(source, info.span.start));
+        let name = info.source_name.as_deref().unwrap_or("<source>");
+
+        let _ = writeln!(
+            out,
+            "{}{}[{}]{}: {}{}{}",
+            self.paint(severity_color),
+            severity,
+            kind,
+            self.paint(RESET),
+            self.paint(BOLD),
+            info.message,
+            self.paint(RESET)
+        );
+
+        // the error's source first, then the other sources of its labels, in order
+        let mut snippets = vec![Snippet {
+            name,
+            source,
+            location: (line, column),
+            annotations: vec![Annotation {
+                span: info.span.clone(),
+                message: None,
+                is_primary: true,
+            }],
+        }];
+        for label in info.labels.iter() {
+            let annotation = Annotation {
+                span: label.span.clone(),
+                message: Some(label.message.as_str()),
+                is_primary: false,
+            };
+            let label_source = label.source.as_deref().unwrap_or(source);
+
+            match snippets
+                .iter_mut()
+                .find(|snippet| std::ptr::eq(snippet.source, label_source))
+            {
+                Some(snippet) => snippet.annotations.push(annotation),
+                None => snippets.push(Snippet {
+                    name: label.source_name.as_deref().unwrap_or("<source>"),
+                    source: label_source,
+                    location: line_col(label_source, label.span.start),
+                    annotations: vec![annotation],
+                }),
+            }
+        }
+
+        let lines: Vec<Vec<(usize, usize)>> = snippets.iter().map(Snippet::lines).collect();
+        let gutter_width = lines
+            .iter()
+            .filter_map(|lines| lines.last())
+            .map(|(line, _)| line.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(gutter_width);
+
+        for (i, (snippet, lines)) in snippets.iter().zip(lines).enumerate() {
+            let _ = writeln!(
+                out,
+                "{}{}{}{} {}:{}:{}",
+                gutter,
+                self.paint(BLUE),
+                if i == 0 { "-->" } else { ":::" },
+                self.paint(RESET),
+                snippet.name,
+                snippet.location.0,
+                snippet.location.1
+            );
+            let _ = writeln!(out, "{} {}|{}", gutter, self.paint(BLUE), self.paint(RESET));
+            self.render_snippet(&mut out, &gutter, snippet, lines, severity_color);
+        }
+
+        if !info.help.is_empty() {
+            let _ = writeln!(out, "{} {}|{}", gutter, self.paint(BLUE), self.paint(RESET));
+        }
+        for help in info.help.iter() {
+            let _ = writeln!(
+                out,
+                "{} {}={} {}help{}: {}",
+                gutter,
+                self.paint(BLUE),
+                self.paint(RESET),
+                self.paint(CYAN),
+                self.paint(RESET),
+                help
+            );
+        }
+
+        out
+    }
+
+    /// Writes the annotated `lines` of `snippet`, as returned by [`Snippet::lines`].
+    fn render_snippet(
+        &self,
+        out: &mut String,
+        gutter: &str,
+        snippet: &Snippet,
+        lines: Vec<(usize, usize)>,
+        severity_color: &'static str,
+    ) {
+        let source_lines: Vec<&str> = snippet.source.split('\n').collect();
+        let mut previous_line = None;
+
+        for (line_number, annotation_index) in lines {
+            let Some(source_line) = source_lines.get(line_number - 1) else {
+                continue;
+            };
+            let line_start = source_lines[..line_number - 1]
+                .iter()
+                .map(|line| line.len() + 1)
+                .sum::<usize>();
+
+            if previous_line != Some(line_number) {
+                if previous_line.is_some_and(|previous| previous + 1 < line_number) {
+                    let _ = writeln!(out, "{}...{}", self.paint(BLUE), self.paint(RESET));
+                }
+                let _ = writeln!(
+                    out,
+                    "{}{:>width$} |{} {}",
+                    self.paint(BLUE),
+                    line_number,
+                    self.paint(RESET),
+                    expand_tabs(source_line.trim_end_matches('\r')),
+                    width = gutter.len()
+                );
+                previous_line = Some(line_number);
+            }
+
+            let annotation = &snippet.annotations[annotation_index];
+            self.render_underline(
+                out,
+                gutter,
+                source_line,
+                line_start,
+                annotation,
+                severity_color,
+            );
+        }
+    }
+
+    /// Writes the underline of the part of `annotation` lying on `source_line`.
+    fn render_underline(
+        &self,
+        out: &mut String,
+        gutter: &str,
+        source_line: &str,
+        line_start: usize,
+        annotation: &Annotation,
+        severity_color: &'static str,
+    ) {
+        let line_end = line_start + source_line.len();
+        let start = annotation.span.start.clamp(line_start, line_end) - line_start;
+        let end = annotation.span.end.clamp(line_start, line_end) - line_start;
+
+        let start = floor_char_boundary(source_line, start);
+        let end = floor_char_boundary(source_line, end.max(start));
+
+        let padding = expand_tabs(&source_line[..start]).chars().count();
+        let width = expand_tabs(&source_line[start..end]).chars().count().max(1);
+
+        let (marker, color) = if annotation.is_primary {
+            ("^", severity_color)
+        } else {
+            ("-", BLUE)
+        };
+
+        let mut underline = format!(
+            "{}{}{}",
+            " ".repeat(padding),
+            self.paint(color),
+            marker.repeat(width)
+        );
+        // messages are only written once, after the end of the span
+        if let Some(message) = annotation.message {
+            if annotation.span.end <= line_end {
+                let _ = write!(underline, " {}", message);
+            }
+        }
+
+        let _ = writeln!(
+            out,
+            "{} {}|{} {}{}",
+            gutter,
+            self.paint(BLUE),
+            self.paint(RESET),
+            underline,
+            self.paint(RESET)
+        );
+    }
+
+    fn paint(&self, code: &'static str) -> &'static str {
+        if self.color {
+            code
+        } else {
+            ""
+        }
+    }
+}
+
+impl PklError {
+    /// Renders the error as a compiler-style report, without colors.
+    ///
+    /// See [`DiagnosticRenderer`] for colored reports.
+    pub fn render(&self, source: &str) -> String {
+        DiagnosticRenderer::new().render(self, source)
+    }
+}
+
+impl PklWarning {
+    /// Renders the warning as a compiler-style report, without colors.
+    pub fn render(&self, source: &str) -> String {
+        DiagnosticRenderer::new().render_warning(self, source)
+    }
+}
+
+/// Returns the name among `candidates` closest to `name`, if close enough to be a likely typo.
+pub(crate) fn closest_name<'c>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'c str>,
+) -> Option<&'c str> {
+    let max_distance = name.chars().count().div_ceil(3).clamp(1, 3);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn expand_tabs(s: &str) -> String {
+    s.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MemoryModuleResolver, Pkl};
+
+    #[test]
+    fn errors_of_imported_modules_are_rendered_against_their_source() {
+        let resolver =
+            MemoryModuleResolver::new().with_module("lib.pkl", "// lib\n\nfoo = 1\na = nope\n");
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.set_source_name("main.pkl");
+
+        let source = "import \"lib.pkl\"\n\nx = lib.a\n";
+        let error = pkl.parse(source).unwrap_err();
+        let report = error.render(source);
+
+        assert!(report.contains("--> lib.pkl:4:5"), "{report}");
+        assert!(report.contains("4 | a = nope"), "{report}");
+        assert!(!report.contains("x = lib.a"), "{report}");
+    }
+}
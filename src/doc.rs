@@ -0,0 +1,939 @@
+use crate::{
+    parser::{
+        AstPklValue, ExprMember, Modifiers, ObjectMember, PklExpr, PklResult, PklStatement,
+        StringPart,
+    },
+    Pkl, PklError, PklValue,
+};
+use std::{
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+/// The format of the pages produced by a [`DocGenerator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    Html,
+    Markdown,
+}
+
+impl DocFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            DocFormat::Html => "html",
+            DocFormat::Markdown => "md",
+        }
+    }
+}
+
+/// A generated documentation page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocPage {
+    /// The file name of the page, relative to the documentation directory.
+    pub path: String,
+    pub content: String,
+}
+
+/// Generates static documentation pages for Pkl modules, from their doc comments,
+/// annotations, default values and the types of their evaluated properties.
+///
+/// ```ignore
+/// let mut generator = DocGenerator::new(DocFormat::Html);
+/// generator.add_module("birds.pkl", &fs::read_to_string("birds.pkl")?)?;
+/// generator.write_to("docs")?;
+/// ```
+///
+/// Each module gets its own page, listed in an index page. References to properties,
+/// whether in values, such as `name = bird.name`, or in doc comments, such as `[bird.name]`,
+/// link to their documentation, across modules for imported, amended and extended modules
+/// added to the same generator.
+///
+/// `local` properties are left out.
+#[derive(Debug, Clone)]
+pub struct DocGenerator {
+    format: DocFormat,
+    modules: Vec<ModuleDoc>,
+}
+
+#[derive(Debug, Clone)]
+struct ModuleDoc {
+    name: String,
+    /// The path the module was added with.
+    path: String,
+    /// The source of its annotations.
+    annotations: Vec<String>,
+    /// `amends` or `extends` and the module it targets.
+    header: Option<(&'static str, String)>,
+    /// The imported modules and their local names.
+    imports: Vec<(String, String)>,
+    /// The documented properties and object members, in declaration order,
+    /// each object being followed by its members.
+    properties: Vec<PropertyDoc>,
+}
+
+#[derive(Debug, Clone)]
+struct PropertyDoc {
+    /// The dotted path of the property, e.g. `bird.name`.
+    path: String,
+    depth: usize,
+    doc: Option<String>,
+    type_name: String,
+    /// The source of its value, objects being documented through their members.
+    value: Option<String>,
+    modifiers: Modifiers,
+    /// The source of its annotations.
+    annotations: Vec<String>,
+    deprecation: Option<String>,
+    /// The dotted paths its value refers to.
+    references: Vec<String>,
+}
+
+impl DocGenerator {
+    pub fn new(format: DocFormat) -> Self {
+        Self {
+            format,
+            modules: Vec::new(),
+        }
+    }
+
+    /// Parses and evaluates the module at `path`, whose content is `source`, and adds it to the documentation.
+    ///
+    /// The module is named after its `module` clause, or the name of its file.
+    /// Its relative imports are resolved against `path`.
+    pub fn add_module(&mut self, path: impl Into<String>, source: &str) -> PklResult<()> {
+        let path = path.into();
+
+        let mut pkl = Pkl::new();
+        pkl.set_module_uri(path.as_str());
+        pkl.set_source_name(path.as_str());
+        let statements = pkl.generate_ast(source)?;
+        pkl.parse(source)?;
+
+        let name = match pkl.module_name() {
+            Some(name) => name.to_owned(),
+            None => {
+                let file_name = path.rsplit('/').next().unwrap_or(&path);
+                file_name
+                    .strip_suffix(".pkl")
+                    .unwrap_or(file_name)
+                    .to_owned()
+            }
+        };
+
+        if self.modules.iter().any(|module| module.name == name) {
+            return Err(PklError::constraint_violation(
+                format!("A module named `{}` was already added", name),
+                0..0,
+            ));
+        }
+
+        let mut module = ModuleDoc {
+            name,
+            path,
+            annotations: Vec::new(),
+            header: None,
+            imports: Vec::new(),
+            properties: Vec::new(),
+        };
+
+        for statement in statements.iter() {
+            match statement {
+                PklStatement::Constant {
+                    name,
+                    value: expr,
+                    modifiers,
+                    doc,
+                    annotations,
+                    ..
+                } => {
+                    let property = Property {
+                        doc: doc.as_deref(),
+                        modifiers: *modifiers,
+                        annotations: annotations.iter().map(|a| &source[a.span()]).collect(),
+                    };
+                    // properties defined several times are documented once, with their last definition
+                    module
+                        .properties
+                        .retain(|p| p.path != *name && !p.path.starts_with(&format!("{}.", name)));
+                    document_property(
+                        &mut module.properties,
+                        &pkl,
+                        source,
+                        name,
+                        0,
+                        property,
+                        expr,
+                    );
+                }
+                PklStatement::Import(target, local_name, _)
+                | PklStatement::GlobImport(target, local_name, _) => {
+                    let local_name = local_name.map(str::to_owned).unwrap_or_else(|| {
+                        let file_name = target.rsplit('/').next().unwrap_or(target);
+                        file_name
+                            .strip_suffix(".pkl")
+                            .unwrap_or(file_name)
+                            .to_owned()
+                    });
+                    module.imports.push((target.to_string(), local_name));
+                }
+                PklStatement::Amends(target, _) => {
+                    module.header = Some(("amends", target.to_string()));
+                }
+                PklStatement::Extends(target, _) => {
+                    module.header = Some(("extends", target.to_string()));
+                }
+                PklStatement::ModuleAnnotations(annotations, _) => {
+                    module.annotations = annotations
+                        .iter()
+                        .map(|a| source[a.span()].to_owned())
+                        .collect();
+                }
+                PklStatement::ModuleClause(_, _) | PklStatement::Error(_) => {}
+            }
+        }
+
+        self.modules.push(module);
+        Ok(())
+    }
+
+    /// Generates the pages of the added modules, along with an index page listing them.
+    pub fn generate(&self) -> Vec<DocPage> {
+        let mut pages = vec![DocPage {
+            path: format!("index.{}", self.format.extension()),
+            content: self.render_index(),
+        }];
+
+        for (i, module) in self.modules.iter().enumerate() {
+            pages.push(DocPage {
+                path: self.page_path(module),
+                content: self.render_module(i),
+            });
+        }
+
+        pages
+    }
+
+    /// Generates the pages and writes them into `dir`, creating it if needed.
+    pub fn write_to(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        for page in self.generate() {
+            fs::write(dir.join(page.path), page.content)?;
+        }
+
+        Ok(())
+    }
+
+    fn page_path(&self, module: &ModuleDoc) -> String {
+        format!("{}.{}", module.name, self.format.extension())
+    }
+
+    /// Returns the index of the added module targeted by `target`, as written in an import or a module header.
+    fn find_module(&self, target: &str) -> Option<usize> {
+        let target = normalize(target);
+        self.modules
+            .iter()
+            .position(|module| normalize(&module.path) == target)
+    }
+
+    /// Returns the link to the documentation of `reference`, a dotted path used in module `index`
+    /// within the object at `scope`, looking it up in the enclosing objects, then in the module,
+    /// its imports and the module it amends or extends.
+    fn resolve(&self, index: usize, scope: &str, reference: &str) -> Option<String> {
+        let module = &self.modules[index];
+
+        let mut scopes: Vec<&str> = Vec::new();
+        let mut current = scope;
+        while !current.is_empty() {
+            scopes.push(current);
+            current = current.rsplit_once('.').map_or("", |(parent, _)| parent);
+        }
+        scopes.push("");
+
+        for scope in scopes {
+            let candidate = if scope.is_empty() {
+                reference.to_owned()
+            } else {
+                format!("{}.{}", scope, reference)
+            };
+
+            let scope_len = if scope.is_empty() {
+                0
+            } else {
+                scope.split('.').count()
+            };
+            if let Some(path) = self.longest_documented(index, &candidate, scope_len + 1) {
+                return Some(self.href(index, index, Some(&path)));
+            }
+        }
+
+        let (first, rest) = reference
+            .split_once('.')
+            .map_or((reference, None), |(first, rest)| (first, Some(rest)));
+        for (target, local_name) in module.imports.iter() {
+            if local_name != first {
+                continue;
+            }
+            let target = self.find_module(target)?;
+
+            return match rest {
+                Some(rest) => self
+                    .longest_documented(target, rest, 1)
+                    .map(|path| self.href(index, target, Some(&path))),
+                None => Some(self.href(index, target, None)),
+            };
+        }
+
+        // properties inherited from the amended or extended module
+        let (_, base) = module.header.as_ref()?;
+        let base = self.find_module(base)?;
+        if base == index {
+            return None;
+        }
+        self.longest_documented(base, reference, 1)
+            .map(|path| self.href(index, base, Some(&path)))
+    }
+
+    /// Returns the longest prefix of the dotted `path` documented in module `index`,
+    /// made of at least `min_segments` segments, e.g. `bird.name` for `bird.name.length`.
+    fn longest_documented(&self, index: usize, path: &str, min_segments: usize) -> Option<String> {
+        let segments: Vec<&str> = path.split('.').collect();
+
+        (min_segments..=segments.len()).rev().find_map(|n| {
+            let prefix = segments[..n].join(".");
+            self.modules[index]
+                .properties
+                .iter()
+                .any(|property| property.path == prefix)
+                .then_some(prefix)
+        })
+    }
+
+    /// Returns the link from a page of module `from` to the documentation of `anchor` in module `to`.
+    fn href(&self, from: usize, to: usize, anchor: Option<&str>) -> String {
+        let page = if from == to {
+            String::new()
+        } else {
+            self.page_path(&self.modules[to])
+        };
+
+        match anchor {
+            Some(anchor) => format!("{}#{}", page, anchor),
+            None => page,
+        }
+    }
+
+    fn render_index(&self) -> String {
+        let mut out = String::new();
+
+        match self.format {
+            DocFormat::Markdown => {
+                out.push_str("# Modules\n\n");
+                for module in self.modules.iter() {
+                    out.push_str(&format!(
+                        "- [`{}`]({}): `{}`\n",
+                        module.name,
+                        self.page_path(module),
+                        module.path
+                    ));
+                }
+            }
+            DocFormat::Html => {
+                out.push_str(&html_header("Modules"));
+                out.push_str("<h1>Modules</h1>\n<ul>\n");
+                for module in self.modules.iter() {
+                    out.push_str(&format!(
+                        "<li><a href=\"{}\"><code>{}</code></a>: <code>{}</code></li>\n",
+                        escape_html(&self.page_path(module)),
+                        escape_html(&module.name),
+                        escape_html(&module.path)
+                    ));
+                }
+                out.push_str("</ul>\n</body>\n</html>\n");
+            }
+        }
+
+        out
+    }
+
+    fn render_module(&self, index: usize) -> String {
+        match self.format {
+            DocFormat::Markdown => self.render_markdown(index),
+            DocFormat::Html => self.render_html(index),
+        }
+    }
+
+    fn render_markdown(&self, index: usize) -> String {
+        let module = &self.modules[index];
+        let mut out = String::from("[Index](index.md)\n\n");
+        out.push_str(&format!("# Module `{}`\n\n", module.name));
+
+        if !module.annotations.is_empty() {
+            out.push_str(&code_block(&module.annotations.join("\n")));
+        }
+        if let Some((keyword, target)) = &module.header {
+            out.push_str(&format!(
+                "{} {}\n\n",
+                capitalize(keyword),
+                self.markdown_module_link(index, target)
+            ));
+        }
+        if !module.imports.is_empty() {
+            let imports: Vec<String> = module
+                .imports
+                .iter()
+                .map(|(target, local_name)| {
+                    format!(
+                        "{} as `{}`",
+                        self.markdown_module_link(index, target),
+                        local_name
+                    )
+                })
+                .collect();
+            out.push_str(&format!("Imports: {}\n\n", imports.join(", ")));
+        }
+
+        if module.properties.is_empty() {
+            return out;
+        }
+        out.push_str("## Properties\n\n");
+
+        for property in module.properties.iter() {
+            let scope = parent(&property.path);
+
+            out.push_str(&format!(
+                "<a id=\"{}\"></a>\n{} `{}`\n\n",
+                property.path,
+                "#".repeat((property.depth + 3).min(6)),
+                property.path
+            ));
+            if let Some(deprecation) = &property.deprecation {
+                out.push_str(&format!("> **{}**\n\n", deprecation));
+            }
+            out.push_str(&code_block(&signature(property)));
+            if let Some(doc) = &property.doc {
+                let doc = link_references(doc, |reference| {
+                    self.resolve(index, scope, reference)
+                        .map(|href| format!("[`{}`]({})", reference, href))
+                });
+                out.push_str(&format!("{}\n\n", doc));
+            }
+
+            let references = self.references(index, property, |reference, href| {
+                format!("[`{}`]({})", reference, href)
+            });
+            if !references.is_empty() {
+                out.push_str(&format!("References: {}\n\n", references.join(", ")));
+            }
+        }
+
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    fn render_html(&self, index: usize) -> String {
+        let module = &self.modules[index];
+        let mut out = html_header(&module.name);
+        out.push_str("<nav><a href=\"index.html\">Index</a></nav>\n");
+        out.push_str(&format!(
+            "<h1>Module <code>{}</code></h1>\n",
+            escape_html(&module.name)
+        ));
+
+        if !module.annotations.is_empty() {
+            out.push_str(&format!(
+                "<pre><code>{}</code></pre>\n",
+                escape_html(&module.annotations.join("\n"))
+            ));
+        }
+        if let Some((keyword, target)) = &module.header {
+            out.push_str(&format!(
+                "<p>{} {}</p>\n",
+                capitalize(keyword),
+                self.html_module_link(index, target)
+            ));
+        }
+        if !module.imports.is_empty() {
+            let imports: Vec<String> = module
+                .imports
+                .iter()
+                .map(|(target, local_name)| {
+                    format!(
+                        "{} as <code>{}</code>",
+                        self.html_module_link(index, target),
+                        escape_html(local_name)
+                    )
+                })
+                .collect();
+            out.push_str(&format!("<p>Imports: {}</p>\n", imports.join(", ")));
+        }
+
+        if !module.properties.is_empty() {
+            out.push_str("<h2>Properties</h2>\n");
+        }
+
+        for property in module.properties.iter() {
+            let scope = parent(&property.path);
+            let level = (property.depth + 3).min(6);
+
+            out.push_str(&format!(
+                "<section id=\"{}\">\n<h{level}><code>{}</code></h{level}>\n",
+                escape_html(&property.path),
+                escape_html(&property.path),
+            ));
+            if let Some(deprecation) = &property.deprecation {
+                out.push_str(&format!(
+                    "<p class=\"deprecated\"><strong>{}</strong></p>\n",
+                    escape_html(deprecation)
+                ));
+            }
+            out.push_str(&format!(
+                "<pre><code>{}</code></pre>\n",
+                escape_html(&signature(property))
+            ));
+            if let Some(doc) = &property.doc {
+                for paragraph in doc.split("\n\n") {
+                    let paragraph = code_spans(&escape_html(paragraph.trim()));
+                    let paragraph = link_references(&paragraph, |reference| {
+                        self.resolve(index, scope, reference).map(|href| {
+                            format!("<a href=\"{}\"><code>{}</code></a>", href, reference)
+                        })
+                    });
+                    out.push_str(&format!("<p>{}</p>\n", paragraph));
+                }
+            }
+
+            let references = self.references(index, property, |reference, href| {
+                format!(
+                    "<a href=\"{}\"><code>{}</code></a>",
+                    escape_html(&href),
+                    escape_html(reference)
+                )
+            });
+            if !references.is_empty() {
+                out.push_str(&format!("<p>References: {}</p>\n", references.join(", ")));
+            }
+            out.push_str("</section>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Returns the links to the resolved references of `property`, each formatted by `link`.
+    fn references(
+        &self,
+        index: usize,
+        property: &PropertyDoc,
+        link: impl Fn(&str, String) -> String,
+    ) -> Vec<String> {
+        let scope = parent(&property.path);
+        let mut links: Vec<String> = Vec::new();
+
+        for reference in property.references.iter() {
+            if let Some(href) = self.resolve(index, scope, reference) {
+                let link = link(reference, href);
+                if !links.contains(&link) {
+                    links.push(link);
+                }
+            }
+        }
+
+        links
+    }
+
+    fn markdown_module_link(&self, index: usize, target: &str) -> String {
+        match self.find_module(target) {
+            Some(to) => format!("[`{}`]({})", target, self.href(index, to, None)),
+            None => format!("`{}`", target),
+        }
+    }
+
+    fn html_module_link(&self, index: usize, target: &str) -> String {
+        match self.find_module(target) {
+            Some(to) => format!(
+                "<a href=\"{}\"><code>{}</code></a>",
+                escape_html(&self.href(index, to, None)),
+                escape_html(target)
+            ),
+            None => format!("<code>{}</code>", escape_html(target)),
+        }
+    }
+}
+
+/// The declaration of a property or an object member.
+struct Property<'s> {
+    doc: Option<&'s str>,
+    modifiers: Modifiers,
+    annotations: Vec<&'s str>,
+}
+
+/// Documents the property at `path` along with its members, unless it is `local`.
+fn document_property(
+    properties: &mut Vec<PropertyDoc>,
+    pkl: &Pkl,
+    source: &str,
+    path: &str,
+    depth: usize,
+    property: Property,
+    expr: &PklExpr,
+) {
+    if property.modifiers.local {
+        return;
+    }
+
+    let value = evaluated(pkl, path);
+    let type_name = match value {
+        Some(PklValue::ClassInstance(class_name, _)) => class_name.to_string(),
+        Some(value) => value.get_type().to_owned(),
+        None => "Unknown".to_owned(),
+    };
+
+    let mut references = Vec::new();
+    collect_references(expr, &mut references);
+
+    properties.push(PropertyDoc {
+        path: path.to_owned(),
+        depth,
+        doc: property.doc.map(str::to_owned),
+        type_name,
+        value: (!is_object(expr)).then(|| source[expr.span()].to_owned()),
+        modifiers: property.modifiers,
+        annotations: property.annotations.iter().map(|a| a.to_string()).collect(),
+        deprecation: deprecation(pkl.annotations_for(path)),
+        references,
+    });
+
+    for (name, member) in members(expr) {
+        let member_property = Property {
+            doc: member.doc.as_deref(),
+            modifiers: member.modifiers,
+            annotations: member
+                .annotations
+                .iter()
+                .map(|a| &source[a.span()])
+                .collect(),
+        };
+        document_property(
+            properties,
+            pkl,
+            source,
+            &format!("{}.{}", path, name),
+            depth + 1,
+            member_property,
+            &member.value,
+        );
+    }
+}
+
+/// Returns the evaluated value of the property at the dotted `path`.
+fn evaluated<'p, 'a>(pkl: &'p Pkl<'a>, path: &str) -> Option<&'p PklValue<'a>> {
+    let mut segments = path.split('.');
+    let first = segments.next()?;
+    let mut value = pkl.table.variables.get(first)?;
+
+    for segment in segments {
+        value = match value {
+            PklValue::Object(object) | PklValue::ClassInstance(_, object) => object.get(segment)?,
+            _ => return None,
+        };
+    }
+
+    Some(value)
+}
+
+/// Returns the message shown for a property annotated with `@Deprecated`, if it is.
+fn deprecation(annotations: &[PklValue]) -> Option<String> {
+    let properties = annotations.iter().find_map(|annotation| match annotation {
+        PklValue::ClassInstance(class_name, properties) if class_name == "Deprecated" => {
+            Some(properties)
+        }
+        _ => None,
+    })?;
+
+    let mut message = String::from("Deprecated");
+    if let Some(PklValue::String(text)) = properties.get("message") {
+        message.push_str(&format!(": {}", text));
+    }
+    if let Some(PklValue::String(replacement)) = properties.get("replaceWith") {
+        message.push_str(&format!(" (replace it with `{}`)", replacement));
+    }
+
+    Some(message)
+}
+
+fn is_object(expr: &PklExpr) -> bool {
+    matches!(
+        expr,
+        PklExpr::Value(
+            AstPklValue::Object(_)
+                | AstPklValue::ClassInstance(_, _, _)
+                | AstPklValue::AmendingObject(_, _, _)
+                | AstPklValue::AmendedObject(_, _, _)
+        )
+    )
+}
+
+/// Returns the members of an object expression in declaration order,
+/// the members of amended objects being overridden by the later ones.
+fn members<'e, 'a>(expr: &'e PklExpr<'a>) -> Vec<(&'a str, &'e ObjectMember<'a>)> {
+    let mut members: Vec<(&'a str, &'e ObjectMember<'a>)> = match expr {
+        PklExpr::Value(AstPklValue::AmendedObject(base, (hash, _), _)) => {
+            let mut members = self::members(base);
+            members.retain(|(name, _)| !hash.contains_key(name));
+            members.extend(hash.iter().map(|(name, member)| (*name, member)));
+            members
+        }
+        PklExpr::Value(
+            AstPklValue::Object((hash, _))
+            | AstPklValue::ClassInstance(_, (hash, _), _)
+            | AstPklValue::AmendingObject(_, (hash, _), _),
+        ) => hash.iter().map(|(name, member)| (*name, member)).collect(),
+        _ => Vec::new(),
+    };
+
+    members.sort_by_key(|(_, member)| member.value.span().start);
+    members
+}
+
+/// Collects the dotted paths referenced by `expr`, e.g. `bird.name` for `bird.name.length`.
+fn collect_references(expr: &PklExpr, references: &mut Vec<String>) {
+    if let Some(path) = dotted_path(expr) {
+        references.push(path);
+        return;
+    }
+
+    match expr {
+        PklExpr::Identifier(_) => {}
+        PklExpr::MemberExpression(base, member, _) => {
+            collect_references(base, references);
+            if let ExprMember::FuncCall(call) = member {
+                for arg in call.1.iter() {
+                    collect_references(arg, references);
+                }
+            }
+        }
+        PklExpr::FuncCall(call) => {
+            for arg in call.1.iter() {
+                collect_references(arg, references);
+            }
+        }
+        PklExpr::Value(AstPklValue::List(values, _)) => {
+            for value in values {
+                collect_references(value, references);
+            }
+        }
+        PklExpr::Value(AstPklValue::InterpolatedString(parts, _)) => {
+            for part in parts {
+                if let StringPart::Expr(expr, _) = part {
+                    collect_references(expr, references);
+                }
+            }
+        }
+        PklExpr::Value(AstPklValue::AmendingObject(base, _, _)) => {
+            collect_references(base, references)
+        }
+        PklExpr::Value(_) => {}
+    }
+}
+
+/// Returns the dotted path of an expression made of identifiers only, e.g. `bird.name`.
+fn dotted_path(expr: &PklExpr) -> Option<String> {
+    match expr {
+        PklExpr::Identifier(identifier) => Some(identifier.value().to_owned()),
+        PklExpr::MemberExpression(base, ExprMember::Identifier(identifier), _) => {
+            Some(format!("{}.{}", dotted_path(base)?, identifier.value()))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the declaration of a property, e.g. `fixed name: String = "Pigeon"`.
+fn signature(property: &PropertyDoc) -> String {
+    let mut out = String::new();
+    for annotation in property.annotations.iter() {
+        out.push_str(annotation);
+        out.push('\n');
+    }
+
+    let modifiers = &property.modifiers;
+    for (set, keyword) in [
+        (modifiers.hidden, "hidden"),
+        (modifiers.fixed, "fixed"),
+        (modifiers.constant, "const"),
+    ] {
+        if set {
+            out.push_str(keyword);
+            out.push(' ');
+        }
+    }
+
+    let name = property.path.rsplit('.').next().unwrap_or(&property.path);
+    out.push_str(&format!("{}: {}", name, property.type_name));
+    if let Some(value) = &property.value {
+        out.push_str(&format!(" = {}", value));
+    }
+
+    out
+}
+
+/// Replaces the `[path]` references of a doc comment by the links returned by `link`,
+/// leaving Markdown links and unresolved references untouched.
+fn link_references(text: &str, link: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        out.push_str(&rest[..open]);
+        rest = &rest[open..];
+
+        let replacement = rest[1..].find(']').and_then(|close| {
+            let reference = &rest[1..close + 1];
+            let after = &rest[close + 2..];
+            let is_path = !reference.is_empty()
+                && reference
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '.');
+
+            if !is_path || after.starts_with('(') || after.starts_with('[') {
+                return None;
+            }
+            link(reference).map(|link| (link, close + 2))
+        });
+
+        match replacement {
+            Some((link, len)) => {
+                out.push_str(&link);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('[');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Converts the `code` spans of an escaped text into `<code>` elements,
+/// an unclosed span keeping its backtick.
+fn code_spans(text: &str) -> String {
+    let parts: Vec<&str> = text.split('`').collect();
+    let mut out = String::new();
+
+    for (i, part) in parts.iter().enumerate() {
+        if i % 2 == 0 {
+            out.push_str(part);
+        } else if i + 1 < parts.len() {
+            out.push_str(&format!("<code>{}</code>", part));
+        } else {
+            out.push('`');
+            out.push_str(part);
+        }
+    }
+
+    out
+}
+
+/// Returns a fenced Pkl code block, fenced by more backticks than `code` contains in a row.
+fn code_block(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+
+    format!("{fence}pkl\n{}\n{fence}\n\n", code)
+}
+
+fn html_header(title: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}</style>\n</head>\n<body>\n",
+        escape_html(title),
+        STYLE
+    )
+}
+
+const STYLE: &str =
+    "body { font-family: sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }
+pre { background: #f5f5f5; padding: 0.5rem; overflow-x: auto; }
+section { border-top: 1px solid #ddd; }
+.deprecated { color: #a15c00; }
+";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Returns the path of the object containing the property at `path`, empty for top-level properties.
+fn parent(path: &str) -> &str {
+    path.rsplit_once('.').map_or("", |(parent, _)| parent)
+}
+
+/// Normalizes a module path for comparisons, dropping `.` components.
+fn normalize(path: &str) -> PathBuf {
+    Path::new(path)
+        .components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIRDS: &str = "module birds\n\n/// A <b>bird</b> & its [bird.name].\nbird {\n  /// Named \"<script>\".\n  name = \"<Pigeon>\"\n}\nlocal secret = 1\n";
+
+    fn pages(format: DocFormat) -> Vec<DocPage> {
+        let mut generator = DocGenerator::new(format);
+        generator.add_module("birds.pkl", BIRDS).unwrap();
+
+        return generator.generate();
+    }
+
+    #[test]
+    fn html_pages_escape_docs_and_values() {
+        let pages = pages(DocFormat::Html);
+        let paths: Vec<_> = pages.iter().map(|page| page.path.as_str()).collect();
+        assert_eq!(paths, ["index.html", "birds.html"]);
+
+        let birds = &pages[1].content;
+        assert!(birds.contains(
+            "<p>A &lt;b&gt;bird&lt;/b&gt; &amp; its <a href=\"#bird.name\"><code>bird.name</code></a>.</p>"
+        ), "{birds}");
+        assert!(
+            birds.contains("<pre><code>name: String = &quot;&lt;Pigeon&gt;&quot;</code></pre>"),
+            "{birds}"
+        );
+        assert!(
+            birds.contains("<p>Named &quot;&lt;script&gt;&quot;.</p>"),
+            "{birds}"
+        );
+        assert!(
+            !birds.contains("<script>") && !birds.contains("secret"),
+            "{birds}"
+        );
+    }
+
+    #[test]
+    fn markdown_pages_link_references() {
+        let pages = pages(DocFormat::Markdown);
+
+        assert!(pages[0]
+            .content
+            .contains("- [`birds`](birds.md): `birds.pkl`"));
+        assert!(pages[1].content.contains("its [`bird.name`](#bird.name)."));
+    }
+}
@@ -0,0 +1,518 @@
+use crate::{
+    cst::{parse_cst, AnnotationTracker, CstElement, CstNode, CstNodeKind},
+    lexer::PklToken,
+    parser::{parse_pkl_recovering, PklResult},
+    PklError,
+};
+use logos::Logos;
+use std::{fmt, ops::Range};
+
+/// Indentation used for new object bodies when it cannot be inferred from the source.
+const DEFAULT_INDENT: &str = "  ";
+
+/// Edits the properties of a Pkl source, writing the changes back into its text.
+///
+/// Edits only rewrite the text of the property they target,
+/// comments and formatting elsewhere are left untouched:
+///
+/// ```ignore
+/// let mut editor = PklEditor::new(source)?;
+/// editor.set("package.version", "\"1.2.4\"")?;
+/// std::fs::write(path, editor.source())?;
+/// ```
+///
+/// Values are given as Pkl source, an edit producing invalid Pkl is rejected
+/// and leaves the source unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PklEditor {
+    source: String,
+}
+
+/// A replacement of a range of the source.
+struct Edit {
+    range: Range<usize>,
+    text: String,
+}
+
+impl PklEditor {
+    /// Creates an editor for `source`, which must not contain syntax errors.
+    pub fn new(source: impl Into<String>) -> PklResult<Self> {
+        let source = source.into();
+        check_source(&source)?;
+
+        Ok(Self { source })
+    }
+
+    /// Returns the edited source.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn into_source(self) -> String {
+        self.source
+    }
+
+    /// Returns the source of the value of the property at the dotted `path`, if any.
+    ///
+    /// Properties defined by an object body, such as `bird { ... }`, return the body.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        let cst = parse_cst(&self.source);
+        let node = find(&cst.root, &segments(path))?;
+        let range = value_range(node)?;
+
+        Some(&self.source[range])
+    }
+
+    /// Sets the property at the dotted `path` to `value`, given as Pkl source,
+    /// replacing its current value or inserting it if it does not exist.
+    pub fn set(&mut self, path: &str, value: &str) -> PklResult<()> {
+        let cst = parse_cst(&self.source);
+        let segments = segments(path);
+
+        let edit = match find(&cst.root, &segments) {
+            Some(node) => replace_value(node, value),
+            None => insert_member(&self.source, &cst.root, path, &segments, value)?,
+        };
+
+        self.apply(edit)
+    }
+
+    /// Inserts a property at the dotted `path`, creating the missing parent objects.
+    ///
+    /// Fails if the property already exists.
+    pub fn insert(&mut self, path: &str, value: &str) -> PklResult<()> {
+        let cst = parse_cst(&self.source);
+        let segments = segments(path);
+
+        if let Some(node) = find(&cst.root, &segments) {
+            return Err(PklError::constraint_violation(
+                format!("Property `{}` already exists", path),
+                node.span.clone(),
+            )
+            .with_source(None, &self.source));
+        }
+
+        let edit = insert_member(&self.source, &cst.root, path, &segments, value)?;
+        self.apply(edit)
+    }
+
+    /// Removes the property at the dotted `path`, along with its doc comments
+    /// and the lines it occupied.
+    pub fn remove(&mut self, path: &str) -> PklResult<()> {
+        let cst = parse_cst(&self.source);
+        let segments = segments(path);
+        let Some(node) = find(&cst.root, &segments) else {
+            // the nearest existing parent, or the whole module
+            let parent = (1..segments.len())
+                .rev()
+                .find_map(|depth| find(&cst.root, &segments[..depth]))
+                .unwrap_or(&cst.root);
+
+            return Err(PklError::unknown_variable(
+                format!("Property `{}` not found", path),
+                parent.span.clone(),
+            )
+            .with_source(None, &self.source));
+        };
+
+        let edit = Edit {
+            range: removal_range(&self.source, node.span.clone()),
+            text: String::new(),
+        };
+        self.apply(edit)
+    }
+
+    /// Applies `edit`, unless the edited source would be invalid.
+    fn apply(&mut self, edit: Edit) -> PklResult<()> {
+        let mut source = self.source.clone();
+        source.replace_range(edit.range, &edit.text);
+
+        check_source(&source)?;
+        self.source = source;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for PklEditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+fn check_source(source: &str) -> PklResult<()> {
+    let (_, errors) = parse_pkl_recovering(&mut PklToken::lexer(source));
+
+    match errors.into_iter().next() {
+        Some(e) => Err(e.with_source(None, source)),
+        None => Ok(()),
+    }
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    path.split('.').collect()
+}
+
+/// Finds the node defining the property at `segments`, the last one if it is defined several times.
+fn find<'a, 'n>(root: &'n CstNode<'a>, segments: &[&str]) -> Option<&'n CstNode<'a>> {
+    let (first, rest) = segments.split_first()?;
+
+    let mut node = root
+        .nodes()
+        .filter(|node| node.kind == CstNodeKind::Property(first))
+        .last()?;
+
+    for segment in rest {
+        node = find_member(node.body()?, segment)?;
+    }
+
+    Some(node)
+}
+
+fn find_member<'a, 'n>(body: &'n CstNode<'a>, name: &str) -> Option<&'n CstNode<'a>> {
+    body.nodes()
+        .filter(|node| node.kind == CstNodeKind::Member(Some(name)))
+        .last()
+}
+
+/// Returns the range going from the first to the last element of the value of a property,
+/// without the trivia around it.
+fn value_range(node: &CstNode) -> Option<Range<usize>> {
+    let name_index = name_index(node)?;
+    let mut elements = node.children[name_index + 1..]
+        .iter()
+        .filter(|element| !is_trivia(element));
+
+    let first = match elements.next()? {
+        CstElement::Token(token) if token.token == Some(PklToken::EqualSign) => elements.next()?,
+        element => element,
+    };
+    let last = elements.next_back().unwrap_or(first);
+
+    Some(first.span().start..last.span().end)
+}
+
+/// Returns the index of the name of a property, after its annotations.
+fn name_index(node: &CstNode) -> Option<usize> {
+    let mut annotations = AnnotationTracker::default();
+
+    node.children.iter().position(|element| {
+        let is_annotation = annotations.visit(element);
+
+        !is_annotation
+            && matches!(
+                element,
+                CstElement::Token(token) if matches!(
+                    token.token,
+                    Some(PklToken::Identifier(_)) | Some(PklToken::IllegalIdentifier(_))
+                )
+            )
+    })
+}
+
+fn is_trivia(element: &CstElement) -> bool {
+    match element {
+        CstElement::Token(token) => token.is_trivia(),
+        CstElement::Node(_) => false,
+    }
+}
+
+fn replace_value(node: &CstNode, value: &str) -> Edit {
+    let name_end = name_index(node).map_or(node.span.end, |i| node.children[i].span().end);
+    let has_equal_sign = node.children.iter().any(|element| {
+        matches!(element, CstElement::Token(token) if token.token == Some(PklToken::EqualSign))
+    });
+
+    match value_range(node) {
+        // keeps the spacing around the equal sign
+        Some(range) if has_equal_sign => Edit {
+            range,
+            text: value.to_owned(),
+        },
+        // `name { ... }` becomes `name = value`
+        Some(range) => Edit {
+            range: name_end..range.end,
+            text: format!(" = {}", value),
+        },
+        None => Edit {
+            range: name_end..name_end,
+            text: format!(" = {}", value),
+        },
+    }
+}
+
+/// Inserts the property at `segments` into the deepest existing object of its path.
+fn insert_member(
+    source: &str,
+    root: &CstNode,
+    path: &str,
+    segments: &[&str],
+    value: &str,
+) -> PklResult<Edit> {
+    // the deepest existing parent
+    let mut depth = 0;
+    let mut parent = None;
+    while depth + 1 < segments.len() {
+        match find(root, &segments[..=depth]) {
+            Some(node) => parent = Some(node),
+            None => break,
+        }
+        depth += 1;
+    }
+
+    let Some(parent) = parent else {
+        // appended at the end of the module
+        let text = member_text(&segments[depth..], value, "", DEFAULT_INDENT);
+        let end = source.len();
+        let separator = if source.is_empty() || source.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+
+        return Ok(Edit {
+            range: end..end,
+            text: format!("{}{}\n", separator, text),
+        });
+    };
+
+    let Some(body) = parent.body() else {
+        return Err(PklError::type_mismatch(
+            format!(
+                "Cannot insert `{}`, `{}` is not an object",
+                path,
+                segments[..depth].join(".")
+            ),
+            parent.span.clone(),
+        ));
+    };
+
+    let open_brace = body.span.start;
+    let close_brace = body.span.end - 1;
+    let brace_indent = line_indent(source, open_brace);
+
+    let last_member = body.nodes().last();
+    let indent = match last_member {
+        Some(member) if line_start(source, member.span.start) != line_start(source, open_brace) => {
+            line_indent(source, member.span.start).to_owned()
+        }
+        _ => format!("{}{}", brace_indent, indent_unit(brace_indent)),
+    };
+    let unit = indent
+        .strip_prefix(brace_indent)
+        .filter(|unit| !unit.is_empty())
+        .unwrap_or(DEFAULT_INDENT);
+
+    let text = member_text(&segments[depth..], value, &indent, unit);
+    let close_line = line_start(source, close_brace);
+
+    if source[close_line..close_brace].trim().is_empty() && close_line > open_brace {
+        // the closing brace is on its own line
+        Ok(Edit {
+            range: close_line..close_line,
+            text: format!("{}{}\n", indent, text),
+        })
+    } else {
+        // `{}` or `{ a = 1 }`, the body is split over several lines, one member per line
+        let mut lines = expanded_members(source, body, &indent);
+        lines.push_str(&format!("\n{}{}\n{}", indent, text, brace_indent));
+
+        Ok(Edit {
+            range: open_brace + 1..close_brace,
+            text: lines,
+        })
+    }
+}
+
+/// Returns the members and comments of a one-line object body, each member on its own line
+/// indented by `indent`, the comments following a member staying on its line.
+fn expanded_members(source: &str, body: &CstNode, indent: &str) -> String {
+    let mut out = String::new();
+
+    for element in body.children.iter() {
+        match element {
+            CstElement::Node(member) => {
+                out.push_str(&format!(
+                    "\n{}{}",
+                    indent,
+                    source[member.span.clone()].trim()
+                ));
+            }
+            CstElement::Token(token) if token.is_trivia() && !token.text.trim().is_empty() => {
+                match out.is_empty() {
+                    true => out.push_str(&format!("\n{}", indent)),
+                    false => out.push(' '),
+                }
+                out.push_str(token.text.trim());
+            }
+            CstElement::Token(_) => {}
+        }
+    }
+
+    out
+}
+
+/// Returns the source of a member, nested in objects for each of the leading `segments`.
+fn member_text(segments: &[&str], value: &str, indent: &str, unit: &str) -> String {
+    match segments {
+        [name] => format!("{} = {}", name, value),
+        [name, rest @ ..] => {
+            let inner_indent = format!("{}{}", indent, unit);
+            format!(
+                "{} {{\n{}{}\n{}}}",
+                name,
+                inner_indent,
+                member_text(rest, value, &inner_indent, unit),
+                indent
+            )
+        }
+        [] => value.to_owned(),
+    }
+}
+
+/// Extends the span of a removed property to its whole lines,
+/// including its doc comments, when it does not share them with other properties.
+fn removal_range(source: &str, span: Range<usize>) -> Range<usize> {
+    let mut start = line_start(source, span.start);
+    if !source[start..span.start].trim().is_empty() {
+        return span;
+    }
+
+    let line_end = source[span.end..]
+        .find('\n')
+        .map_or(source.len(), |i| span.end + i + 1);
+    if !is_comment_only(&source[span.end..line_end]) {
+        return span;
+    }
+
+    while start > 0 {
+        let previous = line_start(source, start - 1);
+        if !source[previous..start].trim_start().starts_with("///") {
+            break;
+        }
+        start = previous;
+    }
+
+    start..line_end
+}
+
+/// Whether `text` only contains whitespace and comments, e.g. the end of a line after a property.
+fn is_comment_only(mut text: &str) -> bool {
+    loop {
+        text = text.trim_start();
+        if text.is_empty() || text.starts_with("//") {
+            return true;
+        }
+
+        match text
+            .strip_prefix("/*")
+            .and_then(|rest| rest.split_once("*/"))
+        {
+            Some((_, rest)) => text = rest,
+            None => return false,
+        }
+    }
+}
+
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Returns the leading whitespace of the line containing `offset`.
+fn line_indent(source: &str, offset: usize) -> &str {
+    let start = line_start(source, offset);
+    let line = &source[start..];
+    let end = line
+        .find(|c: char| c != ' ' && c != '\t')
+        .unwrap_or(line.len());
+
+    &line[..end]
+}
+
+fn indent_unit(indent: &str) -> &'static str {
+    if indent.starts_with('\t') {
+        "\t"
+    } else {
+        DEFAULT_INDENT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edited(source: &str, edit: impl FnOnce(&mut PklEditor) -> PklResult<()>) -> String {
+        let mut editor = PklEditor::new(source).unwrap();
+        edit(&mut editor).unwrap();
+        editor.into_source()
+    }
+
+    #[test]
+    fn set_replaces_values() {
+        assert_eq!(
+            edited("a = 1 // one\nb = 2\n", |e| e.set("a", "3")),
+            "a = 3 // one\nb = 2\n"
+        );
+        assert_eq!(
+            edited("a {\n  b {\n    c = 1\n  }\n}\n", |e| e.set("a.b.c", "2")),
+            "a {\n  b {\n    c = 2\n  }\n}\n"
+        );
+        assert_eq!(
+            edited("a { b = 1 }\n", |e| e.set("a.b", "2")),
+            "a { b = 2 }\n"
+        );
+    }
+
+    #[test]
+    fn insert_adds_members() {
+        assert_eq!(edited("a = 1\n", |e| e.insert("b", "2")), "a = 1\nb = 2\n");
+        assert_eq!(
+            edited("a {\n  b = 1\n}\n", |e| e.insert("a.c", "2")),
+            "a {\n  b = 1\n  c = 2\n}\n"
+        );
+        assert_eq!(
+            edited("a { b = 1 }\n", |e| e.insert("a.c", "2")),
+            "a {\n  b = 1\n  c = 2\n}\n"
+        );
+        assert_eq!(
+            edited("a { b = 1 /* one */ }\n", |e| e.insert("a.c", "2")),
+            "a {\n  b = 1 /* one */\n  c = 2\n}\n"
+        );
+    }
+
+    #[test]
+    fn remove_drops_members_and_their_comments() {
+        assert_eq!(
+            edited("/// doc\na = 1\nb = 2\n", |e| e.remove("a")),
+            "b = 2\n"
+        );
+        assert_eq!(
+            edited("a = 1 /* x */ \nb = 2\n", |e| e.remove("a")),
+            "b = 2\n"
+        );
+        assert_eq!(
+            edited("a {\n  b = 1 // one\n  c = 2\n}\n", |e| e.remove("a.b")),
+            "a {\n  c = 2\n}\n"
+        );
+        assert_eq!(
+            edited("a { b = 1 }\nc = 2\n", |e| e.remove("a.b")),
+            "a { }\nc = 2\n"
+        );
+    }
+
+    #[test]
+    fn errors_point_at_the_nearest_parent() {
+        let source = "a = 1\nb {\n  c = 2\n}\n";
+        let mut editor = PklEditor::new(source).unwrap();
+
+        let e = editor.remove("b.d").unwrap_err();
+        assert_eq!(&source[e.span()], "b {\n  c = 2\n}");
+        assert_eq!((e.line(), e.column()), (Some(2), Some(1)));
+
+        let e = editor.insert("b.c", "3").unwrap_err();
+        assert_eq!(&source[e.span()], "c = 2");
+        assert_eq!((e.line(), e.column()), (Some(3), Some(3)));
+
+        assert_eq!(editor.source(), source);
+    }
+}
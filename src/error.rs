@@ -0,0 +1,343 @@
+use logos::Span;
+use std::{error::Error, fmt, sync::Arc};
+
+/// The details shared by all kinds of [`PklError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorInfo {
+    /// A message describing the error.
+    pub message: String,
+    /// The span in the source where the error occurred, in bytes.
+    pub span: Span,
+    /// The name of the source the span refers to, usually a file path, if known.
+    pub source_name: Option<String>,
+    /// The text of the source the span refers to, once known,
+    /// so that errors from imported modules are rendered against their own source.
+    pub source: Option<Arc<str>>,
+    /// The line and column, both starting at 1, of the start of the span,
+    /// computed once the source is known.
+    pub location: Option<(usize, usize)>,
+    /// Secondary spans related to the error, such as where a conflicting property was declared.
+    pub labels: Vec<Label>,
+    /// Notes helping to fix the error.
+    pub help: Vec<String>,
+}
+
+/// A secondary span related to an error, with its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// The span the label refers to, in bytes.
+    pub span: Span,
+    pub message: String,
+    /// The name of the source the span refers to, if it is not the source of the error.
+    pub source_name: Option<String>,
+    /// The text of the source the span refers to, `None` for the source of the error.
+    pub source: Option<Arc<str>>,
+}
+
+/// An error that occurred while parsing or evaluating Pkl.
+///
+/// Its details are boxed to keep results small.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PklError {
+    /// The source contains an invalid token, such as an unterminated string.
+    Lexing(Box<ErrorInfo>),
+    /// The tokens do not form valid Pkl.
+    Syntax(Box<ErrorInfo>),
+    /// A variable or property that does not exist is referenced.
+    UnknownVariable(Box<ErrorInfo>),
+    /// A value is not of the expected type.
+    TypeMismatch(Box<ErrorInfo>),
+    /// A module could not be imported.
+    Import(Box<ErrorInfo>),
+    /// A value breaks a constraint, such as overriding a `const` or `fixed` property.
+    ConstraintViolation(Box<ErrorInfo>),
+    /// Any other error occurring while evaluating an expression.
+    Evaluation(Box<ErrorInfo>),
+    /// An error whose kind is not known, such as one converted from a `(String, Span)` tuple.
+    Other(Box<ErrorInfo>),
+}
+
+impl PklError {
+    pub fn lexing(message: impl Into<String>, span: Span) -> Self {
+        PklError::Lexing(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn syntax(message: impl Into<String>, span: Span) -> Self {
+        PklError::Syntax(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn unknown_variable(message: impl Into<String>, span: Span) -> Self {
+        PklError::UnknownVariable(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn type_mismatch(message: impl Into<String>, span: Span) -> Self {
+        PklError::TypeMismatch(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn import(message: impl Into<String>, span: Span) -> Self {
+        PklError::Import(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn constraint_violation(message: impl Into<String>, span: Span) -> Self {
+        PklError::ConstraintViolation(Box::new(ErrorInfo::new(message, span)))
+    }
+    pub fn evaluation(message: impl Into<String>, span: Span) -> Self {
+        PklError::Evaluation(Box::new(ErrorInfo::new(message, span)))
+    }
+
+    pub fn info(&self) -> &ErrorInfo {
+        match self {
+            PklError::Lexing(info)
+            | PklError::Syntax(info)
+            | PklError::UnknownVariable(info)
+            | PklError::TypeMismatch(info)
+            | PklError::Import(info)
+            | PklError::ConstraintViolation(info)
+            | PklError::Evaluation(info)
+            | PklError::Other(info) => info,
+        }
+    }
+
+    pub fn info_mut(&mut self) -> &mut ErrorInfo {
+        match self {
+            PklError::Lexing(info)
+            | PklError::Syntax(info)
+            | PklError::UnknownVariable(info)
+            | PklError::TypeMismatch(info)
+            | PklError::Import(info)
+            | PklError::ConstraintViolation(info)
+            | PklError::Evaluation(info)
+            | PklError::Other(info) => info,
+        }
+    }
+
+    /// Returns the name of the error kind, such as `"Syntax"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PklError::Lexing(_) => "Lexing",
+            PklError::Syntax(_) => "Syntax",
+            PklError::UnknownVariable(_) => "UnknownVariable",
+            PklError::TypeMismatch(_) => "TypeMismatch",
+            PklError::Import(_) => "Import",
+            PklError::ConstraintViolation(_) => "ConstraintViolation",
+            PklError::Evaluation(_) => "Evaluation",
+            PklError::Other(_) => "Other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.info().message
+    }
+
+    pub fn span(&self) -> Span {
+        self.info().span.clone()
+    }
+
+    pub fn source_name(&self) -> Option<&str> {
+        self.info().source_name.as_deref()
+    }
+
+    /// Returns the line, starting at 1, of the start of the error span, if the source is known.
+    pub fn line(&self) -> Option<usize> {
+        self.info().location.map(|(line, _)| line)
+    }
+
+    /// Returns the column, starting at 1, of the start of the error span, if the source is known.
+    pub fn column(&self) -> Option<usize> {
+        self.info().location.map(|(_, column)| column)
+    }
+
+    /// Adds a secondary span related to the error, such as where a conflicting property was declared.
+    pub fn with_label(mut self, span: Span, message: impl Into<String>) -> Self {
+        self.info_mut().labels.push(Label {
+            span,
+            message: message.into(),
+            source_name: None,
+            source: None,
+        });
+        self
+    }
+
+    /// Adds a secondary span of another source than the error's, named `name`,
+    /// such as where a module importing the erroneous one imports it.
+    pub fn with_label_in(
+        mut self,
+        name: Option<&str>,
+        source: Arc<str>,
+        span: Span,
+        message: impl Into<String>,
+    ) -> Self {
+        self.info_mut().labels.push(Label {
+            span,
+            message: message.into(),
+            source_name: name.map(str::to_owned),
+            source: Some(source),
+        });
+        self
+    }
+
+    /// Adds a note helping to fix the error.
+    pub fn with_help(mut self, message: impl Into<String>) -> Self {
+        self.info_mut().help.push(message.into());
+        self
+    }
+
+    /// Moves the error to `span`, such as the import failed by an error of a [`crate::ModuleResolver`].
+    pub(crate) fn with_span(mut self, span: Span) -> Self {
+        self.info_mut().span = span;
+        self
+    }
+
+    /// Attaches the source the error span refers to, computing its line and column.
+    ///
+    /// Does nothing if the error already refers to a source,
+    /// for instance when it occurred in an imported module.
+    pub fn with_source(mut self, name: Option<&str>, source: &str) -> Self {
+        self.info_mut().attach_source(name, || source.into());
+        self
+    }
+}
+
+impl ErrorInfo {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            source_name: None,
+            source: None,
+            location: None,
+            labels: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    /// Attaches the source the span refers to, unless it already refers to one.
+    ///
+    /// `source` is only called if the source is attached, letting callers share it between errors.
+    pub(crate) fn attach_source(&mut self, name: Option<&str>, source: impl FnOnce() -> Arc<str>) {
+        if self.location.is_none() && self.source_name.is_none() {
+            let source = source();
+            self.source_name = name.map(|name| name.to_owned());
+            self.location = Some(line_col(&source, self.span.start));
+            self.source = Some(source);
+        }
+    }
+}
+
+/// Computes the line and column, both starting at 1, of the byte `offset` in `source`.
+///
+/// Columns are counted in characters.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(source.len());
+    while !source.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    (line, column)
+}
+
+/// Writes the message, preceded by the source name, line and column it was found at when known,
+/// as `name:line:column: message`.
+impl fmt::Display for ErrorInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.source_name, self.location) {
+            (Some(name), Some((line, column))) => {
+                write!(f, "{}:{}:{}: {}", name, line, column, self.message)
+            }
+            (None, Some((line, column))) => write!(f, "{}:{}: {}", line, column, self.message),
+            _ => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl fmt::Display for PklError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info())
+    }
+}
+
+impl Error for PklError {}
+
+/// A non-fatal issue found while evaluating Pkl.
+///
+/// Its details are boxed to keep it small, as for [`PklError`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PklWarning {
+    /// A property or object member annotated with `@Deprecated` was read.
+    Deprecated(Box<ErrorInfo>),
+}
+
+impl PklWarning {
+    pub fn deprecated(message: impl Into<String>, span: Span) -> Self {
+        PklWarning::Deprecated(Box::new(ErrorInfo::new(message, span)))
+    }
+
+    pub fn info(&self) -> &ErrorInfo {
+        match self {
+            PklWarning::Deprecated(info) => info,
+        }
+    }
+
+    pub fn info_mut(&mut self) -> &mut ErrorInfo {
+        match self {
+            PklWarning::Deprecated(info) => info,
+        }
+    }
+
+    /// Returns the name of the warning kind, such as `"Deprecated"`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PklWarning::Deprecated(_) => "Deprecated",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.info().message
+    }
+
+    pub fn span(&self) -> Span {
+        self.info().span.clone()
+    }
+
+    /// Adds a note helping to fix the warning.
+    pub fn with_help(mut self, message: impl Into<String>) -> Self {
+        self.info_mut().help.push(message.into());
+        self
+    }
+
+    /// Attaches the source the warning span refers to, see [`PklError::with_source`].
+    pub fn with_source(mut self, name: Option<&str>, source: &str) -> Self {
+        self.info_mut().attach_source(name, || source.into());
+        self
+    }
+}
+
+impl fmt::Display for PklWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info())
+    }
+}
+
+impl From<(String, Span)> for PklError {
+    fn from((message, span): (String, Span)) -> Self {
+        PklError::Other(Box::new(ErrorInfo::new(message, span)))
+    }
+}
+
+/// Conversion for callers still handling errors as `(message, span)` tuples.
+impl From<PklError> for (String, Span) {
+    fn from(error: PklError) -> Self {
+        let ErrorInfo { message, span, .. } = match error {
+            PklError::Lexing(info)
+            | PklError::Syntax(info)
+            | PklError::UnknownVariable(info)
+            | PklError::TypeMismatch(info)
+            | PklError::Import(info)
+            | PklError::ConstraintViolation(info)
+            | PklError::Evaluation(info)
+            | PklError::Other(info) => *info,
+        };
+
+        (message, span)
+    }
+}
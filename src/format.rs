@@ -0,0 +1,367 @@
+use crate::{
+    cst::{parse_cst, AnnotationTracker, CstElement, CstNode, CstNodeKind, CstToken},
+    lexer::PklToken,
+    parser::{parse_pkl_recovering, PklResult},
+};
+use logos::Logos;
+
+/// Indentation of each level of object bodies.
+const INDENT: &str = "  ";
+
+/// Formats a Pkl source in the canonical style:
+///
+/// - object bodies are indented by two spaces, with one member per line
+/// - tokens are separated by single spaces, `=` always being surrounded by spaces
+/// - top-level statements are separated by a blank line when they span several lines
+///   or belong to different groups (module clause, `amends`/`extends`, imports, properties),
+///   other blank lines being collapsed into one
+/// - comments are kept where they are, strings are kept as written
+///
+/// Formatting its own output leaves it unchanged.
+/// Sources containing syntax errors are not formatted, their first error is returned instead.
+pub fn format_pkl(source: &str) -> PklResult<String> {
+    let (_, errors) = parse_pkl_recovering(&mut PklToken::lexer(source));
+    if let Some(e) = errors.into_iter().next() {
+        return Err(e);
+    }
+
+    let cst = parse_cst(source);
+    return Ok(format_module(&cst.root));
+}
+
+/// A line-level element of a module or an object body.
+enum Item<'a> {
+    Comment(&'a CstToken<'a>),
+    Statement(&'a CstNode<'a>, String),
+}
+
+fn format_module(root: &CstNode) -> String {
+    let mut out = String::new();
+    let mut previous: Option<Item> = None;
+    let mut newlines = 0;
+    let mut leading_comments = String::new();
+
+    for (i, element) in root.children.iter().enumerate() {
+        let item = match element {
+            CstElement::Token(token) if is_leading_comment(token, &root.children[i + 1..]) => {
+                leading_comments.push_str(token.text.trim_end());
+                leading_comments.push(' ');
+                continue;
+            }
+            CstElement::Token(token) if is_comment(token) => Item::Comment(token),
+            CstElement::Token(token) => {
+                if token.is_newline() {
+                    newlines += 1;
+                }
+                continue;
+            }
+            CstElement::Node(node) => {
+                let text = format_inline(&node.children, 0);
+                Item::Statement(node, std::mem::take(&mut leading_comments) + &text)
+            }
+        };
+
+        match (&previous, &item) {
+            (None, _) => {}
+            // a comment on the same line as the previous item
+            (Some(_), Item::Comment(comment)) if newlines == 0 => {
+                out.push(' ');
+                out.push_str(comment.text.trim_end());
+                continue;
+            }
+            (Some(previous), _) => {
+                let next_statement = root.children[i..].iter().find_map(|element| match element {
+                    CstElement::Node(node) => Some(node),
+                    CstElement::Token(_) => None,
+                });
+                out.push('\n');
+                if needs_blank_line(previous, &item, next_statement, newlines) {
+                    out.push('\n');
+                }
+            }
+        }
+
+        match &item {
+            Item::Comment(comment) => out.push_str(comment.text.trim_end()),
+            Item::Statement(_, text) => out.push_str(text),
+        }
+        previous = Some(item);
+        newlines = 0;
+    }
+
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Whether a blank line separates two top-level items,
+/// `next_statement` being the statement following `next` if it is a comment.
+fn needs_blank_line(
+    previous: &Item,
+    next: &Item,
+    next_statement: Option<&CstNode>,
+    newlines: usize,
+) -> bool {
+    let had_blank_line = newlines > 1;
+
+    match (previous, next) {
+        // a blank line detaches doc comments from the statement they precede
+        (Item::Comment(_), _) => had_blank_line,
+        // module annotations stay right above the module header
+        (Item::Statement(previous, _), _) if previous.kind == CstNodeKind::ModuleAnnotations => {
+            false
+        }
+        (Item::Statement(previous, text), next) => {
+            let next_is_multiline = match next {
+                Item::Statement(_, text) => text.contains('\n'),
+                // comments are attached to the statement they precede
+                Item::Comment(_) => false,
+            };
+
+            had_blank_line
+                || text.contains('\n')
+                || next_is_multiline
+                || next_statement.is_some_and(|next| group(next) != group(previous))
+        }
+    }
+}
+
+/// The group of a top-level statement, statements of different groups being separated by a blank line.
+fn group(node: &CstNode) -> usize {
+    match node.kind {
+        CstNodeKind::ModuleAnnotations | CstNodeKind::ModuleClause => 0,
+        CstNodeKind::Amends | CstNodeKind::Extends => 1,
+        CstNodeKind::Import => 2,
+        _ => 3,
+    }
+}
+
+/// Formats the elements of a statement or a member, `depth` being its indentation level.
+///
+/// Line breaks inside expressions are kept, the following lines being indented one more level,
+/// annotations on their own lines staying at the level of the declaration they precede.
+fn format_inline(elements: &[CstElement], depth: usize) -> String {
+    let mut out = String::new();
+    let mut previous: Option<&PklToken> = None;
+    let mut had_space = false;
+    let mut had_newline = false;
+    let mut annotations = AnnotationTracker::default();
+    // whether only annotations have been written so far
+    let mut in_annotations = true;
+
+    for element in elements {
+        let is_annotation = annotations.visit(element);
+        let continuation_depth = if in_annotations { depth } else { depth + 1 };
+        if let CstElement::Node(_) | CstElement::Token(CstToken { token: Some(_), .. }) = element {
+            let is_trivia = matches!(element, CstElement::Token(token) if token.is_trivia());
+            in_annotations &= is_annotation || is_trivia;
+        }
+
+        match element {
+            CstElement::Token(token) if is_comment(token) => {
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(token.text.trim_end());
+                had_space = true;
+                had_newline = !matches!(token.token, Some(PklToken::MultilineComment(_)));
+            }
+            CstElement::Token(token) if token.is_newline() => had_newline = true,
+            CstElement::Token(token) if token.is_trivia() => had_space = true,
+            CstElement::Token(token) => {
+                let Some(current) = &token.token else {
+                    out.push_str(token.text);
+                    continue;
+                };
+
+                if had_newline && !out.is_empty() {
+                    out.push('\n');
+                    out.push_str(&INDENT.repeat(continuation_depth));
+                } else if previous.is_some_and(|previous| needs_space(previous, current, had_space))
+                {
+                    out.push(' ');
+                }
+
+                out.push_str(token.text);
+                previous = Some(current);
+                had_space = false;
+                had_newline = false;
+            }
+            CstElement::Node(body) => {
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(&format_body(body, depth));
+                previous = Some(&PklToken::CloseBrace);
+                had_space = false;
+                had_newline = false;
+            }
+        }
+    }
+
+    out
+}
+
+/// Formats an object body, braces included, `depth` being the indentation level of its braces.
+fn format_body(body: &CstNode, depth: usize) -> String {
+    let indent = INDENT.repeat(depth + 1);
+    let mut out = String::from("{");
+    let mut has_items = false;
+    let mut newlines = 0;
+    let mut leading_comments = String::new();
+
+    for (i, element) in body.children.iter().enumerate() {
+        let text = match element {
+            CstElement::Token(token) if is_leading_comment(token, &body.children[i + 1..]) => {
+                leading_comments.push_str(token.text.trim_end());
+                leading_comments.push(' ');
+                continue;
+            }
+            CstElement::Token(token) if is_comment(token) => {
+                // a comment following the opening brace or a member on the same line
+                if newlines == 0 {
+                    out.push(' ');
+                    out.push_str(token.text.trim_end());
+                    continue;
+                }
+                token.text.trim_end().to_owned()
+            }
+            CstElement::Token(token) => {
+                if token.is_newline() {
+                    newlines += 1;
+                }
+                continue;
+            }
+            CstElement::Node(member) => {
+                let text = format_inline(&member.children, depth + 1);
+                std::mem::take(&mut leading_comments) + &text
+            }
+        };
+
+        if has_items && newlines > 1 {
+            out.push('\n');
+        }
+        out.push('\n');
+        out.push_str(&indent);
+        out.push_str(&text);
+
+        has_items = true;
+        newlines = 0;
+    }
+
+    if has_items || out.len() > 1 {
+        out.push('\n');
+        out.push_str(&INDENT.repeat(depth));
+    }
+    out.push('}');
+
+    out
+}
+
+/// Whether a space separates two tokens of the same line.
+fn needs_space(previous: &PklToken, next: &PklToken, had_space: bool) -> bool {
+    match (previous, next) {
+        (_, PklToken::Comma | PklToken::CloseParen | PklToken::CloseBracket | PklToken::Dot) => {
+            false
+        }
+        (
+            PklToken::OpenParen | PklToken::OpenBracket | PklToken::FunctionCall(_) | PklToken::Dot,
+            _,
+        ) => false,
+        (PklToken::EqualSign | PklToken::Comma, _) | (_, PklToken::EqualSign) => true,
+        _ => had_space,
+    }
+}
+
+/// Whether `token` is a block comment preceding a statement or member on the same line,
+/// `next` being the elements following it, the comment then staying in front of it.
+fn is_leading_comment(token: &CstToken, next: &[CstElement]) -> bool {
+    let is_block_comment = matches!(token.token, Some(PklToken::MultilineComment(_)));
+    // the spaces and other block comments of the line are skipped
+    let next = next.iter().find(|element| {
+        !matches!(element, CstElement::Token(token) if token.is_trivia() && !token.is_newline())
+    });
+
+    return is_block_comment && matches!(next, Some(CstElement::Node(_)));
+}
+
+fn is_comment(token: &CstToken) -> bool {
+    matches!(
+        token.token,
+        Some(PklToken::LineComment(_))
+            | Some(PklToken::DocComment(_))
+            | Some(PklToken::MultilineComment(_))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCES: &[&str] = &[
+        "module   birds\nimport \"a.pkl\"\nname=\"Pigeon\"\n\n\n\nage = 3\n",
+        "/// A bird\n@Deprecated { message = \"old\" }\nbird {\nname = \"Pigeon\"\n    taxonomy { species = \"Columba\" }\n}\n",
+        "a = 1 // trailing\n// leading\nb = 2\n/* lead */ c = 3\nd = 4 /* trail */\n",
+        "obj {\n  /* in */ x = 1 // after\n\n\n  y = List(1, 2,3)\n}\n",
+        "s = #\"raw \\d\"#\nm = \"\"\"\n  multi\n  \"\"\"\n",
+    ];
+
+    /// The tokens of `source` but for spaces and line breaks, which are all the parser reads:
+    /// two sources with the same tokens have the same AST, but for its spans.
+    fn tokens(source: &str) -> Vec<PklToken<'_>> {
+        PklToken::lexer(source)
+            .map(Result::unwrap)
+            .filter(|token| !matches!(token, PklToken::Space | PklToken::NewLine))
+            .collect()
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for source in SOURCES {
+            let formatted = format_pkl(source).unwrap();
+            assert_eq!(format_pkl(&formatted).unwrap(), formatted, "{source}");
+        }
+    }
+
+    #[test]
+    fn formatting_keeps_the_ast() {
+        for source in SOURCES {
+            let formatted = format_pkl(source).unwrap();
+            assert_eq!(tokens(&formatted), tokens(source), "{formatted}");
+        }
+    }
+
+    #[test]
+    fn comments_stay_where_they_are() {
+        assert_eq!(
+            format_pkl(SOURCES[2]).unwrap(),
+            "a = 1 // trailing\n// leading\nb = 2\n/* lead */ c = 3\nd = 4 /* trail */\n"
+        );
+        assert_eq!(
+            format_pkl(SOURCES[3]).unwrap(),
+            "obj {\n  /* in */ x = 1 // after\n\n  y = List(1, 2, 3)\n}\n"
+        );
+        assert_eq!(
+            format_pkl("a { /* a */ /* b */ x = 1 }\n").unwrap(),
+            "a {\n  /* a */ /* b */ x = 1\n}\n"
+        );
+        assert_eq!(
+            format_pkl("/// detached\n\n\na = 1\n").unwrap(),
+            "/// detached\n\na = 1\n"
+        );
+    }
+
+    #[test]
+    fn statements_and_bodies_are_laid_out() {
+        assert_eq!(
+            format_pkl(SOURCES[0]).unwrap(),
+            "module birds\n\nimport \"a.pkl\"\n\nname = \"Pigeon\"\n\nage = 3\n"
+        );
+        assert_eq!(
+            format_pkl(SOURCES[1]).unwrap(),
+            "/// A bird\n@Deprecated {\n  message = \"old\"\n}\nbird {\n  name = \"Pigeon\"\n  taxonomy {\n    species = \"Columba\"\n  }\n}\n"
+        );
+    }
+}
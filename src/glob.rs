@@ -0,0 +1,173 @@
+use std::{fs, io, path::Path};
+
+/// Whether `text` matches the glob `pattern`, where:
+/// - `*` matches any characters but `/`
+/// - `**` matches any characters, `/` included, `**/` also matching no directory at all
+/// - `?` matches one character but `/`
+/// - `[abc]`, `[a-z]` and `[!a-z]` match one character of, or not of, a set
+/// - `{a,b}` matches one of the comma-separated sub-patterns
+/// - `\` escapes the following character
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    matches_from(&pattern, &text)
+}
+
+/// Whether `pattern` contains glob wildcards.
+pub(crate) fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '[', '{'])
+}
+
+fn matches_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            if let ['/', after @ ..] = rest {
+                if matches_from(after, text) {
+                    return true;
+                }
+            }
+            (0..=text.len()).any(|i| matches_from(rest, &text[i..]))
+        }
+        ['*', rest @ ..] => {
+            for i in 0..=text.len() {
+                if matches_from(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        ['?', rest @ ..] => matches!(text, [c, ..] if *c != '/') && matches_from(rest, &text[1..]),
+        ['[', rest @ ..] => match rest.iter().skip(1).position(|c| *c == ']') {
+            Some(end) => {
+                let (class, after) = (&rest[..end + 1], &rest[end + 2..]);
+                matches!(text, [c, ..] if *c != '/' && class_matches(class, *c))
+                    && matches_from(after, &text[1..])
+            }
+            None => matches!(text, ['[', ..]) && matches_from(rest, &text[1..]),
+        },
+        ['{', rest @ ..] => match alternatives(rest) {
+            Some((alternatives, after)) => alternatives.into_iter().any(|alternative| {
+                let mut pattern = alternative.to_vec();
+                pattern.extend_from_slice(after);
+                matches_from(&pattern, text)
+            }),
+            None => matches!(text, ['{', ..]) && matches_from(rest, &text[1..]),
+        },
+        ['\\', c, rest @ ..] => text.first() == Some(c) && matches_from(rest, &text[1..]),
+        [c, rest @ ..] => text.first() == Some(c) && matches_from(rest, &text[1..]),
+    }
+}
+
+/// Whether `c` belongs to a character class, given without its brackets.
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class {
+        ['!' | '^', rest @ ..] => (true, rest),
+        _ => (false, class),
+    };
+
+    let mut found = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            found |= class[i] <= c && c <= class[i + 2];
+            i += 3;
+        } else {
+            found |= class[i] == c;
+            i += 1;
+        }
+    }
+
+    found != negated
+}
+
+/// Splits the alternatives of a `{a,b}` group, given after its opening brace,
+/// returning them along with the rest of the pattern.
+fn alternatives(pattern: &[char]) -> Option<(Vec<&[char]>, &[char])> {
+    let mut alternatives = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in pattern.iter().enumerate() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth == 0 => {
+                alternatives.push(&pattern[start..i]);
+                return Some((alternatives, &pattern[i + 1..]));
+            }
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                alternatives.push(&pattern[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Returns the files matching the glob `pattern`, a relative or absolute path,
+/// sorted and written the way the pattern is, e.g. `config/a.json` for `config/*.json`.
+pub(crate) fn glob_files(pattern: &str) -> io::Result<Vec<String>> {
+    // the leading directories without wildcards are not searched
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let literal = segments
+        .iter()
+        .position(|segment| is_glob(segment))
+        .unwrap_or(segments.len() - 1);
+    let base = segments[..literal].join("/");
+    let max_depth = match pattern.contains("**") {
+        true => usize::MAX,
+        false => segments.len() - literal,
+    };
+
+    let mut files = Vec::new();
+    let prefix = match base.as_str() {
+        "" if pattern.starts_with('/') => "/".to_owned(),
+        "" => String::new(),
+        base => format!("{}/", base),
+    };
+    let dir = if prefix.is_empty() { "." } else { &prefix };
+    if Path::new(dir).is_dir() {
+        collect_files(Path::new(dir), &prefix, max_depth, &mut files)?;
+    }
+
+    files.retain(|file| glob_matches(pattern, file));
+    files.sort();
+
+    Ok(files)
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    depth: usize,
+    files: &mut Vec<String>,
+) -> io::Result<()> {
+    if depth == 0 {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        let path = format!("{}{}", prefix, name);
+
+        // symbolic links to directories are not followed, avoiding cycles
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &format!("{}/", path), depth - 1, files)?;
+        } else if entry.path().is_file() {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use logos::Logos;
+use crate::error::PklError;
+use logos::{Lexer, Logos, Span};
 
 /* ANCHOR: tokens */
 /// All meaningful Pkl tokens.
@@ -10,6 +11,7 @@ use logos::Logos;
 /// > NOTE: Only basic Pkl is covered for the moment!
 #[derive(Debug, PartialEq, PartialOrd, Logos, Clone)]
 #[logos(error = LexingError)]
+#[logos(extras = LexerExtras)]
 #[logos(skip r"[\t]+")]
 pub enum PklToken<'a> {
     #[token("_", priority = 3)]
@@ -32,16 +34,38 @@ pub enum PklToken<'a> {
     OpenParen,
     #[token(")")]
     CloseParen,
+    #[token("[")]
+    OpenBracket,
+    #[token("]")]
+    CloseBracket,
     #[token(",")]
     Comma,
     #[token("new")]
     New,
     #[token("import")]
     Import,
+    #[token("import*")]
+    GlobImport,
     #[token("as")]
     As,
+    #[token("module")]
+    Module,
+    #[token("amends")]
+    Amends,
+    #[token("extends")]
+    Extends,
+    #[token("local")]
+    Local,
+    #[token("hidden")]
+    Hidden,
+    #[token("fixed")]
+    Fixed,
+    #[token("const")]
+    Const,
     #[token(".")]
     Dot,
+    #[token("@")]
+    At,
     #[token("null")]
     Null,
 
@@ -116,9 +140,9 @@ pub enum PklToken<'a> {
     })]
     OctalInt(i64),
 
-    #[token("NaN", |_| std::f64::NAN)]
-    #[token("Infinity", |_| std::f64::INFINITY)]
-    #[token("-Infinity", |_| std::f64::NEG_INFINITY)]
+    #[token("NaN", |_| f64::NAN)]
+    #[token("Infinity", |_| f64::INFINITY)]
+    #[token("-Infinity", |_| f64::NEG_INFINITY)]
     #[regex(r"-?(?:0|[1-9]+(?:_?\d)*)?(?:\.\d+(?:_?\d)*)(?:[eE][+-]?\d+(?:_?\d)*)?", |lex| {
         let raw = lex.slice();
         let clean_raw: String = raw.chars().filter(|&c| c != '_').collect();
@@ -128,6 +152,7 @@ pub enum PklToken<'a> {
 
     #[regex(r#"(_|\$)[a-zA-Z0-9_]+\("#, |lex| {let raw=lex.slice();&raw[..raw.len()-1]})]
     #[regex(r#"[a-zA-Z][a-zA-Z0-9_]*\("#, |lex| {let raw=lex.slice();&raw[..raw.len()-1]})]
+    #[regex(r#"(read[?*]|import\*)\("#, |lex| {let raw=lex.slice();&raw[..raw.len()-1]})]
     #[regex(r#"`([^`\\]|\\[`\\bnfrt]|\\u\{[a-fA-F0-9]+})*`\("#, |lex| {let raw=lex.slice();&raw[1..raw.len()-2]})]
     FunctionCall(&'a str),
 
@@ -137,47 +162,302 @@ pub enum PklToken<'a> {
     #[regex(r#"`([^`\\]|\\[`\\bnfrt]|\\u\{[a-fA-F0-9]+})*`"#, |lex| {let raw=lex.slice();&raw[1..raw.len()-1]})]
     IllegalIdentifier(&'a str),
 
-    #[regex(r#"//[^\n\\]*"#, |lex| let raw=lex.slice();&raw[2..raw.len()-1])]
+    #[regex(r#"//[^\n]*"#, |lex| &lex.slice()[2..])]
     LineComment(&'a str),
-    #[regex(r#"///[^\n\\]*"#, |lex| let raw=lex.slice();&raw[3..raw.len()-1])]
+    #[regex(r#"///[^\n]*"#, |lex| &lex.slice()[3..])]
     DocComment(&'a str),
     #[regex(r#"/\*[^*]*\*+(?:[^/*][^*]*\*+)*/"#, |lex| let raw=lex.slice();&raw[2..raw.len()-2])]
     MultilineComment(&'a str),
 
-    #[regex(r#""([^"\\]|\\["\\bnfrt]|\\u\{[a-fA-F0-9]+})*""#, |lex| let raw=lex.slice();&raw[1..raw.len()-1])]
+    /// A string, its content may contain interpolated expressions: `"Hello \(name)!"`.
+    #[token("\"", |lex| lex_string(lex, false))]
     String(&'a str),
 
-    // does pkl support <"> character in the multiline strings ?
-    #[regex(r#""""([^"\\]|\\["\\bnfrt]|u[a-fA-F0-9]{4})*""""#, |lex| {
-        let raw=lex.slice();
+    /// A multiline string, its content may contain `"` and interpolated expressions.
+    ///
+    /// The closing delimiter must be on its own line, its indentation is stripped from every line.
+    #[token("\"\"\"", |lex| lex_string(lex, true))]
+    MultiLineString(&'a str),
+
+    /// A string with custom delimiters: `#"..."#`,
+    /// its content along with the number of `#` of its delimiters.
+    ///
+    /// Escape sequences and interpolations need the same number of `#`: `#"\#n \#(name)"#`.
+    #[regex("#+\"", |lex| lex_raw_string(lex, false))]
+    RawString((&'a str, usize)),
+
+    /// A multiline string with custom delimiters: `#"""..."""#`,
+    /// its content along with the number of `#` of its delimiters.
+    #[regex("#+\"\"\"", |lex| lex_raw_string(lex, true))]
+    RawMultiLineString((&'a str, usize)),
+}
+/* ANCHOR_END: tokens */
+
+/// The state carried along the tokens for the parser.
+#[derive(Debug, Clone, Default)]
+pub struct LexerExtras {
+    /// The errors recovered from inside object bodies, only collected when set,
+    /// see [`crate::parser::parse_pkl_recovering`].
+    pub recovered_errors: Option<Vec<PklError>>,
+}
 
-        if raw[3..=3] != *"\n" {
-            return Err(LexingError::ExpectedNewLineAfterMultilineStringStart)
+impl LexerExtras {
+    /// Records an error the parser recovered from.
+    pub fn recover(&mut self, error: PklError) {
+        if let Some(errors) = &mut self.recovered_errors {
+            errors.push(error);
         }
+    }
+
+    /// Removes and returns the errors recovered from so far.
+    pub fn take_recovered(&mut self) -> Vec<PklError> {
+        self.recovered_errors
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+}
+
+/// Lexes the rest of a string literal after its opening delimiter,
+/// returning its raw content, between the delimiters.
+///
+/// The content of a multiline string excludes its first and last line breaks,
+/// as well as the indentation of its closing delimiter.
+fn lex_string<'a>(
+    lex: &mut Lexer<'a, PklToken<'a>>,
+    multiline: bool,
+) -> Result<&'a str, LexingError> {
+    let pounds = lex.slice().bytes().take_while(|c| *c == b'#').count();
+    let len = string_literal_len(
+        lex.remainder().as_bytes(),
+        lex.span().end,
+        multiline,
+        pounds,
+    )?;
+    lex.bump(len);
+
+    let raw = lex.slice();
+    let delimiter_len = pounds + if multiline { 3 } else { 1 };
+    let content = &raw[delimiter_len..raw.len() - delimiter_len];
+
+    if !multiline {
+        return Ok(content);
+    }
+
+    if !content.starts_with('\n') {
+        return Err(LexingError::ExpectedNewLineAfterMultilineStringStart);
+    }
+    let content = &content[1..];
+
+    // the closing delimiter may only be preceded by its indentation on its line
+    let last_line_start = content.rfind('\n').map_or(0, |i| i + 1);
+    if !content[last_line_start..]
+        .bytes()
+        .all(|c| c == b' ' || c == b'\t')
+    {
+        return Err(LexingError::ExpectedNewLineBeforeMultilineStringEnd);
+    }
+
+    Ok(&content[..last_line_start.saturating_sub(1)])
+}
+
+/// Lexes the rest of a string literal with custom delimiters.
+fn lex_raw_string<'a>(
+    lex: &mut Lexer<'a, PklToken<'a>>,
+    multiline: bool,
+) -> Result<(&'a str, usize), LexingError> {
+    let pounds = lex.slice().bytes().take_while(|c| *c == b'#').count();
 
-        // return err if raw[raw.len()-4..=raw.len()-4] != "\n"
-        if raw[raw.len()-4..=raw.len()-4] != *"\n" {
-            return Err(LexingError::ExpectedNewLineBeforeMultilineStringEnd)
+    Ok((lex_string(lex, multiline)?, pounds))
+}
+
+/// Returns the length of a string literal starting right after its opening delimiter,
+/// up to and including its closing delimiter.
+///
+/// Interpolated expressions, `\(expr)`, are skipped over along with the strings they contain.
+///
+/// `offset` is the position of `src` in the source, used to locate invalid escape sequences,
+/// `pounds` is the number of `#` of the string delimiters.
+fn string_literal_len(
+    src: &[u8],
+    offset: usize,
+    multiline: bool,
+    pounds: usize,
+) -> Result<usize, LexingError> {
+    let quotes: &[u8] = if multiline { b"\"\"\"" } else { b"\"" };
+    let mut i = 0;
+
+    while i < src.len() {
+        match src[i] {
+            b'\\' if is_escape_start(&src[i..], pounds) => {
+                // position of the escaped character
+                let e = i + 1 + pounds;
+
+                match src.get(e) {
+                    Some(b'(') => i = e + 1 + interpolation_len(&src[e + 1..], offset + e + 1)?,
+                    Some(b'"' | b'\\' | b'b' | b'n' | b'f' | b'r' | b't') => i = e + 1,
+                    Some(b'u') if src.get(e + 1) == Some(&b'{') => {
+                        let len = src[e + 2..]
+                            .iter()
+                            .position(|c| !c.is_ascii_hexdigit())
+                            .filter(|len| *len > 0 && src.get(e + 2 + len) == Some(&b'}'))
+                            .ok_or_else(|| invalid_escape(src, offset, i, e))?;
+                        // ascii hex digits, always valid utf8
+                        let hex = std::str::from_utf8(&src[e + 2..e + 2 + len]).unwrap();
+                        let end = e + 3 + len;
+
+                        if u32::from_str_radix(hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .is_none()
+                        {
+                            return Err(LexingError::InvalidCodePoint(
+                                hex.to_owned(),
+                                offset + i,
+                                offset + end,
+                            ));
+                        }
+                        i = end;
+                    }
+                    _ => return Err(invalid_escape(src, offset, i, e)),
+                }
+            }
+            b'\n' if !multiline => return Err(LexingError::UnterminatedString),
+            b'"' if is_delimiter(&src[i..], quotes, pounds) => {
+                return Ok(i + quotes.len() + pounds)
+            }
+            _ => i += 1,
         }
+    }
 
-        Ok(&raw[4..raw.len()-4])
-    })]
-    MultiLineString(&'a str),
+    Err(LexingError::UnterminatedString)
+}
+
+/// Returns the error for the invalid escape sequence starting at `start` in `src`,
+/// spanning up to the escaped character at `escaped`, included.
+fn invalid_escape(src: &[u8], offset: usize, start: usize, escaped: usize) -> LexingError {
+    // the escaped character may span several bytes, which all but the first start with `0b10`
+    let end = (escaped + 1..src.len())
+        .find(|j| src[*j] & 0xC0 != 0x80)
+        .unwrap_or(src.len());
+
+    LexingError::InvalidEscapeSequence(offset + start, offset + end)
+}
+
+/// Whether `src` starts with a backslash followed by `pounds` `#`.
+fn is_escape_start(src: &[u8], pounds: usize) -> bool {
+    return src.len() > pounds && src[1..=pounds].iter().all(|c| *c == b'#');
+}
+
+/// Whether `src` starts with the `quotes` followed by `pounds` `#`.
+fn is_delimiter(src: &[u8], quotes: &[u8], pounds: usize) -> bool {
+    let len = quotes.len();
+
+    return src.starts_with(quotes)
+        && src.len() >= len + pounds
+        && src[len..len + pounds].iter().all(|c| *c == b'#');
+}
+
+/// Returns the length of an interpolated expression starting right after `\(`,
+/// up to and including its closing parenthesis.
+fn interpolation_len(src: &[u8], offset: usize) -> Result<usize, LexingError> {
+    let mut depth = 0;
+    let mut i = 0;
+
+    while i < src.len() {
+        match src[i] {
+            b'(' => depth += 1,
+            b')' if depth == 0 => return Ok(i + 1),
+            b')' => depth -= 1,
+            b'"' | b'#' => {
+                let pounds = src[i..].iter().take_while(|c| **c == b'#').count();
+                let start = i + pounds;
+
+                if src.get(start) != Some(&b'"') {
+                    i = start;
+                    continue;
+                }
+
+                let multiline = src[start..].starts_with(b"\"\"\"");
+                let content_start = start + if multiline { 3 } else { 1 };
+
+                i = content_start
+                    + string_literal_len(
+                        &src[content_start..],
+                        offset + content_start,
+                        multiline,
+                        pounds,
+                    )?;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    Err(LexingError::UnterminatedString)
+}
+
+/// Decodes the escape sequences of a string literal content,
+/// `pounds` being the number of `#` of the string delimiters.
+///
+/// The content must have been validated by the lexer beforehand.
+pub fn decode_escapes(raw: &str, pounds: usize) -> Cow<'_, str> {
+    let escape_start = format!("\\{}", "#".repeat(pounds));
+    if !raw.contains(&escape_start) {
+        return Cow::Borrowed(raw);
+    }
+
+    let mut result = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(index) = rest.find(&escape_start) {
+        result.push_str(&rest[..index]);
+        rest = &rest[index + escape_start.len()..];
+
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('b') => result.push('\u{8}'),
+            Some('f') => result.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    result.push(c);
+                }
+            }
+            Some(c) => result.push(c),
+            None => {}
+        }
+        rest = chars.as_str();
+    }
+    result.push_str(rest);
+
+    Cow::Owned(result)
 }
-/* ANCHOR_END: tokens */
 
 #[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[allow(clippy::enum_variant_names)]
 pub enum LexingError {
     InvalidInteger(String),
     InvalidFloat(String),
 
     ExpectedNewLineBeforeMultilineStringEnd,
     ExpectedNewLineAfterMultilineStringStart,
+    UnterminatedString,
+    /// An escape sequence that is not valid, with its position in the source.
+    InvalidEscapeSequence(usize, usize),
+    /// A unicode escape sequence not corresponding to a valid code point,
+    /// with the position of the escape sequence in the source.
+    InvalidCodePoint(String, usize, usize),
     #[default]
     DefaultLexingError,
 }
 
 use std::{
+    borrow::Cow,
     fmt,
     num::{ParseFloatError, ParseIntError},
 };
@@ -195,6 +475,18 @@ impl From<ParseFloatError> for LexingError {
     }
 }
 
+impl LexingError {
+    /// Returns the span of the error in the source,
+    /// `token_span` being the span of the token that failed to lex.
+    pub fn span(&self, token_span: Span) -> Span {
+        match self {
+            LexingError::InvalidEscapeSequence(start, end)
+            | LexingError::InvalidCodePoint(_, start, end) => *start..*end,
+            _ => token_span,
+        }
+    }
+}
+
 impl fmt::Display for LexingError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -210,7 +502,108 @@ impl fmt::Display for LexingError {
                 f,
                 "Expected a newline after the start of the multiline string"
             ),
+            LexingError::UnterminatedString => write!(f, "Unterminated string literal"),
+            LexingError::InvalidEscapeSequence(_, _) => write!(f, "Invalid escape sequence"),
+            LexingError::InvalidCodePoint(hex, _, _) => {
+                write!(f, "Invalid unicode code point: \\u{{{}}}", hex)
+            }
             LexingError::DefaultLexingError => write!(f, "An unspecified lexing error occurred"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use logos::Logos;
+
+    #[test]
+    fn string_literal_len_stops_after_closing_delimiter() {
+        assert_eq!(string_literal_len(b"hello\" rest", 0, false, 0), Ok(6));
+        assert_eq!(string_literal_len(b"a\\\"b\" rest", 0, false, 0), Ok(5));
+        assert_eq!(string_literal_len(b"a\"b\"\"\" rest", 0, true, 0), Ok(6));
+        assert_eq!(string_literal_len(b"a\"b\"# rest", 0, false, 1), Ok(5));
+    }
+
+    #[test]
+    fn string_literal_len_skips_interpolations() {
+        assert_eq!(
+            string_literal_len(b"\\(f(\")\"))\" rest", 0, false, 0),
+            Ok(10)
+        );
+        assert_eq!(
+            string_literal_len(b"\\(\"unterminated)\"", 0, false, 0),
+            Err(LexingError::UnterminatedString)
+        );
+    }
+
+    #[test]
+    fn string_literal_len_rejects_unterminated_strings() {
+        assert_eq!(
+            string_literal_len(b"abc", 0, false, 0),
+            Err(LexingError::UnterminatedString)
+        );
+        assert_eq!(
+            string_literal_len(b"a\nb\"", 0, false, 0),
+            Err(LexingError::UnterminatedString)
+        );
+    }
+
+    #[test]
+    fn string_literal_len_locates_invalid_escapes() {
+        assert_eq!(
+            string_literal_len(b"ab\\q\"", 10, false, 0),
+            Err(LexingError::InvalidEscapeSequence(12, 14))
+        );
+        assert_eq!(
+            string_literal_len("\\é\"".as_bytes(), 0, false, 0),
+            Err(LexingError::InvalidEscapeSequence(0, 3))
+        );
+        assert_eq!(
+            string_literal_len(b"\\u{zz}\"", 0, false, 0),
+            Err(LexingError::InvalidEscapeSequence(0, 2))
+        );
+        assert_eq!(
+            string_literal_len(b"\\u{110000}\"", 0, false, 0),
+            Err(LexingError::InvalidCodePoint("110000".to_owned(), 0, 10))
+        );
+        // without its `#`, a backslash is not an escape sequence
+        assert_eq!(string_literal_len(b"\\q\"#", 0, false, 1), Ok(4));
+    }
+
+    #[test]
+    fn decode_escapes_decodes_each_sequence() {
+        assert_eq!(
+            decode_escapes(r#"a\nb\tc\rd\"e\\f\u{1F600}"#, 0),
+            "a\nb\tc\rd\"e\\f\u{1F600}"
+        );
+        assert_eq!(decode_escapes(r"\b\f", 0), "\u{8}\u{c}");
+    }
+
+    #[test]
+    fn decode_escapes_borrows_content_without_escapes() {
+        assert!(matches!(decode_escapes("plain", 0), Cow::Borrowed("plain")));
+        assert!(matches!(decode_escapes(r"\n", 1), Cow::Borrowed(r"\n")));
+    }
+
+    #[test]
+    fn decode_escapes_requires_delimiter_pounds() {
+        assert_eq!(decode_escapes(r"\n\#n", 1), "\\n\n");
+        assert_eq!(decode_escapes(r"\#n\##t", 2), "\\#n\t");
+    }
+
+    #[test]
+    fn lex_raw_string_counts_delimiter_pounds() {
+        let mut lexer = PklToken::lexer(r###"##"a"#b"##"###);
+        assert_eq!(lexer.next(), Some(Ok(PklToken::RawString(("a\"#b", 2)))));
+
+        let mut lexer = PklToken::lexer("#\"\"\"\n  x\n  \"\"\"#");
+        assert_eq!(
+            lexer.next(),
+            Some(Ok(PklToken::RawMultiLineString(("  x", 1))))
+        );
+
+        let mut lexer = PklToken::lexer(r###"##"a"#"###);
+        assert_eq!(lexer.next(), Some(Err(LexingError::UnterminatedString)));
+    }
+}
@@ -1,21 +1,76 @@
+#![allow(clippy::needless_return)]
+
+use cst::parse_cst;
 use lexer::PklToken;
-use parser::{parse_pkl, PklStatement};
-use std::collections::HashMap;
+use module::ModuleOptions;
+use parser::{parse_pkl, parse_pkl_recovering, PklStatement};
+use resource::ResourceOptions;
+use std::{borrow::Cow, sync::Arc};
 use table::{ast_to_table, PklTable};
 
+#[cfg(feature = "hashbrown_support")]
+use hashbrown::HashMap;
+#[cfg(not(feature = "hashbrown_support"))]
+use std::collections::HashMap;
+
+mod cst;
+mod diagnostic;
+mod doc;
+mod edit;
+mod error;
+mod format;
+mod glob;
 mod lexer;
+mod module;
+#[cfg(feature = "packages")]
+mod package;
 mod parser;
+#[cfg(feature = "packages")]
+mod project;
+mod render;
+mod resource;
 mod table;
 mod utils;
 
+pub use cst::{Cst, CstElement, CstNode, CstNodeKind, CstToken};
+pub use diagnostic::DiagnosticRenderer;
+pub use doc::{DocFormat, DocGenerator, DocPage};
+pub use edit::PklEditor;
+pub use error::{ErrorInfo, Label, PklError, PklWarning};
+pub use format::format_pkl;
+pub use module::{FileModuleResolver, MemoryModuleResolver, ModuleResolver};
 pub use parser::PklResult;
+#[cfg(feature = "packages")]
+pub use project::PklProject;
+pub use render::{render_output, OutputFormat};
+pub use resource::ResourceReader;
 pub use table::PklValue;
 
+/// A parsed source, which the spans of its properties refer to.
+#[derive(Debug, PartialEq, Clone)]
+struct ParsedSource<'a> {
+    name: Option<String>,
+    text: &'a str,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 /// The `Pkl` struct represents the main interface for working with PKL data.
 pub struct Pkl<'a> {
     table: PklTable<'a>,
     imports: String,
+    source_name: Option<String>,
+    /// The sources parsed so far.
+    sources: Vec<ParsedSource<'a>>,
+    /// The index in `sources` of the source declaring each property.
+    origins: HashMap<String, usize>,
+    resources: ResourceOptions,
+    modules: ModuleOptions,
+}
+
+impl<'a> Default for Pkl<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> Pkl<'a> {
@@ -24,9 +79,76 @@ impl<'a> Pkl<'a> {
         Self {
             table: PklTable::new(),
             imports: String::new(),
+            source_name: None,
+            sources: Vec::new(),
+            origins: HashMap::new(),
+            resources: ResourceOptions::default(),
+            modules: ModuleOptions::default(),
         }
     }
 
+    /// Registers an external property, read by the parsed modules with `read("prop:<key>")`.
+    ///
+    /// Deploy-time values can be passed this way instead of being spliced into the source.
+    pub fn with_external_property(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.resources
+            .external_properties
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the URI schemes the parsed modules may read with `read()`,
+    /// such as `prop` for external properties, `env` for environment variables or `file`.
+    ///
+    /// `prop`, `env` and `file` are allowed by default.
+    pub fn with_allowed_resources<S: Into<String>>(
+        mut self,
+        schemes: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.resources.allowed_schemes = schemes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Registers a reader for the resources of its URI scheme, allowing the scheme,
+    /// see [`ResourceReader`].
+    pub fn with_resource_reader(mut self, reader: impl ResourceReader + 'static) -> Self {
+        let scheme = reader.scheme().to_owned();
+        if !self.resources.allowed_schemes.contains(&scheme) {
+            self.resources.allowed_schemes.push(scheme);
+        }
+
+        self.resources.readers.push(Arc::new(reader));
+        self
+    }
+
+    /// Sets the resolver finding and reading the modules imported, amended or extended
+    /// by the parsed modules, see [`ModuleResolver`].
+    ///
+    /// Modules are read from the filesystem by default, with [`FileModuleResolver`].
+    pub fn with_module_resolver(mut self, resolver: impl ModuleResolver + 'static) -> Self {
+        self.modules.resolver = Arc::new(resolver);
+        self
+    }
+
+    /// Sets the URI of the sources parsed next, usually their file path,
+    /// against which their relative imports and `file:` reads are resolved.
+    ///
+    /// Without one, relative imports and reads are resolved as they are, from the working directory
+    /// for the default resolver.
+    pub fn set_module_uri(&mut self, uri: impl Into<String>) {
+        self.modules.uri = Some(uri.into());
+    }
+
+    /// Sets the name of the sources parsed next, usually a file path,
+    /// reported along with the line and column of errors.
+    pub fn set_source_name(&mut self, name: impl Into<String>) {
+        self.source_name = Some(name.into());
+    }
+
     /// Parses a PKL source string and populates the internal context.
     ///
     /// # Arguments
@@ -38,7 +160,23 @@ impl<'a> Pkl<'a> {
     /// A `PklResult` indicating success or failure.
     pub fn parse(&mut self, source: &'a str) -> PklResult<()> {
         let parsed = self.generate_ast(source)?;
-        self.table.extends(ast_to_table(parsed)?);
+        // imported modules share the cache of the evaluation started by the first module parsed
+        let modules = match self.modules.cache {
+            Some(_) => self.modules.clone(),
+            None => self.modules.evaluation(self.source_name.as_deref(), source),
+        };
+        let mut table = ast_to_table(parsed, self.resources.clone(), modules)
+            .map_err(|e| e.with_source(self.source_name.as_deref(), source))?;
+        table.set_warnings_source(self.source_name.as_deref(), source);
+
+        let index = self.sources.len();
+        self.origins
+            .extend(table.declared().map(|name| (name.to_owned(), index)));
+        self.sources.push(ParsedSource {
+            name: self.source_name.clone(),
+            text: source,
+        });
+        self.table.extends(table);
 
         Ok(())
     }
@@ -46,7 +184,11 @@ impl<'a> Pkl<'a> {
     pub fn parse_import(&'a mut self) -> PklResult<()> {
         let x = self.imports.as_str();
         let parsed = self.generate_ast(x)?;
-        self.table.extends(ast_to_table(parsed)?);
+        self.table.extends(ast_to_table(
+            parsed,
+            self.resources.clone(),
+            self.modules.clone(),
+        )?);
 
         Ok(())
     }
@@ -63,7 +205,90 @@ impl<'a> Pkl<'a> {
     pub fn generate_ast(&self, source: &'a str) -> PklResult<Vec<PklStatement<'a>>> {
         use logos::Logos;
         let mut lexer = PklToken::lexer(source);
-        parse_pkl(&mut lexer)
+        parse_pkl(&mut lexer).map_err(|e| e.with_source(self.source_name.as_deref(), source))
+    }
+
+    /// Generates an AST from a PKL source string, recovering from errors.
+    ///
+    /// Statements containing errors are replaced by [`PklStatement::Error`] placeholders,
+    /// so that every error of the source is reported at once.
+    ///
+    /// # Returns
+    ///
+    /// The partial AST, along with all the errors encountered.
+    pub fn generate_ast_recovering(
+        &self,
+        source: &'a str,
+    ) -> (Vec<PklStatement<'a>>, Vec<PklError>) {
+        use logos::Logos;
+        let mut lexer = PklToken::lexer(source);
+        let (statements, errors) = parse_pkl_recovering(&mut lexer);
+        let errors = errors
+            .into_iter()
+            .map(|e| e.with_source(self.source_name.as_deref(), source))
+            .collect();
+
+        (statements, errors)
+    }
+
+    /// Generates a lossless concrete syntax tree from a PKL source string.
+    ///
+    /// The tree keeps every comment and space of the source, which it writes back unchanged
+    /// through its `Display` implementation, the AST of that source being given by [`Pkl::generate_ast`].
+    /// Statements containing errors are kept in [`CstNodeKind::Error`] nodes.
+    pub fn generate_cst(&self, source: &'a str) -> Cst<'a> {
+        parse_cst(source)
+    }
+
+    /// Formats a PKL source string in the canonical style, see [`format_pkl`].
+    pub fn format(&self, source: &str) -> PklResult<String> {
+        format_pkl(source).map_err(|e| e.with_source(self.source_name.as_deref(), source))
+    }
+
+    /// Returns the doc comment of the property or object member at the dotted `path`,
+    /// such as `bird.name`, made of the `///` comments preceding its declaration.
+    pub fn doc_for(&self, path: &str) -> Option<&str> {
+        self.table.doc(path)
+    }
+
+    /// Returns the annotations of the property or object member at the dotted `path`,
+    /// each evaluated as an instance of its class, e.g.
+    /// `PklValue::ClassInstance("Deprecated", {"message": ...})` for `@Deprecated { message = ... }`.
+    pub fn annotations_for(&self, path: &str) -> &[PklValue<'a>] {
+        self.table.annotations(path)
+    }
+
+    /// Returns the annotations of the parsed module, preceding its module clause.
+    pub fn module_annotations(&self) -> &[PklValue<'a>] {
+        &self.table.module_annotations
+    }
+
+    /// Returns the warnings emitted while evaluating the parsed sources,
+    /// such as reads of properties annotated with `@Deprecated`.
+    pub fn warnings(&self) -> Vec<PklWarning> {
+        self.table.warnings()
+    }
+
+    /// Removes and returns the warnings emitted so far, see [`Pkl::warnings`].
+    pub fn take_warnings(&mut self) -> Vec<PklWarning> {
+        self.table.take_warnings()
+    }
+
+    /// Returns the name declared by the parsed module's `module` clause, if any.
+    pub fn module_name(&self) -> Option<&str> {
+        self.table.module_name.as_deref()
+    }
+
+    /// Returns the properties making up the output of the parsed module.
+    ///
+    /// Unlike [`Pkl::get`], `local` and `hidden` properties (and object members) are left out.
+    pub fn output(&self) -> HashMap<Cow<'a, str>, PklValue<'a>> {
+        self.table.output()
+    }
+
+    /// Renders the output of the parsed module, see [`Pkl::output`], in `format`.
+    pub fn render(&self, format: OutputFormat) -> PklResult<String> {
+        render_output(&self.output(), format)
     }
 
     /// Retrieves a value from the context by name.
@@ -76,7 +301,7 @@ impl<'a> Pkl<'a> {
     ///
     /// An `Option` containing a reference to the `PklValue` associated with the name,
     /// or `None` if the variable is not found.
-    pub fn get(&self, name: &'a str) -> Option<&PklValue<'a>> {
+    pub fn get(&self, name: &str) -> Option<&PklValue<'a>> {
         self.table.get(name)
     }
 
@@ -90,7 +315,11 @@ impl<'a> Pkl<'a> {
     /// # Returns
     ///
     /// An `Option` containing the previous value associated with the name, if any.
-    pub fn set(&mut self, name: &'a str, value: PklValue<'a>) -> Option<PklValue<'a>> {
+    pub fn set(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: PklValue<'a>,
+    ) -> Option<PklValue<'a>> {
         self.table.insert(name, value)
     }
 
@@ -103,8 +332,8 @@ impl<'a> Pkl<'a> {
     /// # Returns
     ///
     /// An `Option` containing the removed value, if any.
-    pub fn remove(&mut self, name: &'a str) -> Option<PklValue<'a>> {
-        self.table.variables.remove(name)
+    pub fn remove(&mut self, name: &str) -> Option<PklValue<'a>> {
+        self.table.remove(name)
     }
 
     /// Retrieves a boolean value from the context.
@@ -115,12 +344,13 @@ impl<'a> Pkl<'a> {
     ///
     /// # Returns
     ///
-    /// A `PklResult` containing the boolean value or an error message if not found or wrong type.
-    pub fn get_bool(&self, name: &'a str) -> PklResult<bool> {
+    /// A `PklResult` containing the boolean value, or a [`PklError::UnknownVariable`] if not found,
+    /// or a [`PklError::TypeMismatch`] if of the wrong type, spanning the declaration of the variable.
+    pub fn get_bool(&self, name: &str) -> PklResult<bool> {
         match self.table.get(name) {
             Some(PklValue::Bool(b)) => Ok(*b),
-            Some(_) => Err((format!("Variable `{}` is not a boolean", name), 0..0)),
-            None => Err((format!("Variable `{}` not found", name), 0..0)),
+            Some(_) => Err(self.type_mismatch(name, "a boolean")),
+            None => Err(self.unknown_variable(name)),
         }
     }
 
@@ -132,12 +362,13 @@ impl<'a> Pkl<'a> {
     ///
     /// # Returns
     ///
-    /// A `PklResult` containing the integer value or an error message if not found or wrong type.
-    pub fn get_int(&self, name: &'a str) -> PklResult<i64> {
+    /// A `PklResult` containing the integer value, or a [`PklError::UnknownVariable`] if not found,
+    /// or a [`PklError::TypeMismatch`] if of the wrong type, spanning the declaration of the variable.
+    pub fn get_int(&self, name: &str) -> PklResult<i64> {
         match self.table.get(name) {
             Some(PklValue::Int(i)) => Ok(*i),
-            Some(_) => Err((format!("Variable `{}` is not an integer", name), 0..0)),
-            None => Err((format!("Variable `{}` not found", name), 0..0)),
+            Some(_) => Err(self.type_mismatch(name, "an integer")),
+            None => Err(self.unknown_variable(name)),
         }
     }
 
@@ -149,12 +380,13 @@ impl<'a> Pkl<'a> {
     ///
     /// # Returns
     ///
-    /// A `PklResult` containing the floating-point value or an error message if not found or wrong type.
-    pub fn get_float(&self, name: &'a str) -> PklResult<f64> {
+    /// A `PklResult` containing the floating-point value, or a [`PklError::UnknownVariable`] if not found,
+    /// or a [`PklError::TypeMismatch`] if of the wrong type, spanning the declaration of the variable.
+    pub fn get_float(&self, name: &str) -> PklResult<f64> {
         match self.table.get(name) {
             Some(PklValue::Float(f)) => Ok(*f),
-            Some(_) => Err((format!("Variable `{}` is not a float", name), 0..0)),
-            None => Err((format!("Variable `{}` not found", name), 0..0)),
+            Some(_) => Err(self.type_mismatch(name, "a float")),
+            None => Err(self.unknown_variable(name)),
         }
     }
 
@@ -166,12 +398,13 @@ impl<'a> Pkl<'a> {
     ///
     /// # Returns
     ///
-    /// A `PklResult` containing the string value or an error message if not found or wrong type.
-    pub fn get_string(&self, name: &'a str) -> PklResult<String> {
+    /// A `PklResult` containing the string value, or a [`PklError::UnknownVariable`] if not found,
+    /// or a [`PklError::TypeMismatch`] if of the wrong type, spanning the declaration of the variable.
+    pub fn get_string(&self, name: &str) -> PklResult<String> {
         match self.table.get(name) {
             Some(PklValue::String(s)) => Ok(s.to_owned()),
-            Some(_) => Err((format!("Variable `{}` is not a string", name), 0..0)),
-            None => Err((format!("Variable `{}` not found", name), 0..0)),
+            Some(_) => Err(self.type_mismatch(name, "a string")),
+            None => Err(self.unknown_variable(name)),
         }
     }
 
@@ -183,12 +416,39 @@ impl<'a> Pkl<'a> {
     ///
     /// # Returns
     ///
-    /// A `PklResult` containing the object value or an error message if not found or wrong type.
-    pub fn get_object(&self, name: &'a str) -> PklResult<&HashMap<&'a str, PklValue<'a>>> {
+    /// A `PklResult` containing the object value, or a [`PklError::UnknownVariable`] if not found,
+    /// or a [`PklError::TypeMismatch`] if of the wrong type, spanning the declaration of the variable.
+    pub fn get_object(&self, name: &str) -> PklResult<&HashMap<Cow<'a, str>, PklValue<'a>>> {
         match self.table.get(name) {
             Some(PklValue::Object(o)) => Ok(o),
-            Some(_) => Err((format!("Variable `{}` is not an object", name), 0..0)),
-            None => Err((format!("Variable `{}` not found", name), 0..0)),
+            Some(_) => Err(self.type_mismatch(name, "an object")),
+            None => Err(self.unknown_variable(name)),
+        }
+    }
+
+    /// Builds the error of a `get_*` method reading the property `name`, which is not `expected`,
+    /// spanning its declaration in the source it was parsed from.
+    fn type_mismatch(&self, name: &str, expected: &str) -> PklError {
+        let message = format!("Variable `{}` is not {}", name, expected);
+
+        match (self.table.span(name), self.origins.get(name)) {
+            (Some(span), Some(&index)) => {
+                let source = &self.sources[index];
+                PklError::type_mismatch(message, span)
+                    .with_source(source.name.as_deref(), source.text)
+            }
+            (span, _) => PklError::type_mismatch(message, span.unwrap_or_default()),
+        }
+    }
+
+    /// Builds the error of a `get_*` method reading the undeclared property `name`,
+    /// pointing at the start of the last parsed source.
+    fn unknown_variable(&self, name: &str) -> PklError {
+        let error = PklError::unknown_variable(format!("Variable `{}` not found", name), 0..0);
+
+        match self.sources.last() {
+            Some(source) => error.with_source(source.name.as_deref(), source.text),
+            None => error,
         }
     }
 }
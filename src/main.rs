@@ -1,87 +1,522 @@
-use new_pkl::{Pkl, PklResult};
-use std::time::Instant;
+use new_pkl::{format_pkl, DiagnosticRenderer, OutputFormat, Pkl, PklError, PklWarning};
+#[cfg(feature = "packages")]
+use new_pkl::{FileModuleResolver, PklProject};
+use std::{
+    fs,
+    io::{self, IsTerminal, Write},
+    path::Path,
+    process::exit,
+};
 
-fn main() -> Result<(), (String, String)> {
-    let src = "//import \"test.pkl\"
+const USAGE_COMMANDS: &str = "Usage: new-pkl <command> [options] <files>...
 
-`Hello` = \"hello\"
-test = 222_333.3e-4
-b = true
-octal = 0o1_237
-hex = 0x129_EF2444443
-binary = 0b1_010_10100011111101010101
+Commands:
+  eval <files>     Evaluate modules and print their output
+  ast <files>      Print the syntax tree of modules
+  check <files>    Parse and evaluate modules, reporting every error
+  format <files>   Format modules in the canonical style
+";
+
+const USAGE_OPTIONS: &str = "
+Options:
+  -f, --format <format>  Output format of `eval`: json, yaml, pkl, plist or properties (default: pkl)
+  -o, --output <path>    Write the output to <path> instead of stdout, where `%{moduleName}`
+                         and `%{outputFormat}` are replaced for each input file
+  -p, --property <key=value>
+                         Set an external property, read by modules with `read(\"prop:<key>\")`
+      --allowed-resources <schemes>
+                         Comma-separated URI schemes modules may `read()` (default: prop,env,file)
+";
 
-multiline = \"\"\"
-Although the Dodo is extinct,
-the species will be remembered.
-efefefefef
-\"\"\"
+const USAGE_END: &str = "  -w, --write            Let `format` rewrite the files in place
+      --check            Let `format` fail if a file is not formatted, without writing anything
+  -h, --help             Print this help
+";
 
-identifier_var = multiline
+/// The package and project commands, with the `packages` feature.
+#[cfg(feature = "packages")]
+const PACKAGE_COMMANDS: &str = "  resolve [dirs]   Resolve the dependencies of projects and write their PklProject.deps.json lockfile
+                   (default: the current directory)
+";
+#[cfg(not(feature = "packages"))]
+const PACKAGE_COMMANDS: &str = "";
 
-bird_name = \"Common wood pigeon\"
+/// The package and project options, with the `packages` feature.
+#[cfg(feature = "packages")]
+const PACKAGE_OPTIONS: &str = "      --cache-dir <dir>  Directory of the downloaded packages imported with `package://` (default: ~/.pkl/cache)
+      --project-dir <dir>
+                         Evaluate modules in the project of <dir>, whose dependencies they import with `@alias/`
+";
+#[cfg(not(feature = "packages"))]
+const PACKAGE_OPTIONS: &str = "";
 
-bird {
-  name = bird_name
-  diet = \"Seeds\"
-  taxonomy {
-    species = \"Columba palumbus\"
-  }
+fn usage() -> String {
+    format!("{USAGE_COMMANDS}{PACKAGE_COMMANDS}{USAGE_OPTIONS}{PACKAGE_OPTIONS}{USAGE_END}")
 }
 
-DATA = bird.taxonomy.species
+/// Exit code of failed evaluations and checks.
+const FAILURE: i32 = 1;
+/// Exit code of invalid command lines.
+const USAGE_ERROR: i32 = 2;
 
-int = 3
-duration = int.min
-datasize = int.gb
-x = 3.4e2.ms
-two = -2.ms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Eval,
+    Ast,
+    Check,
+    Format,
+    #[cfg(feature = "packages")]
+    Resolve,
+}
 
-pigeon {
-  name = \"Turtle dove\"
-  extinct = false
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    files: Vec<String>,
+    format: OutputFormat,
+    output: Option<String>,
+    properties: Vec<(String, String)>,
+    allowed_resources: Option<Vec<String>>,
+    #[cfg(feature = "packages")]
+    cache_dir: Option<String>,
+    #[cfg(feature = "packages")]
+    project_dir: Option<String>,
+    /// The project loaded from `project_dir`.
+    #[cfg(feature = "packages")]
+    project: Option<PklProject>,
+    write: bool,
+    check: bool,
 }
 
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        print!("{}", usage());
+        return;
+    }
+
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, usage());
+            exit(USAGE_ERROR);
+        }
+    };
 
-parrot = (pigeon) {
-  name = \"Parrot\"
+    #[cfg(feature = "packages")]
+    let mut options = options;
+    #[cfg(feature = "packages")]
+    if let Some(dir) = &options.project_dir {
+        match PklProject::load(dir) {
+            Ok(project) => options.project = Some(project),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                exit(FAILURE);
+            }
+        }
+    }
+
+    let succeeded = match options.command {
+        Command::Eval => eval(&options),
+        Command::Ast => ast(&options),
+        Command::Check => check(&options),
+        Command::Format => format(&options),
+        #[cfg(feature = "packages")]
+        Command::Resolve => resolve(&options),
+    };
+
+    if !succeeded {
+        exit(FAILURE);
+    }
 }
 
-dodo {
-  name = \"Dodo\"
-} {
-  extinct = true
-} {
-  test = false
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let command = match args[0].as_str() {
+        "eval" => Command::Eval,
+        "ast" => Command::Ast,
+        "check" => Command::Check,
+        "format" => Command::Format,
+        #[cfg(feature = "packages")]
+        "resolve" => Command::Resolve,
+        other => return Err(format!("unknown command `{}`", other)),
+    };
+
+    let mut options = Options {
+        command,
+        files: Vec::new(),
+        format: OutputFormat::Pkl,
+        output: None,
+        properties: Vec::new(),
+        allowed_resources: None,
+        #[cfg(feature = "packages")]
+        cache_dir: None,
+        #[cfg(feature = "packages")]
+        project_dir: None,
+        #[cfg(feature = "packages")]
+        project: None,
+        write: false,
+        check: false,
+    };
+
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-f" | "--format" => {
+                let format = args.next().ok_or("missing value for `--format`")?;
+                options.format = format.parse()?;
+            }
+            "-o" | "--output" => {
+                let output = args.next().ok_or("missing value for `--output`")?;
+                options.output = Some(output.to_owned());
+            }
+            "-p" | "--property" => {
+                let property = args.next().ok_or("missing value for `--property`")?;
+                let (key, value) = property
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected `key=value`, found `{}`", property))?;
+                options.properties.push((key.to_owned(), value.to_owned()));
+            }
+            "--allowed-resources" => {
+                let schemes = args
+                    .next()
+                    .ok_or("missing value for `--allowed-resources`")?;
+                options.allowed_resources = Some(
+                    schemes
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|scheme| !scheme.is_empty())
+                        .map(|scheme| scheme.trim_end_matches(':').to_owned())
+                        .collect(),
+                );
+            }
+            #[cfg(feature = "packages")]
+            "--cache-dir" => {
+                let dir = args.next().ok_or("missing value for `--cache-dir`")?;
+                options.cache_dir = Some(dir.to_owned());
+            }
+            #[cfg(feature = "packages")]
+            "--project-dir" => {
+                let dir = args.next().ok_or("missing value for `--project-dir`")?;
+                options.project_dir = Some(dir.to_owned());
+            }
+            "-w" | "--write" => options.write = true,
+            "--check" => options.check = true,
+            "-" => options.files.push(arg.to_owned()),
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option `{}`", option))
+            }
+            file => options.files.push(file.to_owned()),
+        }
+    }
+
+    #[cfg(feature = "packages")]
+    if options.files.is_empty() && command == Command::Resolve {
+        options.files.push(".".to_owned());
+    }
+    if options.files.is_empty() {
+        return Err("no input file".to_owned());
+    }
+    #[cfg(feature = "packages")]
+    if options.project_dir.is_some() && command == Command::Resolve {
+        return Err("`--project-dir` does not apply to `resolve`".to_owned());
+    }
+    if (options.write || options.check) && command != Command::Format {
+        return Err("`--write` and `--check` only apply to `format`".to_owned());
+    }
+    if options.write && (options.check || options.output.is_some()) {
+        return Err("`--write` cannot be combined with `--check` or `--output`".to_owned());
+    }
+
+    Ok(options)
 }
 
-pigeon = new Bird {
-  name = \"Pigeon\"
-  lifespan = 8
-  migratory = false
+/// Creates a `Pkl` evaluating modules with the external properties, resources, package cache
+/// and project of `options`.
+fn evaluator<'a>(options: &Options) -> Pkl<'a> {
+    let mut pkl = Pkl::new();
+    for (key, value) in options.properties.iter() {
+        pkl = pkl.with_external_property(key, value);
+    }
+    if let Some(schemes) = &options.allowed_resources {
+        pkl = pkl.with_allowed_resources(schemes);
+    }
+    #[cfg(feature = "packages")]
+    if options.cache_dir.is_some() || options.project.is_some() {
+        let mut resolver = FileModuleResolver::new();
+        if let Some(dir) = &options.cache_dir {
+            resolver = resolver.with_package_cache(dir);
+        }
+        if let Some(project) = &options.project {
+            resolver = resolver.with_project(project.clone());
+        }
+        pkl = pkl.with_module_resolver(resolver);
+    }
+
+    pkl
 }
 
-list = List()
-list_with_values = List(pigeon, int, duration, two, list, List(), pigeon.lifespan)
+/// Names the module read from `file`, resolving its relative imports from its directory.
+fn set_module(pkl: &mut Pkl, file: &str) {
+    pkl.set_source_name(file);
+    if file != "-" {
+        pkl.set_module_uri(file);
+    }
+}
 
-STRING = \"test\".repeat(5)
-is_start = STRING.startsWith(\"testtest\")
-";
+/// Evaluates each file and writes its output in the requested format.
+fn eval(options: &Options) -> bool {
+    let mut outputs = Vec::new();
+    let mut succeeded = true;
 
-    let src = src.repeat(1);
-    let time = Instant::now();
+    for file in options.files.iter() {
+        let Some(source) = read_source(file) else {
+            succeeded = false;
+            continue;
+        };
 
-    let mut pkl = Pkl::new();
-    pkl.parse(&src)
-        .map_err(|(s, rng)| (s, src[rng].to_owned()))?;
+        let mut pkl = evaluator(options);
+        set_module(&mut pkl, file);
+        if let Err(e) = pkl.parse(&source) {
+            report_error(&e, &source);
+            succeeded = false;
+            continue;
+        }
+        report_warnings(&pkl.warnings(), &source);
+
+        match pkl.render(options.format) {
+            Ok(output) => outputs.push((file, output)),
+            Err(e) => {
+                eprintln!("{}: {}", file, e);
+                succeeded = false;
+            }
+        }
+    }
+
+    // the outputs of the files evaluated are written even if others failed
+    write_outputs(options, outputs, options.format.extension()) && succeeded
+}
+
+/// Prints the syntax tree of each file.
+fn ast(options: &Options) -> bool {
+    let mut outputs = Vec::new();
+
+    for file in options.files.iter() {
+        let Some(source) = read_source(file) else {
+            return false;
+        };
+
+        let mut pkl = Pkl::new();
+        pkl.set_source_name(file.as_str());
+        match pkl.generate_ast(&source) {
+            Ok(statements) => outputs.push((file, format!("{:#?}\n", statements))),
+            Err(e) => {
+                report_error(&e, &source);
+                return false;
+            }
+        };
+    }
+
+    write_outputs(options, outputs, "txt")
+}
+
+/// Reports every syntax error of each file, then evaluates the files without any.
+fn check(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for file in options.files.iter() {
+        let Some(source) = read_source(file) else {
+            succeeded = false;
+            continue;
+        };
+
+        let mut pkl = evaluator(options);
+        set_module(&mut pkl, file);
+
+        let (_, errors) = pkl.generate_ast_recovering(&source);
+        if !errors.is_empty() {
+            for e in errors.iter() {
+                report_error(e, &source);
+            }
+            succeeded = false;
+            continue;
+        }
 
-    println!(
-        "{}ms to parse {} chars",
-        time.elapsed().as_millis(),
-        src.len()
-    );
+        if let Err(e) = pkl.parse(&source) {
+            report_error(&e, &source);
+            succeeded = false;
+            continue;
+        }
+        report_warnings(&pkl.warnings(), &source);
+    }
 
-    println!("{:?}", pkl);
+    succeeded
+}
+
+/// Formats each file, printing it, rewriting it with `--write`,
+/// or listing the files that are not formatted with `--check`.
+fn format(options: &Options) -> bool {
+    let mut outputs = Vec::new();
+    let mut succeeded = true;
+
+    for file in options.files.iter() {
+        let Some(source) = read_source(file) else {
+            return false;
+        };
+
+        let formatted = match format_pkl(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                report_error(&e.with_source(Some(file), &source), &source);
+                succeeded = false;
+                continue;
+            }
+        };
+
+        if options.check {
+            if formatted != source {
+                println!("{}", file);
+                succeeded = false;
+            }
+        } else if options.write {
+            if formatted != source && !write_file(file, &formatted) {
+                succeeded = false;
+            }
+        } else {
+            outputs.push((file, formatted));
+        }
+    }
+
+    if options.check || options.write {
+        return succeeded;
+    }
+    write_outputs(options, outputs, "pkl") && succeeded
+}
+
+/// Resolves the dependencies of each project directory and writes its lockfile.
+#[cfg(feature = "packages")]
+fn resolve(options: &Options) -> bool {
+    let mut succeeded = true;
+
+    for dir in options.files.iter() {
+        let resolved = PklProject::load(dir).and_then(|mut project| {
+            project.resolve(options.cache_dir.as_deref().map(Path::new))?;
+            project.write_lockfile()?;
+            Ok(project)
+        });
+
+        match resolved {
+            Ok(project) => println!("{}", project.lockfile().display()),
+            Err(e) => {
+                eprintln!("error: {}", e);
+                succeeded = false;
+            }
+        }
+    }
+
+    succeeded
+}
+
+/// Reads `file`, or the standard input for `-`.
+fn read_source(file: &str) -> Option<String> {
+    let source = if file == "-" {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(file)
+    };
+
+    match source {
+        Ok(source) => Some(source),
+        Err(e) => {
+            eprintln!("error: cannot read {}: {}", file, e);
+            None
+        }
+    }
+}
+
+/// Writes the output of each file to stdout or to the `--output` path,
+/// the outputs sharing a path being written one after the other.
+fn write_outputs(options: &Options, outputs: Vec<(&String, String)>, extension: &str) -> bool {
+    let Some(output) = &options.output else {
+        let mut stdout = io::stdout().lock();
+        for (i, (_, content)) in outputs.iter().enumerate() {
+            if i > 0 {
+                let _ = stdout.write_all(separator(options).as_bytes());
+            }
+            let _ = stdout.write_all(content.as_bytes());
+        }
+        return true;
+    };
+
+    let mut files: Vec<(String, String)> = Vec::new();
+    for (file, content) in outputs {
+        let path = output_path(output, file, extension);
+        match files.iter_mut().find(|(existing, _)| *existing == path) {
+            Some((_, existing)) => {
+                existing.push_str(separator(options));
+                existing.push_str(&content);
+            }
+            None => files.push((path, content)),
+        }
+    }
+
+    // every file is written, even after a failure
+    files.iter().fold(true, |succeeded, (path, content)| {
+        write_file(path, content) && succeeded
+    })
+}
+
+/// Separates the outputs of several files written to the same place.
+fn separator(options: &Options) -> &'static str {
+    match (options.command, options.format) {
+        (Command::Eval, OutputFormat::Yaml) => "---\n",
+        _ => "\n",
+    }
+}
+
+/// Expands the placeholders of an `--output` path for `file`.
+fn output_path(output: &str, file: &str, extension: &str) -> String {
+    let module_name = Path::new(file)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .filter(|_| file != "-")
+        .unwrap_or("stdin");
+
+    output
+        .replace("%{moduleName}", module_name)
+        .replace("%{outputFormat}", extension)
+}
+
+fn write_file(path: &str, content: &str) -> bool {
+    if let Some(parent) = Path::new(path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
+        if let Err(e) = fs::create_dir_all(parent) {
+            eprintln!("error: cannot create {}: {}", parent.display(), e);
+            return false;
+        }
+    }
+
+    match fs::write(path, content) {
+        Ok(()) => true,
+        Err(e) => {
+            eprintln!("error: cannot write {}: {}", path, e);
+            false
+        }
+    }
+}
+
+fn renderer() -> DiagnosticRenderer {
+    DiagnosticRenderer::new().with_color(io::stderr().is_terminal())
+}
+
+fn report_error(e: &PklError, source: &str) {
+    eprintln!("{}", renderer().render(e, source));
+}
 
-    Ok(())
+fn report_warnings(warnings: &[PklWarning], source: &str) {
+    let renderer = renderer();
+    for warning in warnings {
+        eprintln!("{}", renderer.render_warning(warning, source));
+    }
 }
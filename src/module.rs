@@ -0,0 +1,721 @@
+use crate::{
+    glob::{glob_files, glob_matches},
+    parser::PklResult,
+    resource::is_scheme,
+    table::PklTable,
+    PklError,
+};
+#[cfg(feature = "packages")]
+use crate::{
+    package::{PackageCache, PackageUri},
+    project::{resolve_package_dependency, PklProject, PROJECT_SCHEMA},
+};
+use logos::Span;
+use std::{
+    env, fmt, fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+#[cfg(feature = "hashbrown_support")]
+use hashbrown::HashMap;
+#[cfg(not(feature = "hashbrown_support"))]
+use std::collections::HashMap;
+
+/// Finds and reads the modules named by `import`, `amends` and `extends`.
+///
+/// ```ignore
+/// let resolver = MemoryModuleResolver::new()
+///     .with_module("base.pkl", "name = \"base\"")
+///     .with_module("https://example.com/shared.pkl", "port = 8080");
+///
+/// let mut pkl = Pkl::new().with_module_resolver(resolver);
+/// pkl.parse("import \"base.pkl\"\nname = base.name")?;
+/// ```
+///
+/// The errors returned are usually [`PklError::Import`] errors,
+/// whose span is replaced with the span of the import they fail.
+pub trait ModuleResolver: Send + Sync {
+    /// Resolves `uri`, as written in the importing module, into the absolute URI of a module.
+    ///
+    /// `base` is the URI of the importing module, against which relative URIs are resolved,
+    /// or `None` for the evaluated module if it was not given one with [`crate::Pkl::set_module_uri`].
+    fn resolve(&self, uri: &str, base: Option<&str>) -> PklResult<String>;
+
+    /// Reads the source of the module at `uri`, as returned by [`ModuleResolver::resolve`].
+    fn read(&self, uri: &str) -> PklResult<String>;
+
+    /// Lists the URIs of the modules matching the glob `pattern`, resolved like a URI, for `import*`.
+    ///
+    /// Globbing is not supported by default.
+    fn list(&self, pattern: &str) -> PklResult<Vec<String>> {
+        Err(import_error(format!(
+            "Cannot list the modules matching `{pattern}`, globbing is not supported"
+        )))
+    }
+}
+
+/// Builds the error of a resolver, located at the failing import by the evaluator.
+fn import_error(message: String) -> PklError {
+    PklError::import(message, 0..0)
+}
+
+/// Fetches the source of `https://` modules.
+type Fetcher = dyn Fn(&str) -> Result<String, String> + Send + Sync;
+
+/// The default resolver, reading modules from the filesystem.
+///
+/// Relative paths are resolved against the directory of the importing module,
+/// or the working directory for the evaluated module.
+/// `https://` modules are only read from a fetcher or a mirror directory, if given one,
+/// `package://` modules from the packages already downloaded in the Pkl cache directory,
+/// `@alias/module.pkl` modules from the dependencies of a project, if given one,
+/// and `pkl:` modules are not supported, but for `pkl:Project`.
+///
+/// Package, project and `pkl:Project` imports need the `packages` feature, enabled by default.
+#[derive(Default, Clone)]
+pub struct FileModuleResolver {
+    fetcher: Option<Arc<Fetcher>>,
+    mirror: Option<PathBuf>,
+    #[cfg(feature = "packages")]
+    package_cache: Option<PathBuf>,
+    #[cfg(feature = "packages")]
+    project: Option<Arc<PklProject>>,
+}
+
+impl FileModuleResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetches `https://` modules with `fetcher`, given their URI and returning their source.
+    pub fn with_https_fetcher(
+        mut self,
+        fetcher: impl Fn(&str) -> Result<String, String> + Send + Sync + 'static,
+    ) -> Self {
+        self.fetcher = Some(Arc::new(fetcher));
+        self
+    }
+
+    /// Reads `https://` modules from a local mirror, `https://example.com/a/b.pkl`
+    /// being read from `<dir>/example.com/a/b.pkl`.
+    ///
+    /// A fetcher, if any, takes priority over the mirror.
+    pub fn with_https_mirror(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.mirror = Some(dir.into());
+        self
+    }
+
+    /// Reads `package://` modules from the Pkl cache directory `dir`, instead of `~/.pkl/cache`.
+    ///
+    /// Packages are never downloaded: `package://example.com/birds@1.0.0#/Bird.pkl`
+    /// is read from the `birds@1.0.0.zip` archive of `<dir>/package-2/example.com/birds@1.0.0`,
+    /// checked against the checksums of the package metadata and URI.
+    #[cfg(feature = "packages")]
+    pub fn with_package_cache(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.package_cache = Some(dir.into());
+        self
+    }
+
+    /// Resolves the `@alias/module.pkl` imports of the modules of `project`
+    /// to its dependencies, as resolved by its lockfile or [`PklProject::resolve`].
+    ///
+    /// Without a project, only the modules of packages may import their dependencies.
+    #[cfg(feature = "packages")]
+    pub fn with_project(mut self, project: PklProject) -> Self {
+        self.project = Some(Arc::new(project));
+        self
+    }
+
+    #[cfg(feature = "packages")]
+    fn packages(&self) -> Result<PackageCache, String> {
+        self.package_cache
+            .clone()
+            .or_else(PackageCache::default_dir)
+            .map(PackageCache::new)
+            .ok_or_else(|| {
+                "Cannot find the package cache, the home directory is unknown".to_owned()
+            })
+    }
+}
+
+impl fmt::Debug for FileModuleResolver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("FileModuleResolver");
+        debug
+            .field("fetcher", &self.fetcher.is_some())
+            .field("mirror", &self.mirror);
+        #[cfg(feature = "packages")]
+        debug.field("package_cache", &self.package_cache).field(
+            "project",
+            &self.project.as_ref().map(|project| project.dir()),
+        );
+
+        debug.finish()
+    }
+}
+
+impl FileModuleResolver {
+    fn resolve_uri(&self, uri: &str, base: Option<&str>) -> Result<String, String> {
+        #[cfg(feature = "packages")]
+        {
+            if uri.starts_with("package://") {
+                PackageUri::parse(uri)?;
+                return Ok(resolve_relative(uri, None));
+            }
+            if uri.starts_with('@') {
+                return match &self.project {
+                    Some(project) => project.resolve_dependency(uri, base, &self.packages()?),
+                    None => resolve_package_dependency(uri, base, &self.packages()?),
+                };
+            }
+            if uri == "pkl:Project" {
+                return Ok(uri.to_owned());
+            }
+        }
+        #[cfg(not(feature = "packages"))]
+        if uri.starts_with("package://") || uri.starts_with('@') || uri == "pkl:Project" {
+            return Err(format!(
+                "Cannot import `{uri}`, packages and projects need the `packages` feature"
+            ));
+        }
+        if uri.starts_with("pkl:") {
+            return Err("Pkl official packages imports not yet supported!".to_owned());
+        }
+
+        // `file:///etc/config.pkl` is read as `/etc/config.pkl`
+        let uri = uri
+            .strip_prefix("file://")
+            .or_else(|| uri.strip_prefix("file:"))
+            .unwrap_or(uri);
+
+        // cached modules are keyed by their URI, which must not depend on how they were imported
+        return Ok(absolutize(&resolve_relative(uri, base)));
+    }
+
+    fn read_uri(&self, uri: &str) -> Result<String, String> {
+        #[cfg(feature = "packages")]
+        {
+            if uri.starts_with("package://") {
+                return self.packages()?.read(&PackageUri::parse(uri)?);
+            }
+            if uri == "pkl:Project" {
+                return Ok(PROJECT_SCHEMA.to_owned());
+            }
+        }
+
+        let Some(url) = uri.strip_prefix("https://") else {
+            return fs::read_to_string(uri).map_err(|e| format!("Error reading {uri}: {e}"));
+        };
+
+        if let Some(fetcher) = &self.fetcher {
+            return fetcher(uri);
+        }
+
+        match &self.mirror {
+            Some(dir) => {
+                let path = dir.join(url.split(['?', '#']).next().unwrap_or(url));
+                fs::read_to_string(&path)
+                    .map_err(|e| format!("Error reading {uri} from {}: {e}", path.display()))
+            }
+            None => Err(format!(
+                "Cannot read {uri}, web imports need an https fetcher or a mirror directory"
+            )),
+        }
+    }
+
+    fn list_uris(&self, pattern: &str) -> Result<Vec<String>, String> {
+        #[cfg(feature = "packages")]
+        if pattern.starts_with("package://") {
+            return self.packages()?.list(&PackageUri::parse(pattern)?);
+        }
+        if pattern.starts_with("https://") {
+            return Err(format!(
+                "Cannot list the modules matching {pattern}, web imports cannot be globbed"
+            ));
+        }
+
+        glob_files(pattern).map_err(|e| format!("Error listing {pattern}: {e}"))
+    }
+}
+
+impl ModuleResolver for FileModuleResolver {
+    fn resolve(&self, uri: &str, base: Option<&str>) -> PklResult<String> {
+        self.resolve_uri(uri, base).map_err(import_error)
+    }
+
+    fn read(&self, uri: &str) -> PklResult<String> {
+        self.read_uri(uri).map_err(import_error)
+    }
+
+    fn list(&self, pattern: &str) -> PklResult<Vec<String>> {
+        self.list_uris(pattern).map_err(import_error)
+    }
+}
+
+/// Serves modules from memory, keyed by their URI, e.g. in tests
+/// or for modules bundled in an archive.
+///
+/// Relative URIs are resolved against the URI of the importing module,
+/// `lib/a.pkl` importing `b.pkl` reading the module inserted as `lib/b.pkl`.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryModuleResolver {
+    modules: HashMap<String, String>,
+}
+
+impl MemoryModuleResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the module at `uri`.
+    pub fn with_module(mut self, uri: impl Into<String>, source: impl Into<String>) -> Self {
+        self.insert(uri, source);
+        self
+    }
+
+    /// Adds the module at `uri`, replacing any module previously inserted there.
+    pub fn insert(&mut self, uri: impl Into<String>, source: impl Into<String>) {
+        self.modules.insert(uri.into(), source.into());
+    }
+}
+
+impl ModuleResolver for MemoryModuleResolver {
+    fn resolve(&self, uri: &str, base: Option<&str>) -> PklResult<String> {
+        return Ok(resolve_relative(uri, base));
+    }
+
+    fn read(&self, uri: &str) -> PklResult<String> {
+        self.modules
+            .get(uri)
+            .cloned()
+            .ok_or_else(|| import_error(format!("Cannot find module `{uri}`")))
+    }
+
+    fn list(&self, pattern: &str) -> PklResult<Vec<String>> {
+        let mut uris: Vec<String> = self
+            .modules
+            .keys()
+            .filter(|uri| glob_matches(pattern, uri))
+            .cloned()
+            .collect();
+        uris.sort();
+
+        Ok(uris)
+    }
+}
+
+/// Resolves a relative file path against the working directory,
+/// so that every path of a file resolves to the same URI.
+fn absolutize(path: &str) -> String {
+    if is_absolute(path) {
+        return path.to_owned();
+    }
+
+    match env::current_dir() {
+        Ok(dir) => resolve_relative(path, Some(&format!("{}/", dir.display()))),
+        Err(_) => path.to_owned(),
+    }
+}
+
+/// Resolves `uri` against the URI of the importing module, `base`, unless it is absolute,
+/// normalizing its `.` and `..` segments: `a/b.pkl` importing `../c.pkl` resolves to `c.pkl`.
+pub(crate) fn resolve_relative(uri: &str, base: Option<&str>) -> String {
+    let (root, mut segments, path) = match base {
+        Some(base) if !is_absolute(uri) => {
+            let (root, base_path) = split_root(base);
+            let mut segments: Vec<&str> = base_path.split('/').collect();
+            // the importing module's file name
+            segments.pop();
+            (root, segments, uri)
+        }
+        _ => {
+            let (root, path) = split_root(uri);
+            (root, Vec::new(), path)
+        }
+    };
+
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => match segments.last() {
+                Some(&"..") | None => segments.push(".."),
+                Some(&"") if segments.len() == 1 => {}
+                Some(_) => {
+                    segments.pop();
+                }
+            },
+            segment => segments.push(segment),
+        }
+    }
+
+    return format!("{}{}", root, segments.join("/"));
+}
+
+/// Splits the scheme and authority of URLs, e.g. `https://example.com`, never popped by `..`,
+/// from their path, package URIs being split after the `#` preceding the path of their module.
+fn split_root(uri: &str) -> (&str, &str) {
+    if uri.starts_with("package://") {
+        if let Some(i) = uri.find('#') {
+            return uri.split_at(i + 1);
+        }
+    }
+
+    match uri.split_once("://") {
+        Some((scheme, rest)) => {
+            let root_len = scheme.len() + 3 + rest.find('/').unwrap_or(rest.len());
+            uri.split_at(root_len)
+        }
+        None => ("", uri),
+    }
+}
+
+/// Whether `uri` is absolute: an absolute path or a URI with a scheme.
+fn is_absolute(uri: &str) -> bool {
+    if uri.starts_with('/') {
+        return true;
+    }
+
+    uri.split_once(':')
+        .is_some_and(|(scheme, _)| is_scheme(scheme))
+}
+
+/// How evaluated modules load the modules they import.
+#[derive(Clone)]
+pub(crate) struct ModuleOptions {
+    /// The resolver set with [`crate::Pkl::with_module_resolver`].
+    pub resolver: Arc<dyn ModuleResolver>,
+    /// The URI of the evaluated module, against which its imports are resolved.
+    pub uri: Option<String>,
+    /// The modules loaded by the current evaluation, `None` outside of one.
+    pub cache: Option<Arc<Mutex<ModuleCache>>>,
+}
+
+impl Default for ModuleOptions {
+    fn default() -> Self {
+        Self {
+            resolver: Arc::new(FileModuleResolver::new()),
+            uri: None,
+            cache: None,
+        }
+    }
+}
+
+impl fmt::Debug for ModuleOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModuleOptions")
+            .field("uri", &self.uri)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ModuleOptions {
+    fn eq(&self, other: &Self) -> bool {
+        let same_cache = match (&self.cache, &other.cache) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        Arc::ptr_eq(&self.resolver, &other.resolver) && self.uri == other.uri && same_cache
+    }
+}
+
+impl ModuleOptions {
+    /// Starts the evaluation of the module at `source`, named `name` in error messages,
+    /// with an empty cache.
+    pub fn evaluation(&self, name: Option<&str>, source: &str) -> Self {
+        let uri = self
+            .uri
+            .as_deref()
+            .map(|uri| self.resolver.resolve(uri, None).unwrap_or(uri.to_owned()));
+        let name = name.map(str::to_owned).or(uri.clone());
+
+        let mut cache = ModuleCache::default();
+        cache.loading.push(LoadingModule {
+            uri: uri.clone(),
+            name,
+            source: source.into(),
+            imported_at: 0..0,
+        });
+
+        Self {
+            resolver: self.resolver.clone(),
+            uri,
+            cache: Some(Arc::new(Mutex::new(cache))),
+        }
+    }
+
+    /// Locks the cache of the current evaluation.
+    pub fn cache(&self) -> Option<MutexGuard<'_, ModuleCache>> {
+        self.cache
+            .as_ref()
+            .map(|cache| cache.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// The modules loaded by one evaluation, shared by all the modules it loads,
+/// so that each module is only evaluated once.
+#[derive(Default)]
+pub(crate) struct ModuleCache {
+    /// The evaluated modules, keyed by their resolved URI,
+    /// owning their values as their sources are dropped once evaluated.
+    modules: HashMap<String, PklTable<'static>>,
+    /// The modules being loaded, from the evaluated module to the last one imported.
+    loading: Vec<LoadingModule>,
+}
+
+/// A module whose imports are being loaded.
+struct LoadingModule {
+    /// Its resolved URI, unknown for an evaluated module not given one.
+    uri: Option<String>,
+    /// Its name in error messages.
+    name: Option<String>,
+    source: Arc<str>,
+    /// The span of the statement importing it, in the source of the previous module.
+    imported_at: Span,
+}
+
+impl ModuleCache {
+    /// Returns the module evaluated at `uri`, without its warnings, already reported.
+    pub fn get(&self, uri: &str) -> Option<PklTable<'static>> {
+        let mut table = self.modules.get(uri)?.clone();
+        table.take_warnings();
+
+        Some(table)
+    }
+
+    pub fn insert(&mut self, uri: String, table: PklTable<'static>) {
+        self.modules.insert(uri, table);
+    }
+
+    /// Checks that importing `uri` at `rng`, in the last module being loaded, does not close a cycle,
+    /// reporting the chain of imports leading back to it otherwise,
+    /// each import labelled in the source of the module importing it.
+    pub fn check_cycle(&self, uri: &str, rng: Span) -> PklResult<()> {
+        let Some(start) = self
+            .loading
+            .iter()
+            .position(|module| module.uri.as_deref() == Some(uri))
+        else {
+            return Ok(());
+        };
+
+        let cycle = &self.loading[start..];
+        let mut names: Vec<&str> = cycle.iter().map(LoadingModule::display_name).collect();
+        names.push(uri);
+
+        let mut error = PklError::import(
+            format!("Import cycle detected: {}", names.join(" -> ")),
+            rng.to_owned(),
+        );
+        // the last import of the cycle is the one the error points at
+        let imports = cycle.iter().skip(1).map(|module| &module.imported_at);
+        for ((importer, imported_at), imported) in
+            cycle.iter().zip(imports).zip(names.iter().skip(1))
+        {
+            error = error.with_label_in(
+                importer.name.as_deref(),
+                importer.source.clone(),
+                imported_at.to_owned(),
+                format!("imports `{imported}`"),
+            );
+        }
+
+        return Err(error);
+    }
+
+    /// Records that the module at `uri`, imported at `rng`, is being loaded.
+    pub fn enter(&mut self, uri: &str, source: Arc<str>, rng: Span) {
+        self.loading.push(LoadingModule {
+            uri: Some(uri.to_owned()),
+            name: Some(uri.to_owned()),
+            source,
+            imported_at: rng,
+        });
+    }
+
+    /// Records that the last module entered is loaded.
+    pub fn exit(&mut self) {
+        self.loading.pop();
+    }
+}
+
+impl LoadingModule {
+    fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("<module>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        FileModuleResolver, MemoryModuleResolver, ModuleResolver, Pkl, PklError, PklValue,
+    };
+    use std::{env, fs};
+
+    fn cycle_error(resolver: MemoryModuleResolver, source: &str) -> PklError {
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.set_module_uri("main.pkl");
+
+        return pkl.parse(source).unwrap_err();
+    }
+
+    #[test]
+    fn two_module_cycles_label_the_import_of_each_module() {
+        let resolver =
+            MemoryModuleResolver::new().with_module("a.pkl", "// a\nimport \"main.pkl\"\n");
+        let error = cycle_error(resolver, "import \"a.pkl\"\n");
+
+        assert!(matches!(error, PklError::Import(_)));
+        assert_eq!(
+            error.message(),
+            "Import cycle detected: main.pkl -> a.pkl -> main.pkl"
+        );
+        assert_eq!(error.source_name(), Some("a.pkl"));
+        assert_eq!(error.span(), 5..22);
+
+        let labels = &error.info().labels;
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].source_name.as_deref(), Some("main.pkl"));
+        assert_eq!(labels[0].source.as_deref(), Some("import \"a.pkl\"\n"));
+        assert_eq!(labels[0].span, 0..14);
+        assert_eq!(labels[0].message, "imports `a.pkl`");
+    }
+
+    #[test]
+    fn three_module_cycles_render_each_import_in_its_source() {
+        let resolver = MemoryModuleResolver::new()
+            .with_module("a.pkl", "import \"b.pkl\"\n")
+            .with_module("b.pkl", "// b\nimport \"main.pkl\"\n");
+        let error = cycle_error(resolver, "import \"a.pkl\"\n");
+
+        assert_eq!(
+            error.message(),
+            "Import cycle detected: main.pkl -> a.pkl -> b.pkl -> main.pkl"
+        );
+        let labels: Vec<_> = error
+            .info()
+            .labels
+            .iter()
+            .map(|label| (label.source_name.as_deref(), label.span.clone()))
+            .collect();
+        assert_eq!(labels, [(Some("main.pkl"), 0..14), (Some("a.pkl"), 0..14)]);
+
+        let report = error.render("");
+        assert!(report.contains(" --> b.pkl:2:1\n"), "{report}");
+        assert!(report.contains("2 | import \"main.pkl\"\n"), "{report}");
+        assert!(report.contains(" ::: main.pkl:1:1\n"), "{report}");
+        assert!(report.contains("imports `a.pkl`"), "{report}");
+        assert!(report.contains(" ::: a.pkl:1:1\n"), "{report}");
+        assert!(report.contains("imports `b.pkl`"), "{report}");
+    }
+
+    #[test]
+    fn file_modules_are_keyed_by_their_absolute_path() {
+        // relative to the working directory, which is the crate root under `cargo test`
+        let dir = format!("target/new-pkl-modules-{}", std::process::id());
+        let absolute = format!("{}/{dir}", env::current_dir().unwrap().display());
+        fs::create_dir_all(&dir).unwrap();
+
+        fs::write(format!("{dir}/main.pkl"), "import \"a.pkl\"\n").unwrap();
+        let cycle = format!("import \"{absolute}/main.pkl\"\n");
+        fs::write(format!("{dir}/a.pkl"), &cycle).unwrap();
+
+        let resolver = FileModuleResolver::new();
+        let resolved = resolver.resolve(&format!("{dir}/./a.pkl"), None).unwrap();
+        assert_eq!(resolved, format!("{absolute}/a.pkl"));
+        assert_eq!(
+            resolver.resolve(&format!("{absolute}/a.pkl"), None),
+            Ok(resolved.clone())
+        );
+        assert_eq!(
+            resolver.resolve("a.pkl", Some(&format!("{dir}/main.pkl"))),
+            Ok(resolved)
+        );
+
+        // the root module is given a relative path, and imported back by its absolute path
+        let mut pkl = Pkl::new();
+        pkl.set_module_uri(format!("{dir}/main.pkl"));
+        pkl.set_source_name("main.pkl");
+        let error = pkl.parse("import \"a.pkl\"\n").unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            error
+                .message()
+                .starts_with("Import cycle detected: main.pkl -> "),
+            "{error}"
+        );
+        assert_eq!(error.info().source.as_deref(), Some(cycle.as_str()));
+    }
+
+    #[test]
+    fn memory_modules_resolve_relative_to_their_importer() {
+        let resolver = MemoryModuleResolver::new()
+            .with_module("lib/a.pkl", "import \"b.pkl\"\nname = b.name\n")
+            .with_module("lib/b.pkl", "name = \"b\"\n")
+            .with_module("https://example.com/shared.pkl", "port = 8080\n");
+
+        assert_eq!(
+            resolver.resolve("../c.pkl", Some("lib/a.pkl")),
+            Ok("c.pkl".to_owned())
+        );
+        assert_eq!(
+            resolver.list("lib/*.pkl"),
+            Ok(vec!["lib/a.pkl".to_owned(), "lib/b.pkl".to_owned()])
+        );
+
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.set_module_uri("main.pkl");
+        pkl.parse(
+            "import \"lib/a.pkl\"\nimport \"https://example.com/shared.pkl\"\nname = a.name\nport = shared.port\n",
+        )
+        .unwrap();
+        assert_eq!(pkl.get_string("name").unwrap(), "b");
+        assert_eq!(pkl.get_int("port").unwrap(), 8080);
+
+        let source = "import \"missing.pkl\"\n";
+        let error = pkl.parse(source).unwrap_err();
+        assert!(matches!(error, PklError::Import(_)), "{error}");
+        assert_eq!(error.message(), "Cannot find module `missing.pkl`");
+        assert_eq!(error.span(), 0..source.len() - 1);
+    }
+
+    #[test]
+    fn glob_imports_are_keyed_by_resolved_uri() {
+        let resolver = MemoryModuleResolver::new()
+            .with_module("app/configs/a.pkl", "port = 1\n")
+            .with_module("app/configs/b.pkl", "port = 2\n")
+            .with_module("app/configs/nested/c.pkl", "port = 3\n")
+            .with_module("app/configs/readme.txt", "");
+
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.set_module_uri("app/main.pkl");
+        pkl.parse("import* \"configs/*.pkl\" as flat\nimport* \"./configs/**.pkl\" as deep\n")
+            .unwrap();
+
+        for (name, expected) in [
+            ("flat", &["app/configs/a.pkl", "app/configs/b.pkl"][..]),
+            (
+                "deep",
+                &[
+                    "app/configs/a.pkl",
+                    "app/configs/b.pkl",
+                    "app/configs/nested/c.pkl",
+                ],
+            ),
+        ] {
+            let modules = pkl.get(name).and_then(PklValue::as_object).unwrap();
+            let mut keys: Vec<&str> = modules.keys().map(|key| key.as_ref()).collect();
+            keys.sort();
+            assert_eq!(keys, expected, "{name}");
+        }
+
+        let deep = pkl.get("deep").and_then(PklValue::as_object).unwrap();
+        let c = deep
+            .get("app/configs/nested/c.pkl")
+            .and_then(PklValue::as_object)
+            .unwrap();
+        assert_eq!(c.get("port"), Some(&PklValue::Int(3)));
+    }
+}
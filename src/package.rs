@@ -0,0 +1,459 @@
+use crate::glob::glob_matches;
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::Ordering,
+    fmt, fs,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+use zip::ZipArchive;
+
+/// A package URI, e.g. `package://example.com/birds@1.0.0#/Bird.pkl`,
+/// or `package://example.com/birds@1.0.0::sha256:<checksum>#/Bird.pkl`
+/// to check the metadata of the package against its SHA-256 checksum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PackageUri {
+    /// The host of the package, with its port if any, e.g. `example.com`.
+    pub authority: String,
+    /// The path of the package, e.g. `/birds`.
+    pub path: String,
+    pub version: String,
+    /// The SHA-256 checksum of the package metadata, in lowercase hexadecimal.
+    pub checksum: Option<String>,
+    /// The path of a module in the package, e.g. `/Bird.pkl`.
+    pub module: Option<String>,
+}
+
+impl PackageUri {
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid package URI `{uri}`: {reason}");
+
+        let rest = uri
+            .strip_prefix("package://")
+            .ok_or_else(|| invalid("expected the `package://` scheme"))?;
+        let (rest, module) = match rest.split_once('#') {
+            Some((rest, module)) if module.starts_with('/') => (rest, Some(module.to_owned())),
+            Some(_) => return Err(invalid("the module path after `#` must start with `/`")),
+            None => (rest, None),
+        };
+        let (rest, checksum) = match rest.split_once("::") {
+            Some((rest, checksum)) => {
+                let checksum = checksum
+                    .strip_prefix("sha256:")
+                    .filter(|checksum| is_sha256(checksum))
+                    .ok_or_else(|| invalid("expected a `::sha256:<checksum>` checksum"))?;
+                (rest, Some(checksum.to_ascii_lowercase()))
+            }
+            None => (rest, None),
+        };
+
+        let (authority, path) = rest
+            .split_once('/')
+            .filter(|(authority, _)| !authority.is_empty())
+            .ok_or_else(|| invalid("missing the host of the package"))?;
+        let (path, version) = path
+            .rsplit_once('@')
+            .filter(|(path, _)| !path.is_empty() && !path.ends_with('/'))
+            .ok_or_else(|| invalid("missing the `@<version>` of the package"))?;
+        // the segments are pushed onto paths of the package cache, see `cache_dir`
+        if path
+            .split('/')
+            .any(|segment| matches!(segment, "" | "." | ".."))
+            || matches!(authority, "." | "..")
+        {
+            return Err(invalid(
+                "the path of the package cannot have empty, `.` or `..` segments",
+            ));
+        }
+        if !is_version(version) {
+            return Err(invalid(&format!(
+                "`{version}` is not a semantic version, e.g. `1.0.0`"
+            )));
+        }
+
+        Ok(Self {
+            authority: authority.to_owned(),
+            path: format!("/{path}"),
+            version: version.to_owned(),
+            checksum,
+            module,
+        })
+    }
+
+    /// The URI of the package itself, without its checksum and module path.
+    pub fn package(&self) -> String {
+        format!("package://{}{}@{}", self.authority, self.path, self.version)
+    }
+
+    /// The URI shared by the versions of the package with the same major version,
+    /// `package://example.com/birds@1` for `package://example.com/birds@1.2.0`.
+    pub fn canonical(&self) -> String {
+        let major = self.version.split('.').next().unwrap_or(&self.version);
+        format!("package://{}{}@{}", self.authority, self.path, major)
+    }
+
+    /// The name of the package, the last segment of its path.
+    fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// The directory of the package in a package cache,
+    /// `package-2/example.com/birds@1.0.0` for `package://example.com/birds@1.0.0`.
+    fn cache_dir(&self, cache: &Path) -> PathBuf {
+        let mut dir = cache.join("package-2").join(encode_path(&self.authority));
+        let mut segments = self.path.split('/').filter(|s| !s.is_empty()).peekable();
+        while let Some(segment) = segments.next() {
+            match segments.peek() {
+                Some(_) => dir.push(encode_path(segment)),
+                None => dir.push(encode_path(&format!("{}@{}", segment, self.version))),
+            }
+        }
+
+        dir
+    }
+}
+
+impl fmt::Display for PackageUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.package())?;
+        if let Some(checksum) = &self.checksum {
+            write!(f, "::sha256:{}", checksum)?;
+        }
+        if let Some(module) = &self.module {
+            write!(f, "#{}", module)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The packages downloaded by Pkl, in the layout of its cache directory, `~/.pkl/cache` by default:
+/// `package://example.com/birds@1.0.0` being stored as
+/// `package-2/example.com/birds@1.0.0/birds@1.0.0.json`, its metadata,
+/// and `package-2/example.com/birds@1.0.0/birds@1.0.0.zip`, its modules.
+///
+/// Packages are never downloaded, only read from the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PackageCache {
+    dir: PathBuf,
+}
+
+impl PackageCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache directory of Pkl, `~/.pkl/cache`, if the home directory is known.
+    pub fn default_dir() -> Option<PathBuf> {
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+
+        Some(PathBuf::from(home).join(".pkl").join("cache"))
+    }
+
+    /// Reads the metadata of a package, checked against the checksum of its URI, if any,
+    /// returning it along with its SHA-256 checksum.
+    pub fn metadata(&self, package: &PackageUri) -> Result<(serde_json::Value, String), String> {
+        let dir = package.cache_dir(&self.dir);
+        let path = dir.join(format!("{}@{}.json", package.name(), package.version));
+        let bytes = fs::read(&path).map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => format!(
+                "Cannot find package `{}` in the package cache {}, download it with `pkl download-package {}`",
+                package.package(),
+                self.dir.display(),
+                package.package()
+            ),
+            _ => format!("Error reading {}: {}", path.display(), e),
+        })?;
+
+        let checksum = sha256(&bytes);
+        if let Some(expected) = &package.checksum {
+            check_sha256(
+                &checksum,
+                expected,
+                &format!("metadata of package `{}`", package.package()),
+            )?;
+        }
+
+        let metadata = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Invalid metadata of package `{}`: {}", package.package(), e))?;
+
+        Ok((metadata, checksum))
+    }
+
+    /// Opens the archive of a package, checked against the checksum recorded in its metadata.
+    fn archive(&self, package: &PackageUri) -> Result<ZipArchive<Cursor<Vec<u8>>>, String> {
+        let (metadata, _) = self.metadata(package)?;
+        let expected = metadata
+            .pointer("/packageZipChecksums/sha256")
+            .and_then(|checksum| checksum.as_str())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid metadata of package `{}`: missing `packageZipChecksums.sha256`",
+                    package.package()
+                )
+            })?;
+
+        let dir = package.cache_dir(&self.dir);
+        let path = dir.join(format!("{}@{}.zip", package.name(), package.version));
+        let bytes = fs::read(&path).map_err(|e| {
+            format!(
+                "Error reading the archive of package `{}` at {}: {}",
+                package.package(),
+                path.display(),
+                e
+            )
+        })?;
+        check_sha256(
+            &sha256(&bytes),
+            expected,
+            &format!("archive of package `{}`", package.package()),
+        )?;
+
+        ZipArchive::new(Cursor::new(bytes)).map_err(|e| {
+            format!(
+                "Invalid archive of package `{}` at {}: {}",
+                package.package(),
+                path.display(),
+                e
+            )
+        })
+    }
+
+    /// Reads the source of the module named by `uri`, e.g. `package://example.com/birds@1.0.0#/Bird.pkl`.
+    pub fn read(&self, uri: &PackageUri) -> Result<String, String> {
+        let module = uri.module.as_deref().ok_or_else(|| {
+            format!(
+                "Package URI `{}` does not name a module, e.g. `{}#/module.pkl`",
+                uri,
+                uri.package()
+            )
+        })?;
+
+        let mut archive = self.archive(uri)?;
+        let mut file = archive
+            .by_name(module.trim_start_matches('/'))
+            .map_err(|_| {
+                format!(
+                    "Cannot find module `{}` in package `{}`",
+                    module,
+                    uri.package()
+                )
+            })?;
+
+        let mut source = String::new();
+        file.read_to_string(&mut source)
+            .map_err(|e| format!("Error reading `{}`: {}", uri, e))?;
+
+        Ok(source)
+    }
+
+    /// Lists the URIs of the modules of a package whose path matches the glob module path of `pattern`.
+    pub fn list(&self, pattern: &PackageUri) -> Result<Vec<String>, String> {
+        let module_pattern = pattern.module.as_deref().unwrap_or("/**");
+        let archive = self.archive(pattern)?;
+
+        let mut uris: Vec<String> = archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(|name| format!("/{}", name.trim_start_matches('/')))
+            .filter(|module| glob_matches(module_pattern, module))
+            .map(|module| {
+                PackageUri {
+                    module: Some(module),
+                    ..pattern.clone()
+                }
+                .to_string()
+            })
+            .collect();
+        uris.sort();
+
+        Ok(uris)
+    }
+}
+
+/// The SHA-256 checksum of `bytes`, in lowercase hexadecimal.
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Checks that the checksum `found` of the `what` is `expected`.
+fn check_sha256(found: &str, expected: &str, what: &str) -> Result<(), String> {
+    if !found.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "Checksum mismatch for the {what}: expected sha256 `{expected}`, found `{found}`"
+        ));
+    }
+
+    Ok(())
+}
+
+fn is_sha256(checksum: &str) -> bool {
+    checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Compares two semantic versions, a pre-release preceding its release: `1.0.0-rc.1 < 1.0.0 < 1.0.1`.
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn parse(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.split('+').next().unwrap_or(version);
+        let (core, pre_release) = match version.split_once('-') {
+            Some((core, pre_release)) => (core, Some(pre_release)),
+            None => (version, None),
+        };
+        let numbers = core.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+
+        (numbers, pre_release)
+    }
+
+    let (a_numbers, a_pre_release) = parse(a);
+    let (b_numbers, b_pre_release) = parse(b);
+
+    a_numbers
+        .cmp(&b_numbers)
+        .then_with(|| match (a_pre_release, b_pre_release) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(a), Some(b)) => a.cmp(b),
+        })
+}
+
+/// Whether `version` is a semantic version, e.g. `1.0.0` or `2.1.0-rc.1+build`.
+fn is_version(version: &str) -> bool {
+    let core = version.split(['-', '+']).next().unwrap_or(version);
+    let parts: Vec<&str> = core.split('.').collect();
+
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Encodes the characters of a cache path segment that are not allowed in file names on every platform,
+/// `localhost:8080` becoming `localhost(3a)8080`.
+fn encode_path(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        match c {
+            '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*' | '(' | ')' => {
+                encoded.push_str(&format!("({:x})", c as u32))
+            }
+            c => encoded.push(c),
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_splits_package_uris() {
+        let checksum = "AB".repeat(32);
+        let uri = PackageUri::parse(&format!(
+            "package://localhost:8080/birds/flying@1.2.0-rc.1::sha256:{checksum}#/Bird.pkl"
+        ))
+        .unwrap();
+
+        assert_eq!(uri.authority, "localhost:8080");
+        assert_eq!(uri.path, "/birds/flying");
+        assert_eq!(uri.version, "1.2.0-rc.1");
+        assert_eq!(uri.checksum, Some("ab".repeat(32)));
+        assert_eq!(uri.module.as_deref(), Some("/Bird.pkl"));
+        assert_eq!(
+            uri.package(),
+            "package://localhost:8080/birds/flying@1.2.0-rc.1"
+        );
+        assert_eq!(uri.canonical(), "package://localhost:8080/birds/flying@1");
+        assert_eq!(
+            uri.to_string(),
+            format!(
+                "package://localhost:8080/birds/flying@1.2.0-rc.1::sha256:{}#/Bird.pkl",
+                "ab".repeat(32)
+            )
+        );
+
+        let uri = PackageUri::parse("package://example.com/birds@1.0.0").unwrap();
+        assert_eq!((uri.checksum, uri.module), (None, None));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_package_uris() {
+        for uri in [
+            "https://example.com/birds@1.0.0",
+            "package://example.com/birds@1.0.0#Bird.pkl",
+            "package://example.com/birds@1.0.0::sha256:abc",
+            "package:///birds@1.0.0",
+            "package://example.com/birds",
+            "package://example.com/birds@1.0",
+            "package://example.com/../../x@1.0.0",
+            "package://example.com/birds/./flying@1.0.0",
+            "package://example.com/birds//flying@1.0.0",
+            "package://../birds@1.0.0",
+        ] {
+            let error = PackageUri::parse(uri).unwrap_err();
+            assert!(
+                error.starts_with(&format!("Invalid package URI `{uri}`")),
+                "{error}"
+            );
+        }
+    }
+
+    #[test]
+    fn cache_dir_encodes_the_segments_of_the_package() {
+        let uri = PackageUri::parse("package://localhost:8080/my/birds@1.0.0").unwrap();
+
+        assert_eq!(
+            uri.cache_dir(Path::new("cache")),
+            Path::new("cache/package-2/localhost(3a)8080/my/birds@1.0.0")
+        );
+        assert_eq!(
+            encode_path("a<b>c\"d\\e|f?g*h(i)"),
+            "a(3c)b(3e)c(22)d(5c)e(7c)f(3f)g(2a)h(28)i(29)"
+        );
+    }
+
+    #[test]
+    fn mismatching_checksums_are_reported() {
+        let cache = std::env::temp_dir().join(format!("new-pkl-packages-{}", std::process::id()));
+        let uri = PackageUri::parse("package://example.com/birds@1.0.0#/Bird.pkl").unwrap();
+        let dir = uri.cache_dir(&cache);
+        fs::create_dir_all(&dir).unwrap();
+        let metadata = format!(
+            r#"{{"packageZipChecksums": {{"sha256": "{}"}}}}"#,
+            "0".repeat(64)
+        );
+        fs::write(dir.join("birds@1.0.0.json"), &metadata).unwrap();
+        fs::write(dir.join("birds@1.0.0.zip"), "not a zip").unwrap();
+
+        let packages = PackageCache::new(&cache);
+        let archive = packages.read(&uri);
+        let zip_checksum = "0".repeat(64);
+        let metadata_checksum = "f".repeat(64);
+        let with_checksum = PackageUri::parse(&format!(
+            "package://example.com/birds@1.0.0::sha256:{metadata_checksum}#/Bird.pkl"
+        ))
+        .unwrap();
+        let found = sha256(metadata.as_bytes());
+        let metadata = packages.metadata(&with_checksum);
+        fs::remove_dir_all(&cache).unwrap();
+
+        let package = "package `package://example.com/birds@1.0.0`";
+        assert_eq!(
+            metadata.unwrap_err(),
+            format!(
+                "Checksum mismatch for the metadata of {package}: \
+                expected sha256 `{metadata_checksum}`, found `{found}`"
+            )
+        );
+        assert_eq!(
+            archive.unwrap_err(),
+            format!(
+                "Checksum mismatch for the archive of {package}: \
+                expected sha256 `{zip_checksum}`, found `{}`",
+                sha256(b"not a zip")
+            )
+        );
+    }
+}
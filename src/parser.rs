@@ -1,39 +1,130 @@
-use crate::{lexer::PklToken, parse_identifier, parse_string};
-use std::ops::{Deref, DerefMut, Range};
+use crate::{
+    error::PklError,
+    lexer::{decode_escapes, LexingError, PklToken},
+    parse_identifier, parse_string,
+};
+use std::{
+    borrow::Cow,
+    ops::{Deref, DerefMut, Range},
+};
 
 #[cfg(feature = "hashbrown_support")]
-use hashbrown::Hashmap as HashMap;
-use logos::{Lexer, Span};
+use hashbrown::HashMap;
+use logos::{Lexer, Logos};
 #[cfg(not(feature = "hashbrown_support"))]
 use std::collections::HashMap;
 
-/// Represents a parsing error in the PKL format.
-///
-/// A `ParseError` is a tuple consisting of:
-///
-/// * `String` - A message describing the error.
-/// * `Span` - The span in the source where the error occurred.
-pub type ParseError = (String, Span);
-
 /// A result type for PKL parsing operations.
 ///
 /// The `PklResult` type is a specialized `Result` type used throughout the PKL parsing code.
-/// It represents either a successful result (`T`) or a `ParseError`.
-pub type PklResult<T> = std::result::Result<T, ParseError>;
+/// It represents either a successful result (`T`) or a [`PklError`].
+pub type PklResult<T> = std::result::Result<T, PklError>;
+
+pub type ExprHash<'a> = (HashMap<&'a str, ObjectMember<'a>>, Range<usize>);
 
-pub type ExprHash<'a> = (HashMap<&'a str, PklExpr<'a>>, Range<usize>);
+/// The modifiers a property or an object member is declared with.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Modifiers {
+    /// `local`: not visible outside of its module or object.
+    pub local: bool,
+    /// `hidden`: evaluated but excluded from the output.
+    pub hidden: bool,
+    /// `fixed`: cannot be overridden when amended.
+    pub fixed: bool,
+    /// `const`: cannot be overridden when amended.
+    pub constant: bool,
+}
+
+impl Modifiers {
+    /// Whether no modifier is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Modifiers::default()
+    }
+
+    /// Sets the modifier corresponding to `token`,
+    /// returns false if `token` is not a modifier.
+    fn set(&mut self, token: &PklToken) -> bool {
+        match token {
+            PklToken::Local => self.local = true,
+            PklToken::Hidden => self.hidden = true,
+            PklToken::Fixed => self.fixed = true,
+            PklToken::Const => self.constant = true,
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+/// A member of an object body.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ObjectMember<'a> {
+    pub value: PklExpr<'a>,
+    pub modifiers: Modifiers,
+    /// The doc comments preceding the member, see [`PklStatement::Constant`].
+    pub doc: Option<Cow<'a, str>>,
+    /// The annotations preceding the member.
+    pub annotations: Vec<Annotation<'a>>,
+}
+
+/// An annotation, e.g. `@Deprecated { message = "use `other` instead" }`:
+/// - name: &str, the full dotted name
+/// - body: the object following the name, if any
+#[derive(Debug, PartialEq, Clone)]
+pub struct Annotation<'a>(pub &'a str, pub Option<ExprHash<'a>>, pub Range<usize>);
+
+impl<'a> Annotation<'a> {
+    pub fn span(&self) -> Range<usize> {
+        self.2.to_owned()
+    }
+}
 
 /* ANCHOR: statements */
 /// Represent any valid Pkl value.
 #[derive(Debug, PartialEq, Clone)]
 pub enum PklStatement<'a> {
-    /// A constant/variable statement
-    Constant(&'a str, PklExpr<'a>, Range<usize>),
+    /// A constant/variable statement, e.g. `name = "Pigeon"`.
+    Constant {
+        name: &'a str,
+        value: PklExpr<'a>,
+        modifiers: Modifiers,
+        /// The consecutive `///` doc comments preceding it, without their `///`
+        /// and the space following it, joined by newlines.
+        doc: Option<Cow<'a, str>>,
+        /// The annotations preceding it, included in its span.
+        annotations: Vec<Annotation<'a>>,
+        span: Range<usize>,
+    },
 
     /// Am import statement:
     /// - name: &str
     /// - local name: Option<&str>
     Import(&'a str, Option<&'a str>, Range<usize>),
+
+    /// A glob import statement, e.g. `import* "birds/*.pkl" as birds`:
+    /// - pattern: &str
+    /// - local name: Option<&str>, required when evaluated
+    GlobImport(&'a str, Option<&'a str>, Range<usize>),
+
+    /// A module clause, e.g. `module com.example.Birds`:
+    /// - name: &str, the full dotted name
+    ModuleClause(&'a str, Range<usize>),
+
+    /// An amends clause, e.g. `amends "base.pkl"`:
+    /// - amended module: &str
+    Amends(&'a str, Range<usize>),
+
+    /// An extends clause, e.g. `extends "base.pkl"`:
+    /// - extended module: &str
+    Extends(&'a str, Range<usize>),
+
+    /// The annotations of the module, preceding its `module`, `amends` or `extends` clause,
+    /// e.g. `@ModuleInfo { minPklVersion = "0.25.0" }`.
+    ModuleAnnotations(Vec<Annotation<'a>>, Range<usize>),
+
+    /// A placeholder for a statement containing an error,
+    /// only produced by [`parse_pkl_recovering`].
+    Error(Range<usize>),
 }
 /* ANCHOR_END: statements */
 
@@ -117,6 +208,15 @@ impl<'a> From<(&'a str, Range<usize>)> for PklExpr<'a> {
     }
 }
 
+/// A part of an interpolated string.
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringPart<'a> {
+    /// A literal piece of the string, escape sequences decoded.
+    Literal(Cow<'a, str>, Range<usize>),
+    /// An interpolated expression: `\(expr)`.
+    Expr(PklExpr<'a>, Range<usize>),
+}
+
 /// Represent any valid Pkl value.
 #[derive(Debug, PartialEq, Clone)]
 pub enum AstPklValue<'a> {
@@ -129,10 +229,12 @@ pub enum AstPklValue<'a> {
     /// Any Integer.
     Int(i64, Range<usize>),
 
-    /// Any quoted string.
-    String(&'a str, Range<usize>),
-    /// Any multiline string.
-    MultiLineString(&'a str, Range<usize>),
+    /// Any quoted string, escape sequences decoded.
+    String(Cow<'a, str>, Range<usize>),
+    /// Any multiline string, escape sequences decoded.
+    MultiLineString(Cow<'a, str>, Range<usize>),
+    /// A string containing interpolated expressions, such as `"Hello \(name)!"`.
+    InterpolatedString(Vec<StringPart<'a>>, Range<usize>),
 
     /// An object.
     Object(ExprHash<'a>),
@@ -144,7 +246,7 @@ pub enum AstPklValue<'a> {
     ClassInstance(&'a str, ExprHash<'a>, Range<usize>),
 
     /// ### An object amending another object:
-    /// - First comes the name of the amended object,
+    /// - First comes the amended expression, any expression evaluating to an object,
     /// - Then the additional values
     /// - Finally the range
     ///
@@ -153,8 +255,11 @@ pub enum AstPklValue<'a> {
     /// x = (other_object) {
     ///     prop = "attribute"
     /// }
+    /// y = (bird.taxonomy) {
+    ///     prop = "attribute"
+    /// }
     /// ```
-    AmendingObject(&'a str, ExprHash<'a>, Range<usize>),
+    AmendingObject(Box<PklExpr<'a>>, ExprHash<'a>, Range<usize>),
 
     /// ### An amended object.
     /// Different from `AmendingObject`
@@ -167,7 +272,7 @@ pub enum AstPklValue<'a> {
     ///    other_prop = "other_attribute"
     /// }
     /// ```
-    AmendedObject(Box<AstPklValue<'a>>, ExprHash<'a>, Range<usize>),
+    AmendedObject(Box<PklExpr<'a>>, ExprHash<'a>, Range<usize>),
 }
 
 impl<'a> Deref for PklStatement<'a> {
@@ -175,37 +280,60 @@ impl<'a> Deref for PklStatement<'a> {
 
     fn deref(&self) -> &Self::Target {
         match self {
-            PklStatement::Constant(_, value, _) => value,
-            PklStatement::Import(_, _, _) => unreachable!(),
+            PklStatement::Constant { value, .. } => value,
+            PklStatement::Import(_, _, _)
+            | PklStatement::GlobImport(_, _, _)
+            | PklStatement::ModuleClause(_, _)
+            | PklStatement::Amends(_, _)
+            | PklStatement::Extends(_, _)
+            | PklStatement::ModuleAnnotations(_, _)
+            | PklStatement::Error(_) => unreachable!(),
         }
     }
 }
 impl<'a> DerefMut for PklStatement<'a> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         match self {
-            PklStatement::Constant(_, value, _) => value,
-            PklStatement::Import(_, _, _) => unreachable!(),
+            PklStatement::Constant { value, .. } => value,
+            PklStatement::Import(_, _, _)
+            | PklStatement::GlobImport(_, _, _)
+            | PklStatement::ModuleClause(_, _)
+            | PklStatement::Amends(_, _)
+            | PklStatement::Extends(_, _)
+            | PklStatement::ModuleAnnotations(_, _)
+            | PklStatement::Error(_) => unreachable!(),
         }
     }
 }
 impl<'a> PklStatement<'a> {
     pub fn span(&self) -> Range<usize> {
         match self {
-            PklStatement::Constant(_, _, rng) => rng.clone(),
-            PklStatement::Import(_, _, rng) => rng.clone(),
+            PklStatement::Constant { span, .. } => span.clone(),
+            PklStatement::Import(_, _, rng) | PklStatement::GlobImport(_, _, rng) => rng.clone(),
+            PklStatement::ModuleClause(_, rng)
+            | PklStatement::Amends(_, rng)
+            | PklStatement::Extends(_, rng)
+            | PklStatement::ModuleAnnotations(_, rng)
+            | PklStatement::Error(rng) => rng.clone(),
         }
     }
     pub fn is_import(&self) -> bool {
-        match self {
-            PklStatement::Import(_, _, _) => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            PklStatement::Import(_, _, _) | PklStatement::GlobImport(_, _, _)
+        )
     }
     pub fn is_constant(&self) -> bool {
-        match self {
-            PklStatement::Constant(_, _, _) => true,
-            _ => false,
-        }
+        matches!(self, PklStatement::Constant { .. })
+    }
+    pub fn is_module_header(&self) -> bool {
+        matches!(
+            self,
+            PklStatement::ModuleClause(_, _)
+                | PklStatement::Amends(_, _)
+                | PklStatement::Extends(_, _)
+                | PklStatement::ModuleAnnotations(_, _)
+        )
     }
 }
 
@@ -233,15 +361,12 @@ impl<'a> AstPklValue<'a> {
             | AstPklValue::String(_, rng)
             | AstPklValue::List(_, rng)
             | AstPklValue::MultiLineString(_, rng)
+            | AstPklValue::InterpolatedString(_, rng)
             | AstPklValue::Null(rng) => rng.clone(),
         }
     }
 }
 
-fn parse_basic_id<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Identifier<'a>> {
-    parse_identifier!(lexer)
-}
-
 fn parse_member_expr_member<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<ExprMember<'a>> {
     let start = lexer.span().end;
 
@@ -260,19 +385,19 @@ fn parse_member_expr_member<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResul
                 // Skip spaces and newlines
             }
             Err(e) => {
-                return Err((e.to_string(), lexer.span()));
+                return Err(PklError::lexing(e.to_string(), e.span(lexer.span())));
             }
             _ => {
-                return Err((
-                    "unexpected token, expected identifier".to_owned(),
+                return Err(PklError::syntax(
+                    "unexpected token, expected identifier",
                     lexer.span(),
                 ));
             }
         }
     }
 
-    Err((
-        "expected identifier but got nothing".to_owned(),
+    Err(PklError::syntax(
+        "expected identifier but got nothing",
         lexer.span(),
     ))
 }
@@ -280,130 +405,398 @@ fn parse_member_expr_member<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResul
 /// Parse a token stream into a Pkl statement.
 pub fn parse_pkl<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Vec<PklStatement<'a>>> {
     let mut statements = Vec::with_capacity(16); // Assuming typical file size for preallocation
-    let mut is_newline = true;
+    let mut state = GlobalState::new();
+
+    while let Some(token) = lexer.next() {
+        parse_global_token(lexer, token, &mut statements, &mut state)?;
+    }
+    state.finish()?;
+
+    Ok(statements)
+}
+
+/// Parse a token stream into Pkl statements, recovering from errors.
+///
+/// Instead of stopping at the first error, the statement containing it is skipped,
+/// up to the next statement or to the closing brace of the objects it opened,
+/// and replaced by a [`PklStatement::Error`] placeholder.
+/// Inside object bodies, only the member containing the error is skipped,
+/// up to the next member or to the closing brace of the body, so that the errors
+/// of the following members are reported too.
+///
+/// Returns the partial AST along with every error encountered.
+pub fn parse_pkl_recovering<'a>(
+    lexer: &mut Lexer<'a, PklToken<'a>>,
+) -> (Vec<PklStatement<'a>>, Vec<PklError>) {
+    let mut statements = Vec::with_capacity(16);
+    let mut errors = Vec::new();
+    let mut state = GlobalState::new();
+    lexer.extras.recovered_errors = Some(Vec::new());
+
+    while let Some(token) = lexer.next() {
+        // annotations are part of the statement they precede
+        if state.is_newline && state.annotations.is_empty() {
+            state.statement_start = lexer.span().start;
+        }
+
+        let parsed = parse_global_token(lexer, token, &mut statements, &mut state);
+        let recovered = lexer.extras.take_recovered();
+        if parsed.is_ok() && recovered.is_empty() {
+            continue;
+        }
+        errors.extend(recovered);
+
+        // the statement being parsed when the error occurred is replaced by the placeholder
+        let start = state.statement_start;
+        if statements.last().is_some_and(|s| s.span().start >= start) {
+            statements.pop();
+        }
+
+        let Err(error) = parsed else {
+            // the statement was parsed up to its end, despite errors inside its objects
+            statements.push(PklStatement::Error(start..lexer.span().end));
+            continue;
+        };
+        errors.push(error);
+
+        let end = skip_statement(lexer, start);
+        statements.push(PklStatement::Error(start..end));
+        state = GlobalState::new();
+    }
+    if let Err(error) = state.finish() {
+        let end = error.span().end;
+        errors.push(error);
+        statements.push(PklStatement::Error(state.statement_start..end));
+    }
+
+    (statements, errors)
+}
+
+/// The state of the parsing at the module level.
+struct GlobalState<'a> {
+    /// Whether the current line has no statement yet.
+    is_newline: bool,
+    /// Whether the current line only has spaces so far, a blank line detaching doc comments.
+    is_blank: bool,
+    /// Modifiers preceding the next property, with the start of the first one.
+    modifiers: (Modifiers, Option<usize>),
+    /// Doc comment lines preceding the next property.
+    doc: Vec<&'a str>,
+    /// Annotations preceding the next property or module clause.
+    annotations: Vec<Annotation<'a>>,
+    /// The start of the statement being parsed.
+    statement_start: usize,
+}
+
+impl<'a> GlobalState<'a> {
+    fn new() -> Self {
+        Self {
+            is_newline: true,
+            is_blank: true,
+            modifiers: (Modifiers::default(), None),
+            doc: Vec::new(),
+            annotations: Vec::new(),
+            statement_start: 0,
+        }
+    }
+
+    /// Returns an error if annotations are left without a declaration to annotate.
+    fn finish(&self) -> PklResult<()> {
+        match self.annotations.last() {
+            Some(annotation) => Err(PklError::syntax(
+                "expected a property or a module clause after this annotation",
+                annotation.span(),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Pushes the pending annotations as module annotations, before the module header.
+    fn push_module_annotations(&mut self, statements: &mut Vec<PklStatement<'a>>) {
+        let annotations = std::mem::take(&mut self.annotations);
+        if let (Some(first), Some(last)) = (annotations.first(), annotations.last()) {
+            let range = first.span().start..last.span().end;
+            statements.push(PklStatement::ModuleAnnotations(annotations, range));
+        }
+    }
+}
+
+/// Skips the rest of the statement starting at `start`, in which an error occurred:
+/// consumes tokens until the objects opened since `start` are closed and a new line begins.
+///
+/// Returns the end of the skipped statement.
+fn skip_statement<'a>(lexer: &mut Lexer<'a, PklToken<'a>>, start: usize) -> usize {
+    let mut end = lexer.span().end;
+
+    // braces left open between the statement start and the error
+    let mut depth: i32 = 0;
+    let mut consumed = PklToken::lexer(&lexer.source()[..end]);
+    consumed.bump(start);
+    for token in consumed {
+        match token {
+            Ok(PklToken::OpenBrace) => depth += 1,
+            Ok(PklToken::CloseBrace) => depth = (depth - 1).max(0),
+            _ => {}
+        }
+    }
+
+    if depth == 0 && lexer.slice().ends_with('\n') {
+        return end;
+    }
 
     while let Some(token) = lexer.next() {
         match token {
-            Ok(PklToken::Identifier(id)) | Ok(PklToken::IllegalIdentifier(id)) => {
-                if !is_newline {
-                    return Err((
-                        "unexpected token here (context: global), expected newline".to_owned(),
-                        lexer.span(),
-                    ));
-                }
-                let statement = parse_const(lexer, id)?;
-                statements.push(statement);
-                is_newline = false;
-            }
-            Ok(PklToken::Import) => {
-                if !is_newline {
-                    return Err((
-                        "unexpected token here (context: global), expected newline".to_owned(),
-                        lexer.span(),
-                    ));
-                }
-                let statement = parse_import(lexer)?;
-                statements.push(statement);
-                is_newline = false;
-            }
-            Ok(PklToken::As) => {
-                if let Some(PklStatement::Import(_, optional_name, rng)) = statements.last_mut() {
-                    if optional_name.is_none() {
-                        fn optional_id<'a>(
-                            lexer: &mut Lexer<'a, PklToken<'a>>,
-                        ) -> PklResult<Identifier<'a>> {
-                            parse_identifier!(
-                                lexer,
-                                "unexpected token here, expected an identifier (context: import)"
-                            )
-                        }
-
-                        let Identifier(other_name, other_rng) = optional_id(lexer)?;
-                        *rng = rng.start..other_rng.end;
-                        *optional_name = Some(other_name);
-                    } else {
-                        return Err((
-                            "Import statement already has an 'as' close (context: import)"
-                                .to_owned(),
-                            lexer.span(),
-                        ));
+            Ok(PklToken::OpenBrace) => depth += 1,
+            Ok(PklToken::CloseBrace) => depth = (depth - 1).max(0),
+            Ok(PklToken::NewLine) if depth == 0 => return end,
+            _ => {}
+        }
+        end = lexer.span().end;
+    }
+
+    end
+}
+
+/// Parses the statement, or part of statement, starting with `token`, at the module level.
+fn parse_global_token<'a>(
+    lexer: &mut Lexer<'a, PklToken<'a>>,
+    token: Result<PklToken<'a>, LexingError>,
+    statements: &mut Vec<PklStatement<'a>>,
+    state: &mut GlobalState<'a>,
+) -> PklResult<()> {
+    if is_blank_line(&token, &mut state.is_blank) {
+        state.doc.clear();
+    }
+
+    if let (pending, Some(_)) = &state.modifiers {
+        match token {
+            Ok(PklToken::Identifier(_))
+            | Ok(PklToken::IllegalIdentifier(_))
+            | Ok(PklToken::Local)
+            | Ok(PklToken::Hidden)
+            | Ok(PklToken::Fixed)
+            | Ok(PklToken::Const)
+            | Ok(PklToken::Space) => {}
+            _ => {
+                return Err(PklError::syntax(
+                    format!(
+                        "expected a property after modifiers {:?} (context: global)",
+                        pending
+                    ),
+                    lexer.span(),
+                ));
+            }
+        }
+    }
+
+    match token {
+        Ok(PklToken::Identifier(id)) | Ok(PklToken::IllegalIdentifier(id)) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
+                    lexer.span(),
+                ));
+            }
+            let (property_modifiers, start) = std::mem::take(&mut state.modifiers);
+            let doc = doc_text(std::mem::take(&mut state.doc));
+            let annotations = std::mem::take(&mut state.annotations);
+            let start = annotations
+                .first()
+                .map(|annotation| annotation.span().start)
+                .or(start);
+            let statement = parse_const(lexer, id, property_modifiers, doc, annotations, start)?;
+            statements.push(statement);
+            state.is_newline = false;
+        }
+        Ok(
+            ref token @ (PklToken::Local | PklToken::Hidden | PklToken::Fixed | PklToken::Const),
+        ) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
+                    lexer.span(),
+                ));
+            }
+            state.modifiers.0.set(token);
+            state.modifiers.1.get_or_insert(lexer.span().start);
+        }
+        Ok(token @ (PklToken::Import | PklToken::GlobImport)) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
+                    lexer.span(),
+                ));
+            }
+            if let Some(annotation) = state.annotations.first() {
+                return Err(PklError::syntax(
+                    "imports cannot be annotated",
+                    annotation.span(),
+                ));
+            }
+            let statement = parse_import(lexer, token == PklToken::GlobImport)?;
+            state.doc.clear();
+            statements.push(statement);
+            state.is_newline = false;
+        }
+        Ok(PklToken::Module) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
+                    lexer.span(),
+                ));
+            }
+            state.push_module_annotations(statements);
+            let statement = parse_module_clause(lexer)?;
+            state.doc.clear();
+            statements.push(statement);
+            state.is_newline = false;
+        }
+        Ok(PklToken::Amends) | Ok(PklToken::Extends) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
+                    lexer.span(),
+                ));
+            }
+            state.push_module_annotations(statements);
+            let statement = parse_amends_clause(lexer)?;
+            state.doc.clear();
+            statements.push(statement);
+            state.is_newline = false;
+        }
+        Ok(PklToken::As) => {
+            if let Some(
+                PklStatement::Import(_, optional_name, rng)
+                | PklStatement::GlobImport(_, optional_name, rng),
+            ) = statements.last_mut()
+            {
+                if optional_name.is_none() {
+                    fn optional_id<'a>(
+                        lexer: &mut Lexer<'a, PklToken<'a>>,
+                    ) -> PklResult<Identifier<'a>> {
+                        parse_identifier!(
+                            lexer,
+                            "unexpected token here, expected an identifier (context: import)"
+                        )
                     }
+
+                    let Identifier(other_name, other_rng) = optional_id(lexer)?;
+                    *rng = rng.start..other_rng.end;
+                    *optional_name = Some(other_name);
                 } else {
-                    return Err((
-                        "unexpected token here (context: global)".to_owned(),
+                    return Err(PklError::syntax(
+                        "Import statement already has an 'as' close (context: import)",
                         lexer.span(),
                     ));
                 }
+            } else {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global)",
+                    lexer.span(),
+                ));
             }
-            Ok(PklToken::Dot) => {
-                if let Some(PklStatement::Constant(_, value, _)) = statements.last_mut() {
-                    let expr_member = parse_member_expr_member(lexer)?;
-                    let expr_start = value.span().start;
-                    let expr_end = expr_member.span().end;
-
-                    *value = PklExpr::MemberExpression(
-                        Box::new(value.clone()),
-                        expr_member.into(),
-                        expr_start..expr_end,
-                    );
-                } else {
-                    return Err((
-                        "unexpected token here (context: global)".to_owned(),
-                        lexer.span(),
-                    ));
+        }
+        Ok(PklToken::Dot) => {
+            if let Some(PklStatement::ModuleClause(name, rng)) = statements.last_mut() {
+                fn name_part<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Identifier<'a>> {
+                    parse_identifier!(
+                        lexer,
+                        "unexpected token here, expected an identifier (context: module)"
+                    )
                 }
+
+                let name_start = rng.end - name.len();
+                let Identifier(_, part_rng) = name_part(lexer)?;
+                *name = &lexer.source()[name_start..part_rng.end];
+                *rng = rng.start..part_rng.end;
+            } else if let Some(PklStatement::Constant {
+                value, span: rng, ..
+            }) = statements.last_mut()
+            {
+                let expr_member = parse_member_expr_member(lexer)?;
+                let expr_start = value.span().start;
+                let expr_end = expr_member.span().end;
+
+                *value = PklExpr::MemberExpression(
+                    Box::new(value.clone()),
+                    expr_member,
+                    expr_start..expr_end,
+                );
+                rng.end = expr_end;
+            } else {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global)",
+                    lexer.span(),
+                ));
             }
-            Ok(PklToken::OpenBrace) => {
-                if let Some(PklStatement::Constant(_, value, rng)) = statements.last_mut() {
-                    match value {
-                        PklExpr::Value(AstPklValue::Object(_))
-                        | PklExpr::Value(AstPklValue::AmendingObject(_, _, _))
-                        | PklExpr::Value(AstPklValue::AmendedObject(_, _, _)) => {
-                            let (new_object, object_span) = parse_object(lexer)?;
-                            let end = object_span.end;
-                            *value = AstPklValue::AmendedObject(
-                                Box::new(value.clone().extract_value()),
-                                (new_object, object_span),
-                                rng.start..end,
-                            )
-                            .into();
-                        }
-                        _ => {
-                            return Err((
-                                "unexpected token here (context: global)".to_owned(),
-                                lexer.span(),
-                            ));
-                        }
+        }
+        Ok(PklToken::OpenBrace) => {
+            if let Some(PklStatement::Constant {
+                value, span: rng, ..
+            }) = statements.last_mut()
+            {
+                match value {
+                    PklExpr::Identifier(_)
+                    | PklExpr::MemberExpression(_, _, _)
+                    | PklExpr::FuncCall(_)
+                    | PklExpr::Value(AstPklValue::Object(_))
+                    | PklExpr::Value(AstPklValue::ClassInstance(_, _, _))
+                    | PklExpr::Value(AstPklValue::AmendingObject(_, _, _))
+                    | PklExpr::Value(AstPklValue::AmendedObject(_, _, _)) => {
+                        let (new_object, object_span) = parse_object(lexer)?;
+                        let end = object_span.end;
+                        *value = AstPklValue::AmendedObject(
+                            Box::new(value.clone()),
+                            (new_object, object_span),
+                            rng.start..end,
+                        )
+                        .into();
+                        rng.end = end;
+                    }
+                    _ => {
+                        return Err(PklError::syntax(
+                            "unexpected token here (context: global)",
+                            lexer.span(),
+                        ));
                     }
-                } else {
-                    return Err((
-                        "unexpected token here (context: global)".to_owned(),
-                        lexer.span(),
-                    ));
                 }
+            } else {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global)",
+                    lexer.span(),
+                ));
             }
-            Ok(PklToken::Space)
-            | Ok(PklToken::DocComment(_))
-            | Ok(PklToken::LineComment(_))
-            | Ok(PklToken::MultilineComment(_)) => {
-                // Skip spaces and comments
-                continue;
-            }
-            Ok(PklToken::NewLine) => {
-                is_newline = true;
-                continue;
-            }
-            Err(e) => return Err((e.to_string(), lexer.span())),
-            _ => {
-                return Err((
-                    "unexpected token here (context: statement)".to_owned(),
+        }
+        Ok(PklToken::At) => {
+            if !state.is_newline {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: global), expected newline",
                     lexer.span(),
                 ));
             }
+            let annotation = parse_annotation(lexer)?;
+            state.annotations.push(annotation);
+        }
+        Ok(PklToken::DocComment(line)) => {
+            state.doc.push(line);
+        }
+        Ok(PklToken::Space) | Ok(PklToken::LineComment(_)) | Ok(PklToken::MultilineComment(_)) => {
+            // Skip spaces and comments
+            return Ok(());
+        }
+        Ok(PklToken::NewLine) => {
+            state.is_newline = true;
+        }
+        Err(e) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+        _ => {
+            return Err(PklError::syntax(
+                "unexpected token here (context: statement)",
+                lexer.span(),
+            ));
         }
     }
 
-    Ok(statements)
+    Ok(())
 }
 
 fn parse_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExpr<'a>> {
@@ -425,9 +818,15 @@ fn parse_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExpr<'a>>
             | Ok(PklToken::HexInt(i))
             | Ok(PklToken::BinaryInt(i)) => return Ok(AstPklValue::Int(i, lexer.span()).into()),
             Ok(PklToken::Float(f)) => return Ok(AstPklValue::Float(f, lexer.span()).into()),
-            Ok(PklToken::String(s)) => return Ok(AstPklValue::String(s, lexer.span()).into()),
+            Ok(PklToken::String(s)) => return Ok(parse_string_literal(lexer, s, false, 0)?.into()),
             Ok(PklToken::MultiLineString(s)) => {
-                return Ok(AstPklValue::MultiLineString(s, lexer.span()).into())
+                return Ok(parse_string_literal(lexer, s, true, 0)?.into())
+            }
+            Ok(PklToken::RawString((s, pounds))) => {
+                return Ok(parse_string_literal(lexer, s, false, pounds)?.into())
+            }
+            Ok(PklToken::RawMultiLineString((s, pounds))) => {
+                return Ok(parse_string_literal(lexer, s, true, pounds)?.into())
             }
             Ok(PklToken::OpenParen) => return Ok(parse_amended_object(lexer)?.into()),
             Ok(PklToken::Space)
@@ -435,11 +834,14 @@ fn parse_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExpr<'a>>
             | Ok(PklToken::DocComment(_))
             | Ok(PklToken::LineComment(_))
             | Ok(PklToken::MultilineComment(_)) => continue,
-            Err(e) => return Err((e.to_string(), lexer.span())),
-            _ => return Err(("unexpected token here".to_owned(), lexer.span())),
+            Err(e) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+            _ => return Err(PklError::syntax("unexpected token here", lexer.span())),
         }
     }
-    Err(("empty expressions are not allowed".to_owned(), lexer.span()))
+    Err(PklError::syntax(
+        "empty expressions are not allowed",
+        lexer.span(),
+    ))
 }
 
 fn parse_fn_call<'a>(
@@ -461,11 +863,11 @@ fn parse_fn_call<'a>(
 
                         *last = PklExpr::MemberExpression(
                             Box::new(last.clone()),
-                            expr_member.into(),
+                            expr_member,
                             expr_start..expr_end,
                         );
                     } else {
-                        return Err(("unexpected token '.'".to_owned(), lexer.span()));
+                        return Err(PklError::syntax("unexpected token '.'", lexer.span()));
                     }
                 }
                 PklToken::Comma if !is_comma => {
@@ -473,7 +875,7 @@ fn parse_fn_call<'a>(
                 }
                 PklToken::CloseParen => {
                     let end = lexer.span().end;
-                    return Ok(FuncCall(id, values.into(), start..end));
+                    return Ok(FuncCall(id, values, start..end));
                 }
                 PklToken::Space
                 | PklToken::NewLine
@@ -514,84 +916,254 @@ fn parse_fn_call<'a>(
                     is_comma = false;
                 }
                 PklToken::String(s) if is_comma => {
-                    values.push(AstPklValue::String(s, lexer.span()).into());
+                    values.push(parse_string_literal(lexer, s, false, 0)?.into());
                     is_comma = false;
                 }
                 PklToken::MultiLineString(s) if is_comma => {
-                    values.push(AstPklValue::MultiLineString(s, lexer.span()).into());
+                    values.push(parse_string_literal(lexer, s, true, 0)?.into());
+                    is_comma = false;
+                }
+                PklToken::RawString((s, pounds)) if is_comma => {
+                    values.push(parse_string_literal(lexer, s, false, pounds)?.into());
+                    is_comma = false;
+                }
+                PklToken::RawMultiLineString((s, pounds)) if is_comma => {
+                    values.push(parse_string_literal(lexer, s, true, pounds)?.into());
                     is_comma = false;
                 }
-                _ => return Err(("unexpected token here".to_owned(), lexer.span())),
+                _ => return Err(PklError::syntax("unexpected token here", lexer.span())),
             },
-            Some(Err(e)) => return Err((e.to_string(), lexer.span())),
-            None => return Err(("Missing list close parenthesis".to_owned(), lexer.span())),
+            Some(Err(e)) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+            None => {
+                return Err(PklError::syntax(
+                    "Missing list close parenthesis",
+                    lexer.span(),
+                ))
+            }
         }
     }
 }
 
 fn parse_object<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<ExprHash<'a>> {
     let start = lexer.span().start;
-    let mut hashmap = HashMap::with_capacity(8); // Assuming typical small object size
-    let mut expect_new_entry = true;
+    let mut state = ObjectState::new(lexer.span().end);
 
     while let Some(token) = lexer.next() {
-        match token {
-            Ok(PklToken::Identifier(id)) | Ok(PklToken::IllegalIdentifier(id)) => {
-                if !expect_new_entry {
-                    return Err((
-                        "unexpected token here (context: object), expected newline or comma"
-                            .to_owned(),
-                        lexer.span(),
-                    ));
-                }
-
-                let value = parse_const_expr(lexer)?;
-                expect_new_entry = matches!(value, PklExpr::Value(AstPklValue::Object((_, _))));
-                hashmap.insert(id, value);
-            }
-            Ok(PklToken::NewLine) | Ok(PklToken::Comma) => {
-                expect_new_entry = true;
-            }
-            Ok(PklToken::Space) => {}
-            Ok(PklToken::CloseBrace) => {
+        let error = match parse_object_token(lexer, token, &mut state) {
+            Ok(false) => continue,
+            Ok(true) => {
                 let end = lexer.span().end;
-                return Ok((hashmap, start..end));
-            }
-            Err(e) => return Err((e.to_string(), lexer.span())),
-            _ => {
-                return Err((
-                    "unexpected token here (context: object)".to_owned(),
-                    lexer.span(),
-                ));
+                return Ok((state.members, start..end));
             }
+            Err(error) => error,
+        };
+
+        if lexer.extras.recovered_errors.is_none() {
+            return Err(error);
+        }
+        // when recovering, the member is skipped and parsing goes on with the next one
+        let Some(is_closed) = skip_member(lexer, state.member_start) else {
+            return Err(error);
+        };
+        lexer.extras.recover(error);
+        if is_closed {
+            let end = lexer.span().end;
+            return Ok((state.members, start..end));
         }
+        state.next_member(lexer.span().end);
     }
 
-    Err(("Missing object close brace".to_owned(), lexer.span()))
+    Err(PklError::syntax("Missing object close brace", lexer.span()))
 }
 
-fn parse_amended_object<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<AstPklValue<'a>> {
-    let start = lexer.span().start;
+/// The state of the parsing of an object body.
+struct ObjectState<'a> {
+    members: HashMap<&'a str, ObjectMember<'a>>,
+    expect_new_entry: bool,
+    /// Modifiers preceding the next member.
+    modifiers: Modifiers,
+    /// Doc comment lines preceding the next member.
+    doc: Vec<&'a str>,
+    /// Whether the current line only has spaces so far, a blank line detaching doc comments.
+    is_blank: bool,
+    /// Annotations preceding the next member.
+    annotations: Vec<Annotation<'a>>,
+    /// The position from which the member being parsed is skipped on errors.
+    member_start: usize,
+}
 
-    let amended_object_name = match lexer.next() {
-        Some(Ok(PklToken::Identifier(id))) | Some(Ok(PklToken::IllegalIdentifier(id))) => {
-            if let Some(Ok(PklToken::CloseParen)) = lexer.next() {
-                id
-            } else {
-                return Err((
-                    "expected close parenthesis (context: amended_object)".to_owned(),
+impl<'a> ObjectState<'a> {
+    fn new(member_start: usize) -> Self {
+        Self {
+            members: HashMap::with_capacity(8), // Assuming typical small object size
+            expect_new_entry: true,
+            modifiers: Modifiers::default(),
+            doc: Vec::new(),
+            is_blank: false,
+            annotations: Vec::new(),
+            member_start,
+        }
+    }
+
+    /// Starts a new member at `member_start`, forgetting what preceded it.
+    fn next_member(&mut self, member_start: usize) {
+        self.expect_new_entry = true;
+        self.modifiers = Modifiers::default();
+        self.doc.clear();
+        self.annotations.clear();
+        self.member_start = member_start;
+    }
+}
+
+/// Skips the rest of the object member starting at `start`, in which an error occurred:
+/// consumes tokens until the objects opened since `start` are closed
+/// and a new line or a comma begins the next member.
+///
+/// Returns whether the object body the member belongs to was closed meanwhile,
+/// or `None` if the source ends first.
+fn skip_member<'a>(lexer: &mut Lexer<'a, PklToken<'a>>, start: usize) -> Option<bool> {
+    // braces left open between the member start and the error
+    let mut depth: i32 = 0;
+    let mut consumed = PklToken::lexer(&lexer.source()[..lexer.span().end]);
+    consumed.bump(start);
+    for token in consumed {
+        match token {
+            Ok(PklToken::OpenBrace) => depth += 1,
+            Ok(PklToken::CloseBrace) => depth -= 1,
+            _ => {}
+        }
+    }
+
+    if depth < 0 {
+        return Some(true);
+    }
+    if depth == 0 && matches!(lexer.slice(), "\n" | ",") {
+        return Some(false);
+    }
+
+    for token in lexer.by_ref() {
+        match token {
+            Ok(PklToken::OpenBrace) => depth += 1,
+            Ok(PklToken::CloseBrace) if depth == 0 => return Some(true),
+            Ok(PklToken::CloseBrace) => depth -= 1,
+            Ok(PklToken::NewLine) | Ok(PklToken::Comma) if depth == 0 => return Some(false),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Parses the member, or part of member, of an object body starting with `token`.
+///
+/// Returns whether `token` closes the object body.
+fn parse_object_token<'a>(
+    lexer: &mut Lexer<'a, PklToken<'a>>,
+    token: Result<PklToken<'a>, LexingError>,
+    state: &mut ObjectState<'a>,
+) -> PklResult<bool> {
+    if is_blank_line(&token, &mut state.is_blank) {
+        state.doc.clear();
+    }
+
+    match token {
+        Ok(
+            token @ (PklToken::Identifier(_)
+            | PklToken::IllegalIdentifier(_)
+            | PklToken::OpenBracket),
+        ) => {
+            if !state.expect_new_entry {
+                return Err(PklError::syntax(
+                    "unexpected token here (context: object), expected newline or comma".to_owned(),
                     lexer.span(),
                 ));
             }
+
+            let name = match token {
+                PklToken::Identifier(id) | PklToken::IllegalIdentifier(id) => id,
+                _ if state.modifiers.is_empty() => parse_entry_key(lexer)?,
+                _ => {
+                    return Err(PklError::syntax(
+                        "object entries cannot have modifiers",
+                        lexer.span(),
+                    ))
+                }
+            };
+            let value = parse_const_expr(lexer)?;
+            state.expect_new_entry = matches!(value, PklExpr::Value(AstPklValue::Object((_, _))));
+            state.members.insert(
+                name,
+                ObjectMember {
+                    value,
+                    modifiers: std::mem::take(&mut state.modifiers),
+                    doc: doc_text(std::mem::take(&mut state.doc)),
+                    annotations: std::mem::take(&mut state.annotations),
+                },
+            );
+        }
+        Ok(
+            ref token @ (PklToken::Local | PklToken::Hidden | PklToken::Fixed | PklToken::Const),
+        ) if state.expect_new_entry => {
+            state.modifiers.set(token);
+        }
+        Ok(PklToken::NewLine) | Ok(PklToken::Comma) if state.modifiers.is_empty() => {
+            state.expect_new_entry = true;
+            state.member_start = lexer.span().end;
         }
-        Some(Err(e)) => return Err((e.to_string(), lexer.span())),
+        Ok(PklToken::DocComment(line)) if state.modifiers.is_empty() => state.doc.push(line),
+        Ok(PklToken::At) if state.expect_new_entry && state.modifiers.is_empty() => {
+            state.annotations.push(parse_annotation(lexer)?);
+        }
+        Ok(PklToken::Space) | Ok(PklToken::LineComment(_)) | Ok(PklToken::MultilineComment(_)) => {}
+        Ok(PklToken::CloseBrace) if state.modifiers.is_empty() && state.annotations.is_empty() => {
+            return Ok(true);
+        }
+        Err(e) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
         _ => {
-            return Err((
-                "expected identifier here (context: amended_object)".to_owned(),
+            return Err(PklError::syntax(
+                "unexpected token here (context: object)",
                 lexer.span(),
             ));
         }
-    };
+    }
+
+    Ok(false)
+}
+
+/// Parses the key of an object entry, e.g. `"birds"` in `["birds"] { ... }`, up to its closing bracket.
+///
+/// Function called after an opening bracket.
+fn parse_entry_key<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<&'a str> {
+    fn parse_key<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<(&'a str, Range<usize>)> {
+        parse_string!(
+            lexer,
+            "unexpected token here, expected a string key (context: entry)",
+            "Missing entry key"
+        )
+    }
+
+    let (key, _) = parse_key(lexer)?;
+
+    loop {
+        match lexer.next() {
+            Some(Ok(PklToken::Space)) => continue,
+            Some(Ok(PklToken::CloseBracket)) => return Ok(key),
+            Some(Err(e)) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+            Some(_) => {
+                return Err(PklError::syntax(
+                    "unexpected token here, expected close bracket (context: entry)",
+                    lexer.span(),
+                ))
+            }
+            None => return Err(PklError::syntax("Missing close bracket", lexer.span())),
+        }
+    }
+}
+
+fn parse_amended_object<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<AstPklValue<'a>> {
+    let start = lexer.span().start;
+
+    let amended_expr = parse_parenthesized_expr(lexer)?;
 
     while let Some(token) = lexer.next() {
         match token {
@@ -600,43 +1172,330 @@ fn parse_amended_object<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<As
                 let object = parse_object(lexer)?;
                 let end = lexer.span().end;
                 return Ok(AstPklValue::AmendingObject(
-                    amended_object_name,
+                    Box::new(amended_expr),
                     object,
                     start..end,
                 ));
             }
-            Err(e) => return Err((e.to_string(), lexer.span())),
+            Err(e) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
             _ => {
-                return Err((
-                    "expected open brace here (context: amended_object)".to_owned(),
+                return Err(PklError::syntax(
+                    "expected open brace here (context: amended_object)",
                     lexer.span(),
                 ));
             }
         }
     }
 
-    Err((
-        "expected open brace (context: amended_object)".to_owned(),
+    Err(PklError::syntax(
+        "expected open brace (context: amended_object)",
         lexer.span(),
     ))
 }
 
+/// Parses an expression followed by its member accesses, up to the closing parenthesis.
+///
+/// Function called after an opening parenthesis, consumes the closing one.
+fn parse_parenthesized_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExpr<'a>> {
+    let mut expr = parse_expr(lexer)?;
+
+    loop {
+        match lexer.next() {
+            Some(Ok(PklToken::Dot)) => {
+                let expr_member = parse_member_expr_member(lexer)?;
+                let expr_start = expr.span().start;
+                let expr_end = expr_member.span().end;
+
+                expr = PklExpr::MemberExpression(Box::new(expr), expr_member, expr_start..expr_end);
+            }
+            Some(Ok(PklToken::CloseParen)) => return Ok(expr),
+            Some(Ok(PklToken::Space))
+            | Some(Ok(PklToken::NewLine))
+            | Some(Ok(PklToken::DocComment(_)))
+            | Some(Ok(PklToken::LineComment(_)))
+            | Some(Ok(PklToken::MultilineComment(_))) => continue,
+            Some(Err(e)) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+            Some(_) => {
+                return Err(PklError::syntax(
+                    "unexpected token here, expected close parenthesis",
+                    lexer.span(),
+                ))
+            }
+            None => return Err(PklError::syntax("Missing close parenthesis", lexer.span())),
+        }
+    }
+}
+
+/// Builds the value of the string literal `raw` that was just lexed,
+/// decoding its escape sequences and parsing the expressions it interpolates, if any.
+///
+/// The indentation of the closing delimiter of a multiline string is stripped from each of its lines.
+///
+/// `pounds` is the number of `#` of the string delimiters.
+fn parse_string_literal<'a>(
+    lexer: &mut Lexer<'a, PklToken<'a>>,
+    raw: &'a str,
+    multiline: bool,
+    pounds: usize,
+) -> PklResult<AstPklValue<'a>> {
+    let span = lexer.span();
+    let escape_start = format!("\\{}", "#".repeat(pounds));
+    let interpolation_start = format!("{escape_start}(");
+
+    // offset of the string content in the source
+    let offset = span.start + pounds + if multiline { 4 } else { 1 };
+    let indent = if multiline {
+        let before_delimiter = &lexer.source()[..span.end - pounds - 3];
+        &before_delimiter[before_delimiter.rfind('\n').map_or(0, |i| i + 1)..]
+    } else {
+        ""
+    };
+
+    if !raw.contains(&interpolation_start) {
+        let value = parse_literal_part(raw, offset, true, true, indent, pounds)?;
+        if multiline {
+            return Ok(AstPklValue::MultiLineString(value, span));
+        }
+        return Ok(AstPklValue::String(value, span));
+    }
+
+    let mut parts = Vec::new();
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while let Some(index) = raw[i..].find(&escape_start) {
+        i += index;
+        if !raw[i..].starts_with(&interpolation_start) {
+            // skip the escape sequence start and the escaped character
+            i += escape_start.len() + 1;
+            continue;
+        }
+
+        if literal_start < i {
+            let value = parse_literal_part(
+                &raw[literal_start..i],
+                offset + literal_start,
+                literal_start == 0,
+                false,
+                indent,
+                pounds,
+            )?;
+            parts.push(StringPart::Literal(
+                value,
+                offset + literal_start..offset + i,
+            ));
+        } else if literal_start == 0 && !indent.is_empty() {
+            return Err(PklError::syntax(
+                "Line must match or exceed the indentation of the closing delimiter",
+                offset..offset,
+            ));
+        }
+
+        let expr_start = offset + i;
+        let mut sub_lexer = PklToken::lexer(lexer.source());
+        sub_lexer.bump(expr_start + interpolation_start.len());
+        let expr = parse_parenthesized_expr(&mut sub_lexer)?;
+        let expr_end = sub_lexer.span().end;
+
+        parts.push(StringPart::Expr(expr, expr_start..expr_end));
+        i = expr_end - offset;
+        literal_start = i;
+    }
+
+    if literal_start < raw.len() {
+        let value = parse_literal_part(
+            &raw[literal_start..],
+            offset + literal_start,
+            literal_start == 0,
+            true,
+            indent,
+            pounds,
+        )?;
+        parts.push(StringPart::Literal(
+            value,
+            offset + literal_start..offset + raw.len(),
+        ));
+    }
+
+    Ok(AstPklValue::InterpolatedString(parts, span))
+}
+
+/// Strips `indent` from the lines of a literal piece of a string, then decodes its escape sequences.
+///
+/// * `offset` - The position of `raw` in the source.
+/// * `starts_line` - Whether `raw` starts at the beginning of a line.
+/// * `ends_content` - Whether `raw` ends the string content, its last line then being complete.
+fn parse_literal_part<'a>(
+    raw: &'a str,
+    offset: usize,
+    starts_line: bool,
+    ends_content: bool,
+    indent: &str,
+    pounds: usize,
+) -> PklResult<Cow<'a, str>> {
+    if indent.is_empty() {
+        return Ok(decode_escapes(raw, pounds));
+    }
+
+    let mut stripped = String::with_capacity(raw.len());
+    let mut line_start = 0;
+
+    for (i, line) in raw.split_inclusive('\n').enumerate() {
+        let content = line.trim_end_matches('\n');
+        let is_complete = line.ends_with('\n') || ends_content;
+
+        if i == 0 && !starts_line {
+            stripped.push_str(line);
+        } else if let Some(rest) = line.strip_prefix(indent) {
+            stripped.push_str(rest);
+        } else if is_complete && indent.starts_with(content) {
+            // blank line, less indented than the closing delimiter
+            stripped.push_str(&line[content.len()..]);
+        } else {
+            return Err(PklError::syntax(
+                "Line must match or exceed the indentation of the closing delimiter",
+                offset + line_start..offset + line_start + content.len(),
+            ));
+        }
+        line_start += line.len();
+    }
+
+    // the next line starts with an interpolated expression
+    if raw.ends_with('\n') && !ends_content {
+        return Err(PklError::syntax(
+            "Line must match or exceed the indentation of the closing delimiter",
+            offset + raw.len()..offset + raw.len(),
+        ));
+    }
+
+    Ok(Cow::Owned(decode_escapes(&stripped, pounds).into_owned()))
+}
+
+/// Whether `token` ends a blank line, `is_blank` tracking whether the current line only has spaces.
+fn is_blank_line(token: &Result<PklToken, LexingError>, is_blank: &mut bool) -> bool {
+    let ends_blank_line = *is_blank && matches!(token, Ok(PklToken::NewLine));
+    *is_blank = match token {
+        Ok(PklToken::NewLine) => true,
+        Ok(PklToken::Space) => *is_blank,
+        _ => false,
+    };
+
+    return ends_blank_line;
+}
+
+/// Joins the lines of consecutive doc comments, removing the space following each `///`.
+fn doc_text(lines: Vec<&str>) -> Option<Cow<'_, str>> {
+    let mut lines = lines.into_iter().map(|line| {
+        line.strip_prefix(' ')
+            .unwrap_or(line)
+            .trim_end_matches('\r')
+    });
+
+    let first = lines.next()?;
+    let rest: Vec<&str> = lines.collect();
+    if rest.is_empty() {
+        return Some(Cow::Borrowed(first));
+    }
+
+    Some(Cow::Owned(format!("{}\n{}", first, rest.join("\n"))))
+}
+
+/// Parses an annotation, after its `@`: `@Deprecated`, `@Deprecated { message = "..." }`.
+///
+/// The body of the annotation must start on the same line as its name.
+fn parse_annotation<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Annotation<'a>> {
+    let start = lexer.span().start;
+    let name_start = lexer.span().end;
+
+    let mut name_end = match lexer.next() {
+        Some(Ok(PklToken::Identifier(_))) | Some(Ok(PklToken::IllegalIdentifier(_))) => {
+            lexer.span().end
+        }
+        Some(Err(e)) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
+        _ => {
+            return Err(PklError::syntax(
+                "expected an annotation name after '@'",
+                lexer.span(),
+            ))
+        }
+    };
+
+    loop {
+        // tokens following the name are only consumed if they belong to the annotation
+        let mut lookahead = lexer.clone();
+        match lookahead.next() {
+            Some(Ok(PklToken::Dot)) => match lookahead.next() {
+                Some(Ok(PklToken::Identifier(_))) | Some(Ok(PklToken::IllegalIdentifier(_))) => {
+                    name_end = lookahead.span().end;
+                    *lexer = lookahead;
+                }
+                _ => {
+                    return Err(PklError::syntax(
+                        "unexpected token here, expected an identifier (context: annotation)",
+                        lookahead.span(),
+                    ))
+                }
+            },
+            token @ (Some(Ok(PklToken::Space)) | Some(Ok(PklToken::OpenBrace))) => {
+                let mut token = token;
+                while let Some(Ok(PklToken::Space)) = token {
+                    token = lookahead.next();
+                }
+                if !matches!(token, Some(Ok(PklToken::OpenBrace))) {
+                    break;
+                }
+
+                *lexer = lookahead;
+                let body = parse_object(lexer)?;
+                let end = body.1.end;
+
+                return Ok(Annotation(
+                    &lexer.source()[name_start..name_end],
+                    Some(body),
+                    start..end,
+                ));
+            }
+            _ => break,
+        }
+    }
+
+    Ok(Annotation(
+        &lexer.source()[name_start..name_end],
+        None,
+        start..name_end,
+    ))
+}
+
 /* ANCHOR: const */
 /// Parse a token stream into a Pkl const Statement.
 fn parse_const<'a>(
     lexer: &mut Lexer<'a, PklToken<'a>>,
     name: &'a str,
+    modifiers: Modifiers,
+    doc: Option<Cow<'a, str>>,
+    annotations: Vec<Annotation<'a>>,
+    start: Option<usize>,
 ) -> PklResult<PklStatement<'a>> {
-    let start = lexer.span().start;
+    let start = start.unwrap_or(lexer.span().start);
     let value = parse_const_expr(lexer)?;
     let end = lexer.span().end;
 
-    Ok(PklStatement::Constant(name, value, start..end))
+    Ok(PklStatement::Constant {
+        name,
+        value,
+        modifiers,
+        doc,
+        annotations,
+        span: start..end,
+    })
 }
 /* ANCHOR_END: const */
 
-/// Function called after 'import' keyword.
-fn parse_import<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklStatement<'a>> {
+/// Function called after 'import' or 'import*' keyword.
+fn parse_import<'a>(
+    lexer: &mut Lexer<'a, PklToken<'a>>,
+    is_glob: bool,
+) -> PklResult<PklStatement<'a>> {
     let start = lexer.span().start;
 
     fn parse_value<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<(&'a str, Range<usize>)> {
@@ -649,9 +1508,51 @@ fn parse_import<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklStateme
 
     let (value, rng) = parse_value(lexer)?;
 
+    if is_glob {
+        return Ok(PklStatement::GlobImport(value, None, start..rng.end));
+    }
     return Ok(PklStatement::Import(value, None, start..rng.end));
 }
 
+/// Function called after 'module' keyword.
+fn parse_module_clause<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklStatement<'a>> {
+    let start = lexer.span().start;
+
+    fn parse_name<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Identifier<'a>> {
+        parse_identifier!(
+            lexer,
+            "unexpected token here, expected a module name (context: module)",
+            "Missing module name"
+        )
+    }
+
+    let Identifier(name, rng) = parse_name(lexer)?;
+
+    Ok(PklStatement::ModuleClause(name, start..rng.end))
+}
+
+/// Function called after 'amends' or 'extends' keyword.
+fn parse_amends_clause<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklStatement<'a>> {
+    let start = lexer.span().start;
+    let is_amends = lexer.slice() == "amends";
+
+    fn parse_value<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<(&'a str, Range<usize>)> {
+        parse_string!(
+            lexer,
+            "unexpected token here, expected a module uri (context: amends)",
+            "Missing module uri"
+        )
+    }
+
+    let (value, rng) = parse_value(lexer)?;
+
+    if is_amends {
+        Ok(PklStatement::Amends(value, start..rng.end))
+    } else {
+        Ok(PklStatement::Extends(value, start..rng.end))
+    }
+}
+
 /* ANCHOR: const_expr */
 /// Parse a token stream into a Pkl Expr after an identifier.
 fn parse_const_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExpr<'a>> {
@@ -672,16 +1573,16 @@ fn parse_const_expr<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<PklExp
                 continue;
             }
             Some(Err(e)) => {
-                return Err((e.to_string(), lexer.span()));
+                return Err(PklError::lexing(e.to_string(), e.span(lexer.span())));
             }
             Some(_) => {
-                return Err((
-                    "unexpected token here (context: constant)".to_owned(),
+                return Err(PklError::syntax(
+                    "unexpected token here (context: constant)",
                     lexer.span(),
                 ));
             }
             None => {
-                return Err(("Expected '='".to_owned(), lexer.span()));
+                return Err(PklError::syntax("Expected '='", lexer.span()));
             }
         }
     }
@@ -701,15 +1602,15 @@ fn parse_class_instance<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Pk
             | Some(Ok(PklToken::DocComment(_)))
             | Some(Ok(PklToken::LineComment(_)))
             | Some(Ok(PklToken::MultilineComment(_))) => continue,
-            Some(Err(e)) => return Err((e.to_string(), lexer.span())),
+            Some(Err(e)) => return Err(PklError::lexing(e.to_string(), e.span(lexer.span()))),
             Some(_) => {
-                return Err((
+                return Err(PklError::syntax(
                     "unexpected token here (context: class_instance), expected identifier"
                         .to_owned(),
                     lexer.span(),
                 ));
             }
-            None => return Err(("Expected identifier".to_owned(), lexer.span())),
+            None => return Err(PklError::syntax("Expected identifier", lexer.span())),
         }
     };
 
@@ -732,17 +1633,89 @@ fn parse_class_instance<'a>(lexer: &mut Lexer<'a, PklToken<'a>>) -> PklResult<Pk
                 continue;
             }
             Some(Err(e)) => {
-                return Err((e.to_string(), lexer.span()));
+                return Err(PklError::lexing(e.to_string(), e.span(lexer.span())));
             }
             Some(_) => {
-                return Err((
-                    "unexpected token here (context: constant)".to_owned(),
+                return Err(PklError::syntax(
+                    "unexpected token here (context: constant)",
                     lexer.span(),
                 ));
             }
             None => {
-                return Err(("Expected '='".to_owned(), lexer.span()));
+                return Err(PklError::syntax("Expected '='", lexer.span()));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_literal_part_strips_indentation() {
+        let part = parse_literal_part("  a\n    b", 0, true, true, "  ", 0);
+        assert_eq!(part, Ok(Cow::Borrowed("a\n  b")));
+    }
+
+    #[test]
+    fn parse_literal_part_keeps_text_following_an_interpolation() {
+        let part = parse_literal_part(" x\n  y", 0, false, true, "  ", 0);
+        assert_eq!(part, Ok(Cow::Borrowed(" x\ny")));
+    }
+
+    #[test]
+    fn parse_literal_part_allows_less_indented_blank_lines() {
+        let part = parse_literal_part("  a\n\n \n  b", 0, true, true, "  ", 0);
+        assert_eq!(part, Ok(Cow::Borrowed("a\n\n\nb")));
+    }
+
+    #[test]
+    fn parse_literal_part_decodes_escapes_after_stripping() {
+        let part = parse_literal_part("  \\t\n  \\\"", 0, true, true, "  ", 0);
+        assert_eq!(part, Ok(Cow::Borrowed("\t\n\"")));
+
+        let part = parse_literal_part("  \\#t", 0, true, true, "  ", 1);
+        assert_eq!(part, Ok(Cow::Borrowed("\t")));
+    }
+
+    #[test]
+    fn parse_literal_part_rejects_less_indented_lines() {
+        let error = parse_literal_part("  a\n b", 10, true, true, "  ", 0).unwrap_err();
+        assert_eq!(error.span(), 14..16);
+
+        // the line continues with an interpolated expression, it cannot be blank
+        let error = parse_literal_part("  a\n", 10, true, false, "  ", 0).unwrap_err();
+        assert_eq!(error.span(), 14..14);
+    }
+
+    #[test]
+    fn parse_pkl_recovering_reports_errors_of_each_object_member() {
+        let source = "a {\n  x = )\n  y = 2\n  z = ]\n}\nb = 1\n";
+        let (statements, errors) = parse_pkl_recovering(&mut PklToken::lexer(source));
+
+        let spans: Vec<_> = errors.iter().map(PklError::span).collect();
+        assert_eq!(spans, vec![10..11, 26..27]);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[0], PklStatement::Error(0..29));
+        assert!(matches!(
+            statements[1],
+            PklStatement::Constant { name: "b", .. }
+        ));
+    }
+
+    #[test]
+    fn parse_pkl_recovering_skips_objects_opened_by_a_member() {
+        let source = "a {\n  x = f({ y = 1 })\n  z = ]\n}\n";
+        let (_, errors) = parse_pkl_recovering(&mut PklToken::lexer(source));
+
+        let spans: Vec<_> = errors.iter().map(PklError::span).collect();
+        assert_eq!(spans, vec![12..13, 29..30]);
+    }
+
+    #[test]
+    fn parse_literal_part_only_decodes_without_indentation() {
+        let part = parse_literal_part("a\\nb", 0, true, true, "", 0);
+        assert_eq!(part, Ok(Cow::Borrowed("a\nb")));
+    }
+}
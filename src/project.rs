@@ -0,0 +1,902 @@
+use crate::{
+    package::{compare_versions, PackageCache, PackageUri},
+    parser::{AstPklValue, FuncCall, Identifier, PklExpr, PklResult, PklStatement},
+    Pkl, PklError, PklValue,
+};
+use serde_json::{json, Value};
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fs,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+};
+
+/// The file name of projects.
+const PROJECT_FILE: &str = "PklProject";
+/// The file name of the lockfile written next to project files.
+const LOCKFILE: &str = "PklProject.deps.json";
+
+/// The module amended by project files, `pkl:Project`.
+pub(crate) const PROJECT_SCHEMA: &str = r#"module pkl.Project
+
+/// The package published from the project: its `name`, `baseUri`, `version` and `packageZipUrl`.
+package = null
+
+/// The dependencies of the project, keyed by the alias they are imported with, `@alias/module.pkl`:
+/// `["birds"] { uri = "package://example.com/birds@1.0.0" }` for packages,
+/// `["fruit"] = import("../fruit/PklProject")` for other projects.
+dependencies {}
+
+/// The settings of the evaluator.
+evaluatorSettings {}
+"#;
+
+/// A dependency as declared by a project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Declared {
+    /// A package, e.g. `["birds"] { uri = "package://example.com/birds@1.0.0" }`.
+    Remote(PackageUri),
+    /// Another project, given by its directory, e.g. `["fruit"] = import("../fruit/PklProject")`.
+    Local(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Dependency {
+    alias: String,
+    declared: Declared,
+    /// The span of its declaration in the project file.
+    span: Range<usize>,
+}
+
+/// The version of a package depended upon, shared by every project and package
+/// depending on the same major version of the package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Resolved {
+    /// A package of the package cache, with the SHA-256 checksum of its metadata.
+    Remote(PackageUri, String),
+    /// A local project publishing the package, in a directory.
+    Local(PackageUri, PathBuf),
+}
+
+/// A Pkl project: a directory whose `PklProject` file, a module amending `pkl:Project`,
+/// declares the packages and other projects its modules import with `@alias/module.pkl`.
+///
+/// ```pkl
+/// amends "pkl:Project"
+///
+/// dependencies {
+///   ["birds"] {
+///     uri = "package://example.com/birds@1.0.0"
+///   }
+///   ["fruit"] = import("../fruit/PklProject")
+/// }
+/// ```
+///
+/// The dependencies, transitive ones included, are resolved with [`PklProject::resolve`]
+/// to the highest version required of each major version of a package,
+/// and written to the `PklProject.deps.json` lockfile with [`PklProject::write_lockfile`],
+/// which is honored by later loads. Modules are then evaluated in the project with
+/// [`crate::FileModuleResolver::with_project`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PklProject {
+    /// The absolute path of the project file.
+    file: PathBuf,
+    source: String,
+    /// The package published from the project, if any.
+    package: Option<PackageUri>,
+    dependencies: Vec<Dependency>,
+    /// The projects declared as local dependencies, transitive ones included.
+    locals: Vec<PklProject>,
+    /// The resolved dependencies, keyed by their canonical URI, e.g. `package://example.com/birds@1`.
+    resolved: Option<BTreeMap<String, Resolved>>,
+}
+
+impl PklProject {
+    /// Loads the project of the directory or `PklProject` file at `path`,
+    /// along with its lockfile, if any.
+    pub fn load(path: impl AsRef<Path>) -> PklResult<Self> {
+        let mut project = Self::load_file(path.as_ref())?;
+
+        let mut pending = project.local_dirs();
+        while let Some(dir) = pending.pop() {
+            let is_loaded =
+                dir == project.dir() || project.locals.iter().any(|local| local.dir() == dir);
+            if !is_loaded {
+                let local = Self::load_file(&dir)?;
+                pending.extend(local.local_dirs());
+                project.locals.push(local);
+            }
+        }
+
+        let lockfile = project.lockfile();
+        if lockfile.is_file() {
+            project.resolved = Some(project.read_lockfile(&lockfile)?);
+        }
+
+        Ok(project)
+    }
+
+    /// The directory of the project.
+    pub fn dir(&self) -> &Path {
+        self.file.parent().unwrap_or(&self.file)
+    }
+
+    /// The path of the `PklProject.deps.json` lockfile of the project.
+    pub fn lockfile(&self) -> PathBuf {
+        self.dir().join(LOCKFILE)
+    }
+
+    /// Whether the dependencies of the project are resolved, by its lockfile or [`PklProject::resolve`].
+    pub fn is_resolved(&self) -> bool {
+        self.resolved.is_some()
+    }
+
+    /// Evaluates a project file, without loading its local dependencies.
+    fn load_file(path: &Path) -> PklResult<Self> {
+        let file = match path.is_dir() {
+            true => path.join(PROJECT_FILE),
+            false => path.to_owned(),
+        };
+        let file = normalize(&std::path::absolute(&file).unwrap_or(file));
+        let name = file.display().to_string();
+        let source = fs::read_to_string(&file)
+            .map_err(|e| project_error(format!("Cannot read project {}: {}", name, e)))?;
+
+        let mut pkl = Pkl::new();
+        pkl.set_source_name(name.as_str());
+        pkl.set_module_uri(name.as_str());
+        pkl.parse(&source)?;
+        let statements = pkl.generate_ast(&source)?;
+        let dir = file.parent().unwrap_or(&file).to_owned();
+
+        let error = |message: String, span: Range<usize>| {
+            PklError::evaluation(message, span).with_source(Some(&name), &source)
+        };
+        let span_of = |property: &str| {
+            statements
+                .iter()
+                .find_map(|statement| match statement {
+                    PklStatement::Constant { name, span, .. } if *name == property => {
+                        Some(span.to_owned())
+                    }
+                    _ => None,
+                })
+                .unwrap_or(0..0)
+        };
+
+        let package = match pkl.get("package") {
+            None | Some(PklValue::Null) => None,
+            Some(PklValue::Object(package)) => {
+                let (Some(PklValue::String(base_uri)), Some(PklValue::String(version))) =
+                    (package.get("baseUri"), package.get("version"))
+                else {
+                    return Err(error(
+                        "The `package` of a project must have a `baseUri` and a `version`"
+                            .to_owned(),
+                        span_of("package"),
+                    ));
+                };
+
+                let uri = PackageUri::parse(&format!("{}@{}", base_uri, version))
+                    .map_err(|e| error(e, span_of("package")))?;
+                Some(uri)
+            }
+            Some(value) => {
+                return Err(error(
+                    format!(
+                        "The `package` of a project must be an object, found {}",
+                        value.get_type()
+                    ),
+                    span_of("package"),
+                ))
+            }
+        };
+
+        // the declarations of the dependencies, telling local ones apart
+        let declarations = statements.iter().find_map(|statement| match statement {
+            PklStatement::Constant {
+                name: "dependencies",
+                value: PklExpr::Value(AstPklValue::Object((members, _))),
+                ..
+            } => Some(members),
+            _ => None,
+        });
+
+        let mut dependencies = Vec::new();
+        let values = match pkl.get("dependencies") {
+            Some(PklValue::Object(values)) => values.clone(),
+            _ => Default::default(),
+        };
+        let mut aliases: Vec<&str> = values.keys().map(|alias| alias.as_ref()).collect();
+        aliases.sort();
+
+        for alias in aliases {
+            let expr = declarations
+                .and_then(|members| members.get(alias))
+                .map(|member| &member.value);
+            let span = expr.map_or_else(|| span_of("dependencies"), PklExpr::span);
+
+            let declared = match (expr, &values[alias]) {
+                (Some(PklExpr::FuncCall(FuncCall(Identifier("import", _), args, _))), _) => {
+                    match args.as_slice() {
+                        [PklExpr::Value(AstPklValue::String(path, _))] => {
+                            let file = normalize(&dir.join(path.as_ref()));
+                            Declared::Local(file.parent().unwrap_or(&file).to_owned())
+                        }
+                        _ => {
+                            return Err(error(
+                                format!(
+                                    "Local dependency `{}` must import a project file by its path",
+                                    alias
+                                ),
+                                span,
+                            ))
+                        }
+                    }
+                }
+                (_, PklValue::Object(dependency)) => match dependency.get("uri") {
+                    Some(PklValue::String(uri)) => {
+                        let uri = PackageUri::parse(uri.as_str())
+                            .map_err(|e| error(e, span.to_owned()))?;
+                        if uri.module.is_some() {
+                            return Err(error(
+                                format!(
+                                    "The URI of dependency `{}` must name a package, not a module",
+                                    alias
+                                ),
+                                span,
+                            ));
+                        }
+                        Declared::Remote(uri)
+                    }
+                    _ => {
+                        return Err(error(
+                            format!("Dependency `{}` must have a `uri`", alias),
+                            span,
+                        ))
+                    }
+                },
+                (_, value) => {
+                    return Err(error(
+                        format!(
+                            "Dependency `{}` must be an object or a project import, found {}",
+                            alias,
+                            value.get_type()
+                        ),
+                        span,
+                    ))
+                }
+            };
+
+            dependencies.push(Dependency {
+                alias: alias.to_string(),
+                declared,
+                span,
+            });
+        }
+
+        // the evaluated project, its statements and values borrow its source, moved below,
+        // and hashbrown maps may use their borrows when dropped
+        drop((pkl, statements, values));
+
+        Ok(Self {
+            file,
+            source,
+            package,
+            dependencies,
+            locals: Vec::new(),
+            resolved: None,
+        })
+    }
+
+    fn local_dirs(&self) -> Vec<PathBuf> {
+        self.dependencies
+            .iter()
+            .filter_map(|dependency| match &dependency.declared {
+                Declared::Local(dir) => Some(dir.to_owned()),
+                Declared::Remote(_) => None,
+            })
+            .collect()
+    }
+
+    /// Reports an error at `span` of the project file.
+    fn error(&self, message: impl Into<String>, span: Range<usize>) -> PklError {
+        PklError::import(message, span)
+            .with_source(Some(&self.file.display().to_string()), &self.source)
+    }
+
+    /// Resolves the dependencies of the project, transitive ones included,
+    /// reading the metadata of packages from the package cache directory,
+    /// `~/.pkl/cache` if `package_cache` is `None`.
+    ///
+    /// Local projects take precedence over the packages they publish,
+    /// and the highest version required of each major version of a package is selected.
+    pub fn resolve(&mut self, package_cache: Option<&Path>) -> PklResult<()> {
+        let cache = package_cache
+            .map(Path::to_owned)
+            .or_else(PackageCache::default_dir)
+            .map(PackageCache::new)
+            .ok_or_else(|| {
+                project_error("Cannot find the package cache, the home directory is unknown")
+            })?;
+
+        let mut resolved = BTreeMap::new();
+        for local in self.locals.iter() {
+            let Some(package) = &local.package else {
+                return Err(project_error(format!(
+                    "Project {} must declare a `package` to be a dependency",
+                    local.dir().display()
+                )));
+            };
+            resolved.insert(
+                package.canonical(),
+                Resolved::Local(package.to_owned(), local.dir().to_owned()),
+            );
+        }
+
+        // the packages to resolve, along with what requires them
+        let mut pending: Vec<(PackageUri, Requirer)> = Vec::new();
+        for project in [&*self].into_iter().chain(self.locals.iter()) {
+            for dependency in project.dependencies.iter() {
+                if let Declared::Remote(uri) = &dependency.declared {
+                    let requirer = Requirer::Project(project, dependency.span.to_owned());
+                    pending.push((uri.to_owned(), requirer));
+                }
+            }
+        }
+
+        let mut i = 0;
+        while i < pending.len() {
+            let (uri, requirer) = &pending[i];
+            let key = uri.canonical();
+            let is_selected = match resolved.get(&key) {
+                Some(Resolved::Local(_, _)) => true,
+                Some(Resolved::Remote(selected, _)) => {
+                    compare_versions(&selected.version, &uri.version) != Ordering::Less
+                }
+                None => false,
+            };
+
+            if !is_selected {
+                let (metadata, checksum) = cache.metadata(uri).map_err(|e| requirer.error(e))?;
+                let dependencies =
+                    package_dependencies(&metadata).map_err(|e| requirer.error(e))?;
+                let package = PackageUri {
+                    checksum: None,
+                    module: None,
+                    ..uri.to_owned()
+                };
+
+                for dependency in dependencies.into_values() {
+                    pending.push((dependency, Requirer::Package(package.package())));
+                }
+                resolved.insert(key, Resolved::Remote(package, checksum));
+            }
+            i += 1;
+        }
+
+        self.resolved = Some(resolved);
+
+        Ok(())
+    }
+
+    /// Writes the resolved dependencies to the `PklProject.deps.json` lockfile of the project.
+    pub fn write_lockfile(&self) -> PklResult<()> {
+        let resolved = self.resolved.as_ref().ok_or_else(|| {
+            project_error(format!(
+                "The dependencies of project {} are not resolved",
+                self.dir().display()
+            ))
+        })?;
+
+        let mut dependencies = serde_json::Map::new();
+        for (key, dependency) in resolved.iter() {
+            let entry = match dependency {
+                Resolved::Remote(package, checksum) => json!({
+                    "type": "remote",
+                    "uri": project_package_uri(package),
+                    "checksums": { "sha256": checksum },
+                }),
+                Resolved::Local(package, dir) => json!({
+                    "type": "local",
+                    "uri": project_package_uri(package),
+                    "path": relative_path(self.dir(), dir),
+                }),
+            };
+            dependencies.insert(key.to_owned(), entry);
+        }
+
+        let lockfile = json!({
+            "schemaVersion": 1,
+            "resolvedDependencies": dependencies,
+        });
+        let path = self.lockfile();
+        let content = serde_json::to_string_pretty(&lockfile).unwrap_or_default() + "\n";
+
+        fs::write(&path, content)
+            .map_err(|e| project_error(format!("Cannot write {}: {}", path.display(), e)))
+    }
+
+    fn read_lockfile(&self, path: &Path) -> PklResult<BTreeMap<String, Resolved>> {
+        let invalid = |reason: String| {
+            project_error(format!("Invalid lockfile {}: {}", path.display(), reason))
+        };
+
+        let content = fs::read(path)
+            .map_err(|e| project_error(format!("Cannot read {}: {}", path.display(), e)))?;
+        let lockfile: Value =
+            serde_json::from_slice(&content).map_err(|e| invalid(e.to_string()))?;
+        if lockfile["schemaVersion"] != 1 {
+            return Err(invalid(
+                "unsupported `schemaVersion`, expected 1".to_owned(),
+            ));
+        }
+        let entries = lockfile["resolvedDependencies"]
+            .as_object()
+            .ok_or_else(|| invalid("missing `resolvedDependencies`".to_owned()))?;
+
+        let mut resolved = BTreeMap::new();
+        for (key, entry) in entries.iter() {
+            let uri = entry["uri"]
+                .as_str()
+                .and_then(|uri| uri.strip_prefix("projectpackage://"))
+                .ok_or_else(|| {
+                    invalid(format!("missing the `projectpackage://` URI of `{}`", key))
+                })?;
+            let package = PackageUri::parse(&format!("package://{}", uri)).map_err(invalid)?;
+
+            let dependency = match (entry["type"].as_str(), entry["checksums"]["sha256"].as_str(), entry["path"].as_str()) {
+                (Some("remote"), Some(checksum), _) => Resolved::Remote(package, checksum.to_owned()),
+                (Some("local"), _, Some(path)) => {
+                    Resolved::Local(package, normalize(&self.dir().join(path)))
+                }
+                _ => {
+                    return Err(invalid(format!(
+                        "expected a remote dependency with a checksum or a local one with a path for `{}`",
+                        key
+                    )))
+                }
+            };
+            resolved.insert(key.to_owned(), dependency);
+        }
+
+        Ok(resolved)
+    }
+
+    /// Resolves the import of `@alias/path/to/module.pkl` by the module at `base`,
+    /// returning the URI of the imported module.
+    pub(crate) fn resolve_dependency(
+        &self,
+        uri: &str,
+        base: Option<&str>,
+        cache: &PackageCache,
+    ) -> Result<String, String> {
+        let (alias, path) = split_dependency_import(uri)?;
+
+        // the packages import their own dependencies, in the versions selected for the project
+        let key = match base {
+            Some(base) if base.starts_with("package://") => {
+                let package = PackageUri::parse(base)?;
+                let (metadata, _) = cache.metadata(&package)?;
+                package_dependencies(&metadata)?
+                    .get(alias)
+                    .map(PackageUri::canonical)
+                    .ok_or_else(|| {
+                        format!(
+                            "Cannot import `{}`, package `{}` has no dependency `{}`",
+                            uri,
+                            package.package(),
+                            alias
+                        )
+                    })?
+            }
+            _ => {
+                let project = self.project_of(base);
+                let dependency = project
+                    .dependencies
+                    .iter()
+                    .find(|dependency| dependency.alias == alias)
+                    .ok_or_else(|| {
+                        format!(
+                            "Cannot import `{}`, project {} has no dependency `{}`",
+                            uri,
+                            project.dir().display(),
+                            alias
+                        )
+                    })?;
+
+                match &dependency.declared {
+                    Declared::Remote(package) => package.canonical(),
+                    Declared::Local(dir) => self
+                        .locals
+                        .iter()
+                        .find(|local| local.dir() == dir)
+                        .and_then(|local| local.package.as_ref())
+                        .map(PackageUri::canonical)
+                        .ok_or_else(|| {
+                            format!(
+                                "Cannot import `{}`, project {} does not declare a `package`",
+                                uri,
+                                dir.display()
+                            )
+                        })?,
+                }
+            }
+        };
+
+        let resolved = self.resolved.as_ref().ok_or_else(|| {
+            format!(
+                "Cannot import `{}`, the dependencies of project {} are not resolved, resolve them to write its {}",
+                uri,
+                self.dir().display(),
+                LOCKFILE
+            )
+        })?;
+
+        match resolved.get(&key) {
+            Some(Resolved::Remote(package, checksum)) => Ok(format!(
+                "{}::sha256:{}#/{}",
+                package.package(),
+                checksum,
+                path
+            )),
+            Some(Resolved::Local(_, dir)) => Ok(dir.join(path).display().to_string()),
+            None => Err(format!(
+                "Cannot import `{}`, the {} of project {} is out of date, resolve its dependencies again",
+                uri,
+                LOCKFILE,
+                self.dir().display()
+            )),
+        }
+    }
+
+    /// The project containing the module at `base`: a local dependency, or this project.
+    fn project_of(&self, base: Option<&str>) -> &PklProject {
+        let Some(base) = base.filter(|base| !base.contains("://")) else {
+            return self;
+        };
+        let path = Path::new(base);
+        let path = normalize(&std::path::absolute(path).unwrap_or(path.to_owned()));
+
+        self.locals
+            .iter()
+            .filter(|local| path.starts_with(local.dir()))
+            .chain(
+                [self]
+                    .into_iter()
+                    .filter(|project| path.starts_with(project.dir())),
+            )
+            .max_by_key(|project| project.dir().components().count())
+            .unwrap_or(self)
+    }
+}
+
+/// What requires a package, to which errors resolving it are reported.
+enum Requirer<'p> {
+    /// A project, declaring the package at a span of its file.
+    Project(&'p PklProject, Range<usize>),
+    /// Another package, e.g. `package://example.com/birds@1.0.0`.
+    Package(String),
+}
+
+impl Requirer<'_> {
+    fn error(&self, message: String) -> PklError {
+        match self {
+            Self::Project(project, span) => project.error(message, span.to_owned()),
+            Self::Package(package) => {
+                project_error(format!("{}, required by `{}`", message, package))
+            }
+        }
+    }
+}
+
+/// Resolves the import of `@alias/path/to/module.pkl` by a module of a package, outside of any project,
+/// to the version of the dependency recorded in the metadata of the package.
+pub(crate) fn resolve_package_dependency(
+    uri: &str,
+    base: Option<&str>,
+    cache: &PackageCache,
+) -> Result<String, String> {
+    let (alias, path) = split_dependency_import(uri)?;
+    let Some(base) = base.filter(|base| base.starts_with("package://")) else {
+        return Err(format!(
+            "Cannot import `{}`, dependency imports are only supported in projects and packages",
+            uri
+        ));
+    };
+
+    let package = PackageUri::parse(base)?;
+    let (metadata, _) = cache.metadata(&package)?;
+    let dependency = package_dependencies(&metadata)?
+        .remove(alias)
+        .ok_or_else(|| {
+            format!(
+                "Cannot import `{}`, package `{}` has no dependency `{}`",
+                uri,
+                package.package(),
+                alias
+            )
+        })?;
+
+    Ok(format!("{}#/{}", dependency, path))
+}
+
+/// Splits `@alias/path/to/module.pkl` into its alias and module path.
+fn split_dependency_import(uri: &str) -> Result<(&str, &str), String> {
+    uri.strip_prefix('@')
+        .and_then(|uri| uri.split_once('/'))
+        .filter(|(alias, path)| !alias.is_empty() && !path.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "Invalid dependency import `{}`, expected `@<alias>/<module path>`",
+                uri
+            )
+        })
+}
+
+/// The dependencies recorded in the metadata of a package, by alias, with their checksum.
+fn package_dependencies(metadata: &Value) -> Result<BTreeMap<String, PackageUri>, String> {
+    let mut dependencies = BTreeMap::new();
+    let Some(entries) = metadata["dependencies"].as_object() else {
+        return Ok(dependencies);
+    };
+
+    for (alias, dependency) in entries.iter() {
+        let uri = dependency["uri"].as_str().ok_or_else(|| {
+            format!(
+                "Invalid package metadata: dependency `{}` has no `uri`",
+                alias
+            )
+        })?;
+        let mut uri = PackageUri::parse(uri)?;
+        if let Some(checksum) = dependency["checksums"]["sha256"].as_str() {
+            uri.checksum = Some(checksum.to_ascii_lowercase());
+        }
+        dependencies.insert(alias.to_owned(), uri);
+    }
+
+    Ok(dependencies)
+}
+
+/// Reports an error that does not refer to a source.
+fn project_error(message: impl Into<String>) -> PklError {
+    PklError::import(message, 0..0)
+}
+
+/// The `projectpackage://` URI identifying a package in lockfiles.
+fn project_package_uri(package: &PackageUri) -> String {
+    package
+        .package()
+        .replacen("package://", "projectpackage://", 1)
+}
+
+/// Removes the `.` and `..` components of `path`, without following symbolic links.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// The path of `to` relative to the directory `from`, both absolute, e.g. `../fruit`.
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(to.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut segments: Vec<String> = vec!["..".to_owned(); from.len() - common];
+    segments.extend(
+        to[common..]
+            .iter()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+    );
+
+    match segments.is_empty() {
+        true => ".".to_owned(),
+        false => segments.join("/"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory of the system's temporary directory, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("new-pkl-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+
+            return Self(dir);
+        }
+
+        fn write(&self, path: &str, content: &str) {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+
+        /// Writes the metadata of `package://example.com/<package>` to the package cache.
+        fn write_package(&self, package: &str, metadata: &str) {
+            let (name, version) = package.split_once('@').unwrap();
+            self.write(
+                &format!("cache/package-2/example.com/{package}/{name}@{version}.json"),
+                metadata,
+            );
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    const PROJECT: &str = "amends \"pkl:Project\"\n\ndependencies {\n  [\"birds\"] {\n    uri = \"package://example.com/birds@1.0.0\"\n  }\n  [\"fruit\"] {\n    uri = \"package://example.com/fruit@1.0.0\"\n  }\n}\n";
+
+    /// A project depending on `birds@1.0.0` and `fruit@1.0.0`, itself depending on `birds@1.2.0`,
+    /// and on `birds@2.0.0` under another alias.
+    fn project(dir: &TempDir) -> PklProject {
+        dir.write("project/PklProject", PROJECT);
+        dir.write_package("birds@1.0.0", "{}");
+        dir.write_package("birds@1.2.0", "{}");
+        dir.write_package("birds@2.0.0", "{}");
+        dir.write_package(
+            "fruit@1.0.0",
+            r#"{"dependencies": {
+                "birds": {"uri": "package://example.com/birds@1.2.0"},
+                "newBirds": {"uri": "package://example.com/birds@2.0.0"}
+            }}"#,
+        );
+
+        return PklProject::load(dir.0.join("project")).unwrap();
+    }
+
+    fn resolved_versions(project: &PklProject) -> Vec<(String, String)> {
+        project
+            .resolved
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|(key, resolved)| match resolved {
+                Resolved::Remote(package, _) | Resolved::Local(package, _) => {
+                    (key.to_owned(), package.version.to_owned())
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn versions_are_compared_semantically() {
+        assert_eq!(compare_versions("1.10.0", "1.9.0"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0+build", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn resolve_selects_the_highest_version_of_each_major_version() {
+        let dir = TempDir::new("project-resolve");
+        let mut project = project(&dir);
+        assert!(!project.is_resolved());
+
+        project.resolve(Some(&dir.0.join("cache"))).unwrap();
+
+        assert_eq!(
+            resolved_versions(&project),
+            [
+                (
+                    "package://example.com/birds@1".to_owned(),
+                    "1.2.0".to_owned()
+                ),
+                (
+                    "package://example.com/birds@2".to_owned(),
+                    "2.0.0".to_owned()
+                ),
+                (
+                    "package://example.com/fruit@1".to_owned(),
+                    "1.0.0".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn lockfiles_are_read_back_as_written() {
+        let dir = TempDir::new("project-lockfile");
+        let mut project = project(&dir);
+        project.resolve(Some(&dir.0.join("cache"))).unwrap();
+        project.write_lockfile().unwrap();
+
+        let loaded = PklProject::load(dir.0.join("project")).unwrap();
+
+        assert!(loaded.is_resolved());
+        assert_eq!(loaded.resolved, project.resolved);
+        let lockfile = fs::read_to_string(project.lockfile()).unwrap();
+        assert!(lockfile.contains("\"projectpackage://example.com/birds@1.2.0\""));
+    }
+
+    #[test]
+    fn dependency_imports_resolve_to_the_selected_version() {
+        let dir = TempDir::new("project-import");
+        let mut project = project(&dir);
+        let cache = PackageCache::new(dir.0.join("cache"));
+
+        let error = project
+            .resolve_dependency("@birds/Bird.pkl", None, &cache)
+            .unwrap_err();
+        assert!(error.contains("are not resolved"), "{error}");
+
+        project.resolve(Some(&dir.0.join("cache"))).unwrap();
+        let uri = project
+            .resolve_dependency("@birds/Bird.pkl", None, &cache)
+            .unwrap();
+        let checksum = sha256_of(
+            &dir.0
+                .join("cache/package-2/example.com/birds@1.2.0/birds@1.2.0.json"),
+        );
+        assert_eq!(
+            uri,
+            format!("package://example.com/birds@1.2.0::sha256:{checksum}#/Bird.pkl")
+        );
+
+        let error = project
+            .resolve_dependency("@trees/Oak.pkl", None, &cache)
+            .unwrap_err();
+        assert!(error.contains("has no dependency `trees`"), "{error}");
+    }
+
+    #[test]
+    fn stale_lockfiles_are_reported() {
+        let dir = TempDir::new("project-stale");
+        let mut project = project(&dir);
+        project.resolve(Some(&dir.0.join("cache"))).unwrap();
+        project.write_lockfile().unwrap();
+
+        // a dependency declared after the lockfile was written
+        dir.write_package("trees@1.0.0", "{}");
+        dir.write(
+            "project/PklProject",
+            &PROJECT.replace(
+                "\n}\n",
+                "\n  [\"trees\"] {\n    uri = \"package://example.com/trees@1.0.0\"\n  }\n}\n",
+            ),
+        );
+        let project = PklProject::load(dir.0.join("project")).unwrap();
+        let cache = PackageCache::new(dir.0.join("cache"));
+
+        assert!(project
+            .resolve_dependency("@birds/Bird.pkl", None, &cache)
+            .is_ok());
+        let error = project
+            .resolve_dependency("@trees/Oak.pkl", None, &cache)
+            .unwrap_err();
+        assert!(error.contains("is out of date"), "{error}");
+    }
+
+    fn sha256_of(path: &Path) -> String {
+        use sha2::{Digest, Sha256};
+
+        return format!("{:x}", Sha256::digest(fs::read(path).unwrap()));
+    }
+}
@@ -0,0 +1,503 @@
+use crate::{parser::PklResult, PklError, PklValue};
+use std::{borrow::Cow, fmt, str::FromStr};
+
+#[cfg(feature = "hashbrown_support")]
+use hashbrown::HashMap;
+#[cfg(not(feature = "hashbrown_support"))]
+use std::collections::HashMap;
+
+/// The formats the output of a module can be rendered to, see [`render_output`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Yaml,
+    Pkl,
+    Plist,
+    Properties,
+}
+
+impl OutputFormat {
+    /// The extension of files written in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Pkl => "pkl",
+            OutputFormat::Plist => "plist",
+            OutputFormat::Properties => "properties",
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(OutputFormat::Json),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "pkl" | "pcf" => Ok(OutputFormat::Pkl),
+            "plist" => Ok(OutputFormat::Plist),
+            "properties" => Ok(OutputFormat::Properties),
+            _ => Err(format!(
+                "unknown output format `{}`, expected json, yaml, pkl, plist or properties",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.extension())
+    }
+}
+
+/// Renders the properties of a module, such as the ones returned by [`crate::Pkl::output`], in `format`.
+///
+/// Properties and object members are sorted by name, durations and data sizes are rendered
+/// as their Pkl source, e.g. `"5.min"`, in formats lacking them.
+///
+/// Fails on values the format cannot represent: `null` in plists, lists in properties files,
+/// `NaN` and infinite floats in JSON.
+pub fn render_output(
+    properties: &HashMap<Cow<str>, PklValue>,
+    format: OutputFormat,
+) -> PklResult<String> {
+    let mut out = String::new();
+
+    match format {
+        OutputFormat::Json => {
+            render_json_object(&mut out, properties, 0)?;
+            out.push('\n');
+        }
+        OutputFormat::Yaml => render_yaml_object(&mut out, properties, 0),
+        OutputFormat::Pkl => render_pkl_object(&mut out, properties, 0),
+        OutputFormat::Plist => {
+            out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            out.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
+            out.push_str("<plist version=\"1.0\">\n");
+            render_plist_object(&mut out, properties, 0)?;
+            out.push_str("</plist>\n");
+        }
+        OutputFormat::Properties => render_properties(&mut out, properties, "")?,
+    }
+
+    Ok(out)
+}
+
+fn sorted<'h, 'a>(
+    hash: &'h HashMap<Cow<'a, str>, PklValue<'a>>,
+) -> Vec<(&'h str, &'h PklValue<'a>)> {
+    let mut members: Vec<_> = hash
+        .iter()
+        .map(|(name, value)| (name.as_ref(), value))
+        .collect();
+    members.sort_by_key(|(name, _)| *name);
+    members
+}
+
+fn members<'h, 'a>(value: &'h PklValue<'a>) -> Option<&'h HashMap<Cow<'a, str>, PklValue<'a>>> {
+    match value {
+        PklValue::Object(hash) | PklValue::ClassInstance(_, hash) => Some(hash),
+        _ => None,
+    }
+}
+
+fn unsupported(value: &PklValue, format: OutputFormat) -> PklError {
+    PklError::type_mismatch(
+        format!(
+            "Cannot render a value of type {} as {}",
+            value.get_type(),
+            format
+        ),
+        0..0,
+    )
+}
+
+fn render_json_object(
+    out: &mut String,
+    hash: &HashMap<Cow<str>, PklValue>,
+    depth: usize,
+) -> PklResult<()> {
+    if hash.is_empty() {
+        out.push_str("{}");
+        return Ok(());
+    }
+
+    out.push_str("{\n");
+    for (i, (name, value)) in sorted(hash).into_iter().enumerate() {
+        if i > 0 {
+            out.push_str(",\n");
+        }
+        out.push_str(&"  ".repeat(depth + 1));
+        out.push_str(&json_string(name));
+        out.push_str(": ");
+        render_json(out, value, depth + 1)?;
+    }
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+    out.push('}');
+
+    Ok(())
+}
+
+fn render_json(out: &mut String, value: &PklValue, depth: usize) -> PklResult<()> {
+    match value {
+        PklValue::Null => out.push_str("null"),
+        PklValue::Bool(b) => out.push_str(&b.to_string()),
+        PklValue::Int(i) => out.push_str(&i.to_string()),
+        PklValue::Float(f) if !f.is_finite() => return Err(unsupported(value, OutputFormat::Json)),
+        PklValue::Float(_) => out.push_str(&value.to_string()),
+        PklValue::String(s) => out.push_str(&json_string(s)),
+        PklValue::Duration(_) | PklValue::DataSize(_) => {
+            out.push_str(&json_string(&value.to_string()))
+        }
+        PklValue::List(values) if values.is_empty() => out.push_str("[]"),
+        PklValue::List(values) => {
+            out.push_str("[\n");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(",\n");
+                }
+                out.push_str(&"  ".repeat(depth + 1));
+                render_json(out, value, depth + 1)?;
+            }
+            out.push('\n');
+            out.push_str(&"  ".repeat(depth));
+            out.push(']');
+        }
+        PklValue::Object(hash) | PklValue::ClassInstance(_, hash) => {
+            render_json_object(out, hash, depth)?
+        }
+    }
+
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn render_yaml_object(out: &mut String, hash: &HashMap<Cow<str>, PklValue>, depth: usize) {
+    for (name, value) in sorted(hash) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&yaml_string(name));
+        out.push(':');
+        render_yaml_value(out, value, depth);
+    }
+}
+
+/// Renders a value following a key or a list dash, starting on the same line.
+fn render_yaml_value(out: &mut String, value: &PklValue, depth: usize) {
+    match value {
+        PklValue::List(values) if !values.is_empty() => {
+            out.push('\n');
+            for value in values {
+                out.push_str(&"  ".repeat(depth));
+                out.push('-');
+                render_yaml_value(out, value, depth + 1);
+            }
+        }
+        PklValue::Object(hash) | PklValue::ClassInstance(_, hash) if !hash.is_empty() => {
+            out.push('\n');
+            render_yaml_object(out, hash, depth + 1);
+        }
+        _ => {
+            out.push(' ');
+            out.push_str(&yaml_scalar(value));
+            out.push('\n');
+        }
+    }
+}
+
+fn yaml_scalar(value: &PklValue) -> String {
+    match value {
+        PklValue::Null => "null".to_owned(),
+        PklValue::Float(f) if f.is_nan() => ".nan".to_owned(),
+        PklValue::Float(f) if f.is_infinite() && *f > 0.0 => ".inf".to_owned(),
+        PklValue::Float(f) if f.is_infinite() => "-.inf".to_owned(),
+        PklValue::String(s) => yaml_string(s),
+        PklValue::Duration(_) | PklValue::DataSize(_) => yaml_string(&value.to_string()),
+        PklValue::List(_) => "[]".to_owned(),
+        PklValue::Object(_) | PklValue::ClassInstance(_, _) => "{}".to_owned(),
+        _ => value.to_string(),
+    }
+}
+
+/// Returns `s` as a YAML scalar, quoted when it would be read as another type or contains special characters.
+fn yaml_string(s: &str) -> String {
+    let is_plain = !s.is_empty()
+        && s.trim() == s
+        && !s.starts_with(|c: char| "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !s.contains(": ")
+        && !s.contains(" #")
+        && !s.ends_with(':')
+        && !s.chars().any(char::is_control)
+        && !matches!(
+            s.to_lowercase().as_str(),
+            "null"
+                | "~"
+                | "true"
+                | "false"
+                | "yes"
+                | "no"
+                | "on"
+                | "off"
+                | ".nan"
+                | ".inf"
+                | "-.inf"
+        )
+        && s.parse::<f64>().is_err();
+
+    if is_plain {
+        s.to_owned()
+    } else {
+        json_string(s)
+    }
+}
+
+fn render_pkl_object(out: &mut String, hash: &HashMap<Cow<str>, PklValue>, depth: usize) {
+    for (name, value) in sorted(hash) {
+        out.push_str(&"  ".repeat(depth));
+        out.push_str(&pkl_identifier(name));
+
+        match value {
+            PklValue::Object(hash) => {
+                out.push(' ');
+                render_pkl_body(out, hash, depth);
+            }
+            _ => {
+                out.push_str(" = ");
+                render_pkl_value(out, value, depth);
+            }
+        }
+        out.push('\n');
+    }
+}
+
+fn render_pkl_body(out: &mut String, hash: &HashMap<Cow<str>, PklValue>, depth: usize) {
+    if hash.is_empty() {
+        out.push_str("{}");
+        return;
+    }
+
+    out.push_str("{\n");
+    render_pkl_object(out, hash, depth + 1);
+    out.push_str(&"  ".repeat(depth));
+    out.push('}');
+}
+
+fn render_pkl_value(out: &mut String, value: &PklValue, depth: usize) {
+    match value {
+        PklValue::String(s) => out.push_str(&pkl_string(s)),
+        PklValue::List(values) => {
+            out.push_str("List(");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                render_pkl_value(out, value, depth);
+            }
+            out.push(')');
+        }
+        PklValue::Object(hash) => {
+            out.push_str("new Dynamic ");
+            render_pkl_body(out, hash, depth);
+        }
+        PklValue::ClassInstance(name, hash) => {
+            out.push_str(&format!("new {} ", name));
+            render_pkl_body(out, hash, depth);
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+fn pkl_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Returns `name`, quoted with backticks unless it is a regular identifier.
+fn pkl_identifier(name: &str) -> String {
+    let is_regular = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+        && !KEYWORDS.contains(&name);
+
+    if is_regular {
+        name.to_owned()
+    } else {
+        format!("`{}`", name)
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "amends",
+    "as",
+    "class",
+    "const",
+    "else",
+    "extends",
+    "external",
+    "false",
+    "fixed",
+    "for",
+    "function",
+    "hidden",
+    "if",
+    "import",
+    "in",
+    "is",
+    "let",
+    "local",
+    "module",
+    "new",
+    "null",
+    "open",
+    "out",
+    "outer",
+    "read",
+    "super",
+    "this",
+    "throw",
+    "trace",
+    "true",
+    "typealias",
+    "when",
+];
+
+fn render_plist_object(
+    out: &mut String,
+    hash: &HashMap<Cow<str>, PklValue>,
+    depth: usize,
+) -> PklResult<()> {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str("<dict>\n");
+    for (name, value) in sorted(hash) {
+        out.push_str(&"  ".repeat(depth + 1));
+        out.push_str(&format!("<key>{}</key>\n", escape_xml(name)));
+        render_plist(out, value, depth + 1)?;
+    }
+    out.push_str(&"  ".repeat(depth));
+    out.push_str("</dict>\n");
+
+    Ok(())
+}
+
+fn render_plist(out: &mut String, value: &PklValue, depth: usize) -> PklResult<()> {
+    let indent = "  ".repeat(depth);
+
+    match value {
+        PklValue::Null => return Err(unsupported(value, OutputFormat::Plist)),
+        PklValue::Bool(b) => out.push_str(&format!("{}<{}/>\n", indent, b)),
+        PklValue::Int(i) => out.push_str(&format!("{}<integer>{}</integer>\n", indent, i)),
+        PklValue::Float(_) => out.push_str(&format!("{}<real>{}</real>\n", indent, value)),
+        PklValue::String(s) => {
+            out.push_str(&format!("{}<string>{}</string>\n", indent, escape_xml(s)))
+        }
+        PklValue::Duration(_) | PklValue::DataSize(_) => out.push_str(&format!(
+            "{}<string>{}</string>\n",
+            indent,
+            escape_xml(&value.to_string())
+        )),
+        PklValue::List(values) => {
+            out.push_str(&format!("{}<array>\n", indent));
+            for value in values {
+                render_plist(out, value, depth + 1)?;
+            }
+            out.push_str(&format!("{}</array>\n", indent));
+        }
+        PklValue::Object(hash) | PklValue::ClassInstance(_, hash) => {
+            render_plist_object(out, hash, depth)?
+        }
+    }
+
+    Ok(())
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Renders objects as dotted keys, `null` values being left out.
+fn render_properties(
+    out: &mut String,
+    hash: &HashMap<Cow<str>, PklValue>,
+    prefix: &str,
+) -> PklResult<()> {
+    for (name, value) in sorted(hash) {
+        let key = format!("{}{}", prefix, name);
+
+        if let Some(hash) = members(value) {
+            render_properties(out, hash, &format!("{}.", key))?;
+            continue;
+        }
+
+        let value = match value {
+            PklValue::Null => continue,
+            PklValue::List(_) => return Err(unsupported(value, OutputFormat::Properties)),
+            PklValue::String(s) => s.to_owned(),
+            _ => value.to_string(),
+        };
+        out.push_str(&format!(
+            "{} = {}\n",
+            escape_properties(&key, true),
+            escape_properties(&value, false)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Escapes a key or a value of a properties file.
+fn escape_properties(s: &str, is_key: bool) -> String {
+    let mut out = String::new();
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{c}' => out.push_str("\\f"),
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            ':' | '=' | '#' | '!' if is_key => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,399 @@
+use crate::{
+    glob::{glob_files, glob_matches},
+    parser::PklResult,
+    PklError, PklValue,
+};
+use base64::prelude::*;
+use std::{borrow::Cow, fmt, fs, ops::Range, sync::Arc};
+
+#[cfg(feature = "hashbrown_support")]
+use hashbrown::HashMap;
+#[cfg(not(feature = "hashbrown_support"))]
+use std::collections::HashMap;
+
+/// The URI schemes modules may read by default.
+const DEFAULT_ALLOWED_SCHEMES: &[&str] = &["prop", "env", "file"];
+
+/// Reads the resources of a URI scheme for `read()`, `read?()` and `read*()`.
+///
+/// ```ignore
+/// struct SecretReader;
+///
+/// impl ResourceReader for SecretReader {
+///     fn scheme(&self) -> &str {
+///         "secret"
+///     }
+///
+///     fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+///         Ok(vault::get(path).map(String::into_bytes))
+///     }
+/// }
+///
+/// let pkl = Pkl::new().with_resource_reader(SecretReader);
+/// ```
+///
+/// Readers registered for the `file`, `env` or `prop` schemes replace the built-in ones.
+pub trait ResourceReader: Send + Sync {
+    /// The URI scheme read by this reader, without its colon, e.g. `file`.
+    fn scheme(&self) -> &str;
+
+    /// Reads the resource at `path`, the part of its URI following the scheme,
+    /// e.g. `secret.txt` for `file:secret.txt`, returning `None` if it does not exist.
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String>;
+
+    /// Lists the paths of the resources matching the glob `pattern`, for `read*()`.
+    ///
+    /// Globbing is not supported by default.
+    fn list(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let _ = pattern;
+        Err(format!(
+            "the `{}` scheme does not support globbing",
+            self.scheme()
+        ))
+    }
+
+    /// Whether reading a resource evaluates to its text rather than to a `Resource` object,
+    /// as for environment variables and external properties.
+    fn is_text(&self) -> bool {
+        false
+    }
+}
+
+/// Reads files: `file:config.json` or `file:///etc/hosts`.
+///
+/// Relative paths are resolved against the directory of the reading module, given as a path
+/// ending with `/`, or the working directory if it is `None`.
+struct FileReader<'d>(Option<&'d str>);
+
+impl FileReader<'_> {
+    /// Prefixes relative paths with the directory of the reading module.
+    fn resolve<'p>(&self, path: &'p str) -> Cow<'p, str> {
+        let path = file_path(path);
+
+        match self.0 {
+            Some(dir) if !path.starts_with('/') => Cow::Owned(format!("{}{}", dir, path)),
+            _ => Cow::Borrowed(path),
+        }
+    }
+}
+
+impl ResourceReader for FileReader<'_> {
+    fn scheme(&self) -> &str {
+        "file"
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        let path = self.resolve(path);
+
+        match fs::read(path.as_ref()) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Error reading {}: {}", path, e)),
+        }
+    }
+
+    fn list(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let authority = if pattern.starts_with("//") { "//" } else { "" };
+        let resolved = self.resolve(pattern);
+        // the files are listed as written in the pattern, relative to the module's directory
+        let dir = &resolved[..resolved.len() - file_path(pattern).len()];
+
+        let files =
+            glob_files(&resolved).map_err(|e| format!("Error listing {}: {}", pattern, e))?;
+        Ok(files
+            .iter()
+            .map(|file| format!("{}{}", authority, file.strip_prefix(dir).unwrap_or(file)))
+            .collect())
+    }
+}
+
+/// Strips the empty authority of `file:///absolute/path` URIs.
+fn file_path(path: &str) -> &str {
+    path.strip_prefix("//").unwrap_or(path)
+}
+
+/// Reads environment variables: `env:HOME`.
+struct EnvReader;
+
+impl ResourceReader for EnvReader {
+    fn scheme(&self) -> &str {
+        "env"
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(std::env::var(path).ok().map(String::into_bytes))
+    }
+
+    fn list(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let mut names: Vec<String> = std::env::vars()
+            .map(|(name, _)| name)
+            .filter(|name| glob_matches(pattern, name))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn is_text(&self) -> bool {
+        true
+    }
+}
+
+/// Reads external properties: `prop:env`.
+struct PropertyReader<'p>(&'p HashMap<String, String>);
+
+impl ResourceReader for PropertyReader<'_> {
+    fn scheme(&self) -> &str {
+        "prop"
+    }
+
+    fn read(&self, path: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.0.get(path).map(|value| value.clone().into_bytes()))
+    }
+
+    fn list(&self, pattern: &str) -> Result<Vec<String>, String> {
+        let mut names: Vec<String> = self
+            .0
+            .keys()
+            .filter(|name| glob_matches(pattern, name))
+            .cloned()
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn is_text(&self) -> bool {
+        true
+    }
+}
+
+/// How a resource is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReadMode {
+    /// `read()`, failing if the resource does not exist.
+    Required,
+    /// `read?()`, evaluating to `null` if the resource does not exist.
+    Nullable,
+    /// `read*()`, reading every resource matching a glob pattern.
+    Glob,
+}
+
+/// What evaluated modules may read with `read()`.
+#[derive(Clone)]
+pub(crate) struct ResourceOptions {
+    /// The external properties, read with `read("prop:<name>")`.
+    pub external_properties: HashMap<String, String>,
+    /// The URI schemes `read()` may access.
+    pub allowed_schemes: Vec<String>,
+    /// The readers registered with [`crate::Pkl::with_resource_reader`].
+    pub readers: Vec<Arc<dyn ResourceReader>>,
+}
+
+impl Default for ResourceOptions {
+    fn default() -> Self {
+        Self {
+            external_properties: HashMap::new(),
+            allowed_schemes: DEFAULT_ALLOWED_SCHEMES
+                .iter()
+                .map(|scheme| scheme.to_string())
+                .collect(),
+            readers: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Debug for ResourceOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let readers: Vec<&str> = self.readers.iter().map(|reader| reader.scheme()).collect();
+
+        f.debug_struct("ResourceOptions")
+            .field("external_properties", &self.external_properties)
+            .field("allowed_schemes", &self.allowed_schemes)
+            .field("readers", &readers)
+            .finish()
+    }
+}
+
+impl PartialEq for ResourceOptions {
+    fn eq(&self, other: &Self) -> bool {
+        self.external_properties == other.external_properties
+            && self.allowed_schemes == other.allowed_schemes
+            && self.readers.len() == other.readers.len()
+            && self
+                .readers
+                .iter()
+                .zip(other.readers.iter())
+                .all(|(a, b)| Arc::ptr_eq(a, b))
+    }
+}
+
+impl ResourceOptions {
+    /// Reads the resource at `uri`, `rng` being the span of the `read()` call.
+    ///
+    /// URIs without a scheme are file paths. Relative file paths are resolved against
+    /// the URI of the reading module, `base`, if it is a file path. Resources read with the `file` scheme
+    /// or custom readers evaluate to a `Resource` object, with `uri`, `text` and `base64` properties,
+    /// and the ones read with `read*()` to an object mapping their URI to their value.
+    pub fn read<'a>(
+        &self,
+        uri: &str,
+        base: Option<&str>,
+        mode: ReadMode,
+        rng: Range<usize>,
+    ) -> PklResult<PklValue<'a>> {
+        let (scheme, path) = match uri.split_once(':') {
+            Some((scheme, path)) if is_scheme(scheme) => (scheme, path),
+            _ => ("file", uri),
+        };
+
+        if !self.allowed_schemes.iter().any(|allowed| allowed == scheme) {
+            return Err(PklError::evaluation(
+                format!(
+                    "Refusing to read `{}`, the `{}` scheme is not allowed",
+                    uri, scheme
+                ),
+                rng,
+            ));
+        }
+
+        let custom = self
+            .readers
+            .iter()
+            .rev()
+            .find(|reader| reader.scheme() == scheme);
+        // the directory of the reading module, unless it was not read from a file
+        let dir = base
+            .filter(|base| {
+                !base
+                    .split_once(':')
+                    .is_some_and(|(scheme, _)| is_scheme(scheme))
+            })
+            .and_then(|base| base.rfind('/').map(|i| &base[..=i]));
+        let files = FileReader(dir);
+        let properties = PropertyReader(&self.external_properties);
+        let reader: &dyn ResourceReader = match (custom, scheme) {
+            (Some(reader), _) => reader.as_ref(),
+            (None, "file") => &files,
+            (None, "env") => &EnvReader,
+            (None, "prop") => &properties,
+            (None, _) => {
+                return Err(PklError::evaluation(
+                    format!("Unsupported resource scheme `{}`", scheme),
+                    rng,
+                ))
+            }
+        };
+
+        let to_error = |message: String| PklError::evaluation(message, rng.to_owned());
+        // the part of the URI preceding its path, e.g. `file:`
+        let uri_prefix = &uri[..uri.len() - path.len()];
+
+        if mode == ReadMode::Glob {
+            let mut resources = HashMap::new();
+            for path in reader.list(path).map_err(to_error)? {
+                let uri = format!("{}{}", uri_prefix, path);
+                let Some(bytes) = reader.read(&path).map_err(to_error)? else {
+                    continue;
+                };
+
+                let value = resource_value(reader, &uri, bytes);
+                resources.insert(uri.into(), value);
+            }
+
+            return Ok(PklValue::Object(resources));
+        }
+
+        match reader.read(path).map_err(to_error)? {
+            Some(bytes) => Ok(resource_value(reader, uri, bytes)),
+            None if mode == ReadMode::Nullable => Ok(PklValue::Null),
+            None => Err(
+                PklError::evaluation(format!("Cannot find resource `{}`", uri), rng)
+                    .with_help("use `read?()` to get `null` for missing resources"),
+            ),
+        }
+    }
+}
+
+/// Whether `scheme` is a URI scheme rather than, say, a Windows drive letter.
+pub(crate) fn is_scheme(scheme: &str) -> bool {
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Returns the value of a read resource: its text, or a `Resource` object.
+fn resource_value<'a>(reader: &dyn ResourceReader, uri: &str, bytes: Vec<u8>) -> PklValue<'a> {
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if reader.is_text() {
+        return PklValue::String(text);
+    }
+
+    let mut properties = HashMap::new();
+    properties.insert("uri".into(), PklValue::String(uri.to_owned()));
+    properties.insert("text".into(), PklValue::String(text));
+    properties.insert(
+        "base64".into(),
+        PklValue::String(BASE64_STANDARD.encode(&bytes)),
+    );
+
+    PklValue::ClassInstance("Resource".into(), properties)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Pkl, PklError, PklValue};
+    use std::fs;
+
+    #[test]
+    fn relative_files_are_read_from_the_module_directory() {
+        let dir = std::env::temp_dir().join(format!("new-pkl-resources-{}", std::process::id()));
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::write(dir.join("data/a.txt"), "a").unwrap();
+        fs::write(dir.join("data/b.txt"), "b").unwrap();
+
+        let mut pkl = Pkl::new();
+        pkl.set_module_uri(format!("{}/main.pkl", dir.display()));
+        let result = pkl.parse(
+            "a = read(\"data/a.txt\").text\nb = read(\"file:data/b.txt\").text\nall = read*(\"data/*.txt\")\n",
+        );
+        fs::remove_dir_all(&dir).unwrap();
+        result.unwrap();
+
+        assert_eq!(pkl.get_string("a").unwrap(), "a");
+        assert_eq!(pkl.get_string("b").unwrap(), "b");
+        let Some(PklValue::Object(all)) = pkl.get("all") else {
+            panic!("read* should evaluate to an object");
+        };
+        let mut keys: Vec<&str> = all.keys().map(|key| key.as_ref()).collect();
+        keys.sort();
+        assert_eq!(keys, ["data/a.txt", "data/b.txt"]);
+    }
+
+    #[test]
+    fn reads_are_limited_to_the_allowed_schemes() {
+        let mut pkl = Pkl::new()
+            .with_external_property("region", "eu-west-1")
+            .with_allowed_resources(["prop"]);
+        pkl.parse("region = read(\"prop:region\")\n").unwrap();
+        assert_eq!(pkl.get_string("region").unwrap(), "eu-west-1");
+
+        for source in [
+            "home = read(\"env:HOME\")\n",
+            "hosts = read(\"/etc/hosts\")\n",
+        ] {
+            let error = pkl.parse(source).unwrap_err();
+
+            assert!(matches!(error, PklError::Evaluation(_)), "{error}");
+            assert_eq!(error.span(), source.find("read").unwrap()..source.len() - 1);
+        }
+        let error = pkl.parse("home = read(\"env:HOME\")\n").unwrap_err();
+        assert_eq!(
+            error.message(),
+            "Refusing to read `env:HOME`, the `env` scheme is not allowed"
+        );
+
+        let error = pkl.parse("missing = read(\"prop:missing\")\n").unwrap_err();
+        assert!(error.message().contains("missing"), "{error}");
+    }
+}
@@ -1,7 +1,12 @@
 use crate::{
+    diagnostic::closest_name,
+    error::{PklError, PklWarning},
+    module::ModuleOptions,
     parser::{
-        AstPklValue, ExprHash, ExprMember, FuncCall, Identifier, PklExpr, PklResult, PklStatement,
+        Annotation, AstPklValue, ExprHash, ExprMember, FuncCall, Identifier, Modifiers,
+        ObjectMember, PklExpr, PklResult, PklStatement, StringPart,
     },
+    resource::{ReadMode, ResourceOptions},
     Pkl,
 };
 use bool_api::match_bool_methods_api;
@@ -10,11 +15,11 @@ use duration::{match_duration_props_api, Duration};
 use float_api::match_float_props_api;
 use int_api::match_int_props_api;
 use list_api::match_list_props_api;
-use std::{fs, ops::Range};
+use std::{borrow::Cow, cell::RefCell, fmt, ops::Range, sync::Arc};
 use string_api::{match_string_methods_api, match_string_props_api};
 
 #[cfg(feature = "hashbrown_support")]
-use hashbrown::Hashmap as HashMap;
+use hashbrown::HashMap;
 #[cfg(not(feature = "hashbrown_support"))]
 use std::collections::HashMap;
 
@@ -40,6 +45,9 @@ mod string_api;
 /// * `MultiLineString` - Represents a multiline string.
 /// * `Object` - Represents a nested object, which is a hashmap of key-value pairs.
 /// * `ClassInstance` - Represents an instance of a class, which includes the class name and its properties.
+///
+/// Names borrow from the parsed source where possible,
+/// those of imported modules are owned, see [`PklValue::into_owned`].
 #[derive(Debug, PartialEq, Clone)]
 pub enum PklValue<'a> {
     Null,
@@ -63,10 +71,10 @@ pub enum PklValue<'a> {
     List(Vec<PklValue<'a>>),
 
     /// A nested object represented as a hashmap of key-value pairs.
-    Object(HashMap<&'a str, PklValue<'a>>),
+    Object(HashMap<Cow<'a, str>, PklValue<'a>>),
 
     /// An instance of a class, including the class name and its properties.
-    ClassInstance(&'a str, HashMap<&'a str, PklValue<'a>>),
+    ClassInstance(Cow<'a, str>, HashMap<Cow<'a, str>, PklValue<'a>>),
 
     /// A duration
     Duration(Duration<'a>),
@@ -167,7 +175,7 @@ impl<'a> PklValue<'a> {
         }
     }
 
-    pub fn as_object(&self) -> Option<&HashMap<&'a str, PklValue<'a>>> {
+    pub fn as_object(&self) -> Option<&HashMap<Cow<'a, str>, PklValue<'a>>> {
         if let PklValue::Object(ref o) = self {
             Some(o)
         } else {
@@ -190,6 +198,39 @@ impl<'a> PklValue<'a> {
             None
         }
     }
+
+    /// Converts the value into one owning all its names,
+    /// which no longer borrows from the parsed source.
+    pub fn into_owned(self) -> PklValue<'static> {
+        match self {
+            PklValue::Null => PklValue::Null,
+            PklValue::Bool(b) => PklValue::Bool(b),
+            PklValue::Float(f) => PklValue::Float(f),
+            PklValue::Int(i) => PklValue::Int(i),
+            PklValue::String(s) => PklValue::String(s),
+            PklValue::List(values) => {
+                PklValue::List(values.into_iter().map(PklValue::into_owned).collect())
+            }
+            PklValue::Object(hash) => PklValue::Object(owned_members(hash)),
+            PklValue::ClassInstance(class_name, hash) => {
+                PklValue::ClassInstance(owned_name(class_name), owned_members(hash))
+            }
+            PklValue::Duration(duration) => PklValue::Duration(duration.into_owned()),
+            PklValue::DataSize(byte) => PklValue::DataSize(byte.into_owned()),
+        }
+    }
+}
+
+fn owned_name(name: Cow<'_, str>) -> Cow<'static, str> {
+    Cow::Owned(name.into_owned())
+}
+
+fn owned_members(
+    hash: HashMap<Cow<'_, str>, PklValue<'_>>,
+) -> HashMap<Cow<'static, str>, PklValue<'static>> {
+    hash.into_iter()
+        .map(|(name, value)| (owned_name(name), value.into_owned()))
+        .collect()
 }
 
 impl From<bool> for PklValue<'_> {
@@ -222,15 +263,15 @@ impl<'a> From<Vec<PklValue<'a>>> for PklValue<'a> {
     }
 }
 
-impl<'a> From<HashMap<&'a str, PklValue<'a>>> for PklValue<'a> {
-    fn from(value: HashMap<&'a str, PklValue<'a>>) -> Self {
+impl<'a> From<HashMap<Cow<'a, str>, PklValue<'a>>> for PklValue<'a> {
+    fn from(value: HashMap<Cow<'a, str>, PklValue<'a>>) -> Self {
         PklValue::Object(value)
     }
 }
 
-impl<'a> From<(String, HashMap<&'a str, PklValue<'a>>)> for PklValue<'a> {
-    fn from(value: (String, HashMap<&'a str, PklValue<'a>>)) -> Self {
-        PklValue::ClassInstance(Box::leak(value.0.into_boxed_str()), value.1)
+impl<'a> From<(String, HashMap<Cow<'a, str>, PklValue<'a>>)> for PklValue<'a> {
+    fn from(value: (String, HashMap<Cow<'a, str>, PklValue<'a>>)) -> Self {
+        PklValue::ClassInstance(Cow::Owned(value.0), value.1)
     }
 }
 
@@ -246,6 +287,66 @@ impl<'a> From<Byte<'a>> for PklValue<'a> {
     }
 }
 
+/// Formats a value the way Pkl converts it to a string,
+/// as done when interpolating it in a string.
+impl fmt::Display for PklValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Writes the members of an object, sorted by name, strings are quoted.
+        fn write_members(
+            f: &mut fmt::Formatter<'_>,
+            hash: &HashMap<Cow<str>, PklValue>,
+        ) -> fmt::Result {
+            let mut members: Vec<_> = hash.iter().collect();
+            members.sort_by_key(|(name, _)| *name);
+
+            write!(f, "{{")?;
+            for (i, (name, value)) in members.into_iter().enumerate() {
+                let separator = if i == 0 { " " } else { "; " };
+                match value {
+                    PklValue::String(s) => write!(f, "{separator}{name} = {s:?}")?,
+                    _ => write!(f, "{separator}{name} = {value}")?,
+                }
+            }
+            write!(f, " }}")
+        }
+
+        match self {
+            PklValue::Null => write!(f, "null"),
+            PklValue::Bool(b) => write!(f, "{b}"),
+            PklValue::Float(x) if x.is_nan() => write!(f, "NaN"),
+            PklValue::Float(x) if x.is_infinite() && *x > 0.0 => write!(f, "Infinity"),
+            PklValue::Float(x) if x.is_infinite() => write!(f, "-Infinity"),
+            PklValue::Float(x) if x.fract() == 0.0 => write!(f, "{x:.1}"),
+            PklValue::Float(x) => write!(f, "{x}"),
+            PklValue::Int(i) => write!(f, "{i}"),
+            PklValue::String(s) => write!(f, "{s}"),
+            PklValue::List(values) => {
+                write!(f, "List(")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    match value {
+                        PklValue::String(s) => write!(f, "{s:?}")?,
+                        _ => write!(f, "{value}")?,
+                    }
+                }
+                write!(f, ")")
+            }
+            PklValue::Object(hash) => {
+                write!(f, "new Dynamic ")?;
+                write_members(f, hash)
+            }
+            PklValue::ClassInstance(name, hash) => {
+                write!(f, "new {name} ")?;
+                write_members(f, hash)
+            }
+            PklValue::Duration(duration) => write!(f, "{duration}"),
+            PklValue::DataSize(byte) => write!(f, "{byte}"),
+        }
+    }
+}
+
 impl<'a> From<()> for PklValue<'a> {
     fn from(_: ()) -> Self {
         PklValue::Null
@@ -254,14 +355,105 @@ impl<'a> From<()> for PklValue<'a> {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PklTable<'a> {
-    pub variables: HashMap<&'a str, PklValue<'a>>,
+    pub variables: HashMap<Cow<'a, str>, PklValue<'a>>,
+    /// The name declared in the `module` clause, if any.
+    pub module_name: Option<Cow<'a, str>>,
+    /// The modifiers of properties and object members, keyed by their dotted path.
+    /// Properties declared without modifiers are not present.
+    modifiers: HashMap<String, Modifiers>,
+    /// The span of the value of `fixed` and `const` properties declared in this module,
+    /// keyed by their dotted path.
+    modifier_spans: HashMap<String, Range<usize>>,
+    /// The span of the statement declaring each property, keyed by its name:
+    /// the property itself, its import, or the clause of the module it is amended from.
+    spans: HashMap<String, Range<usize>>,
+    /// The doc comments of properties and object members, keyed by their dotted path.
+    docs: HashMap<String, Cow<'a, str>>,
+    /// The evaluated annotations of properties and object members, keyed by their dotted path,
+    /// each annotation being a class instance, e.g. `Deprecated { message = "..." }`.
+    annotations: HashMap<String, Vec<PklValue<'a>>>,
+    /// The evaluated annotations of the module.
+    pub module_annotations: Vec<PklValue<'a>>,
+    /// The warnings emitted while evaluating, such as reads of deprecated properties.
+    warnings: RefCell<Vec<PklWarning>>,
+    /// The values of the `local` members of the object bodies being evaluated, innermost last.
+    scopes: RefCell<Vec<HashMap<&'a str, PklValue<'a>>>>,
+    /// What the module may read with `read()`, passed on to the modules it loads.
+    resources: ResourceOptions,
+    /// How the module loads the modules it imports, amends or extends.
+    modules: ModuleOptions,
     imports: Vec<String>,
 }
 
+/// The module named in an `amends` or `extends` clause.
+struct ParentModule<'a> {
+    uri: &'a str,
+    is_amends: bool,
+    /// The type of the value of each property of the parent module.
+    ///
+    /// Properties have no type annotations, so overrides are checked against these types,
+    /// see [`is_compatible`].
+    declared: HashMap<String, String>,
+}
+
+impl<'a> ParentModule<'a> {
+    /// Checks that `name` may be assigned `value` in a module amending or extending this one.
+    fn check(&self, name: &str, value: &PklValue<'a>, rng: Range<usize>) -> PklResult<()> {
+        match self.declared.get(name) {
+            Some(declared) if !is_compatible(declared, value.get_type()) => {
+                Err(PklError::type_mismatch(
+                    format!(
+                        "Property `{}` is of type {} in module `{}`, cannot assign a value of type {}",
+                        name,
+                        declared,
+                        self.uri,
+                        value.get_type()
+                    ),
+                    rng,
+                ))
+            }
+            None if self.is_amends => Err(PklError::unknown_variable(
+                format!(
+                    "Cannot find property `{}` in amended module `{}`, amending modules can only override existing properties",
+                    name, self.uri
+                ),
+                rng,
+            )
+            .with_help("use `extends` instead of `amends` to declare new properties")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether a value of type `found` may override a property whose parent value is of type `parent`.
+///
+/// `null` is compatible with every type, `Int` and `Float` with each other,
+/// and objects with class instances.
+fn is_compatible(parent: &str, found: &str) -> bool {
+    let kind = |type_name| match type_name {
+        "Int" | "Float" => "Number",
+        "Object" | "ClassInstance" => "Object",
+        type_name => type_name,
+    };
+
+    return parent == "Null" || found == "Null" || kind(parent) == kind(found);
+}
+
 impl<'a> PklTable<'a> {
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            module_name: None,
+            modifiers: HashMap::new(),
+            modifier_spans: HashMap::new(),
+            spans: HashMap::new(),
+            docs: HashMap::new(),
+            annotations: HashMap::new(),
+            module_annotations: Vec::new(),
+            warnings: RefCell::new(Vec::new()),
+            scopes: RefCell::new(Vec::new()),
+            resources: ResourceOptions::default(),
+            modules: ModuleOptions::default(),
             imports: vec![],
         }
     }
@@ -276,10 +468,24 @@ impl<'a> PklTable<'a> {
     /// # Returns
     ///
     /// An `Option` containing the previous value associated with the name, if any.
-    pub fn insert(&mut self, name: &'a str, value: PklValue<'a>) -> Option<PklValue<'a>> {
+    pub fn insert(
+        &mut self,
+        name: impl Into<Cow<'a, str>>,
+        value: PklValue<'a>,
+    ) -> Option<PklValue<'a>> {
+        let name = name.into();
+        // the value no longer comes from its declaration
+        self.spans.remove(name.as_ref());
+
         self.variables.insert(name, value)
     }
 
+    /// Removes the variable with the given name from the context, returning its value, if any.
+    pub fn remove(&mut self, name: &str) -> Option<PklValue<'a>> {
+        self.spans.remove(name);
+        self.variables.remove(name)
+    }
+
     /// Merges another `PklTable` into this table.
     ///
     /// This method takes another `PklTable` and inserts all of its variables into the current table.
@@ -292,7 +498,7 @@ impl<'a> PklTable<'a> {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```ignore
     /// let mut table1 = PklTable::new();
     /// table1.insert("var1", PklValue::Int(1));
     ///
@@ -308,6 +514,21 @@ impl<'a> PklTable<'a> {
         for (name, value) in other_table.variables {
             self.insert(name, value);
         }
+
+        if other_table.module_name.is_some() {
+            self.module_name = other_table.module_name;
+        }
+
+        self.modifiers.extend(other_table.modifiers);
+        self.spans.extend(other_table.spans);
+        self.docs.extend(other_table.docs);
+        self.annotations.extend(other_table.annotations);
+        if !other_table.module_annotations.is_empty() {
+            self.module_annotations = other_table.module_annotations;
+        }
+        self.warnings
+            .get_mut()
+            .extend(other_table.warnings.into_inner());
     }
 
     /// Retrieves the value of a variable with the given name from the context.
@@ -320,39 +541,518 @@ impl<'a> PklTable<'a> {
     ///
     /// An `Option` containing a reference to the `PklValue` associated with the name,
     /// or `None` if the variable is not found.
-    pub fn get(&self, name: &'a str) -> Option<&PklValue<'a>> {
+    pub fn get(&self, name: &str) -> Option<&PklValue<'a>> {
         self.variables.get(name)
     }
 
-    pub fn import(&mut self, name: &'a str, rng: Range<usize>) -> PklResult<()> {
-        match name {
-            name if name.starts_with("package://") => {
-                return Err(("Package imports not yet supported!".to_owned(), rng))
+    /// Returns the modifiers the property or object member at `path` is declared with.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path of the property, e.g. `bird.name`.
+    pub fn modifiers(&self, path: &str) -> Modifiers {
+        self.modifiers.get(path).copied().unwrap_or_default()
+    }
+
+    /// Returns the span of the statement declaring the property `name`, if declared by a parsed module:
+    /// the property itself, its import, or the `amends` or `extends` clause it is inherited from.
+    pub fn span(&self, name: &str) -> Option<Range<usize>> {
+        self.spans.get(name).cloned()
+    }
+
+    /// Returns the names of the properties declared by a parsed module, see [`PklTable::span`].
+    pub fn declared(&self) -> impl Iterator<Item = &str> {
+        self.spans.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the doc comment of the property or object member at `path`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path of the property, e.g. `bird.name`.
+    pub fn doc(&self, path: &str) -> Option<&str> {
+        self.docs.get(path).map(|doc| doc.as_ref())
+    }
+
+    /// Returns the evaluated annotations of the property or object member at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The dotted path of the property, e.g. `bird.name`.
+    pub fn annotations(&self, path: &str) -> &[PklValue<'a>] {
+        self.annotations
+            .get(path)
+            .map_or(&[], |annotations| annotations)
+    }
+
+    /// Returns the warnings emitted so far.
+    pub fn warnings(&self) -> Vec<PklWarning> {
+        self.warnings.borrow().clone()
+    }
+
+    /// Removes and returns the warnings emitted so far.
+    pub fn take_warnings(&mut self) -> Vec<PklWarning> {
+        std::mem::take(self.warnings.get_mut())
+    }
+
+    /// Attaches the source the spans of the warnings emitted so far refer to,
+    /// see [`PklWarning::with_source`].
+    pub fn set_warnings_source(&mut self, name: Option<&str>, source: &str) {
+        // the source is copied at most once, and shared between the warnings
+        let mut shared: Option<Arc<str>> = None;
+        for warning in self.warnings.get_mut().iter_mut() {
+            warning
+                .info_mut()
+                .attach_source(name, || shared.get_or_insert_with(|| source.into()).clone());
+        }
+    }
+
+    /// Emits a warning if the property at `path`, read at `span`, is annotated with `@Deprecated`.
+    fn warn_if_deprecated(&self, path: &str, span: Range<usize>) {
+        let deprecated = self
+            .annotations(path)
+            .iter()
+            .find_map(|annotation| match annotation {
+                PklValue::ClassInstance(class_name, properties) if class_name == "Deprecated" => {
+                    Some(properties)
+                }
+                _ => None,
+            });
+        let Some(properties) = deprecated else {
+            return;
+        };
+
+        let mut message = format!("`{}` is deprecated", path);
+        if let Some(PklValue::String(reason)) = properties.get("message") {
+            message = format!("{}: {}", message, reason);
+        }
+        let mut warning = PklWarning::deprecated(message, span);
+        if let Some(PklValue::String(replacement)) = properties.get("replaceWith") {
+            warning = warning.with_help(format!("replace it with `{}`", replacement));
+        }
+
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    /// Returns the properties making up the output of the module,
+    /// `local` and `hidden` properties and object members are left out.
+    pub fn output(&self) -> HashMap<Cow<'a, str>, PklValue<'a>> {
+        self.variables
+            .iter()
+            .filter(|(name, _)| {
+                let modifiers = self.modifiers(name);
+                !modifiers.local && !modifiers.hidden
+            })
+            .map(|(name, value)| (name.clone(), self.strip_hidden(name, value.to_owned())))
+            .collect()
+    }
+
+    /// Removes the `hidden` members of `value`, located at `path`.
+    fn strip_hidden(&self, path: &str, value: PklValue<'a>) -> PklValue<'a> {
+        let strip = |hash: HashMap<Cow<'a, str>, PklValue<'a>>| {
+            hash.into_iter()
+                .filter_map(|(name, value)| {
+                    let member_path = format!("{path}.{name}");
+                    if self.modifiers(&member_path).hidden {
+                        return None;
+                    }
+                    Some((name, self.strip_hidden(&member_path, value)))
+                })
+                .collect()
+        };
+
+        match value {
+            PklValue::Object(hash) => PklValue::Object(strip(hash)),
+            PklValue::ClassInstance(class_name, hash) => {
+                PklValue::ClassInstance(class_name, strip(hash))
             }
-            name if name.starts_with("pkl:") => {
-                return Err((
-                    "Pkl official packages imports not yet supported!".to_owned(),
-                    rng,
-                ))
+            value => value,
+        }
+    }
+
+    /// Returns the table as seen by other modules, without its `local` properties.
+    fn exported(mut self) -> PklTable<'a> {
+        let locals: Vec<String> = self
+            .modifiers
+            .iter()
+            .filter(|(path, modifiers)| modifiers.local && !path.contains('.'))
+            .map(|(path, _)| path.to_owned())
+            .collect();
+
+        self.variables
+            .retain(|name, _| !locals.iter().any(|local| local == name));
+        self.spans
+            .retain(|name, _| !locals.iter().any(|local| local == name));
+        self.modifiers.retain(|path, _| {
+            let name = path.split('.').next().unwrap_or(path);
+            !locals.iter().any(|local| local == name)
+        });
+        self.docs.retain(|path, _| {
+            let name = path.split('.').next().unwrap_or(path);
+            !locals.iter().any(|local| local == name)
+        });
+        self.annotations.retain(|path, _| {
+            let name = path.split('.').next().unwrap_or(path);
+            !locals.iter().any(|local| local == name)
+        });
+
+        self
+    }
+
+    /// Converts the table into one owning all its names and values,
+    /// which no longer borrows from the parsed source.
+    fn into_owned(self) -> PklTable<'static> {
+        PklTable {
+            variables: owned_members(self.variables),
+            module_name: self.module_name.map(owned_name),
+            modifiers: self.modifiers,
+            modifier_spans: self.modifier_spans,
+            spans: self.spans,
+            docs: self
+                .docs
+                .into_iter()
+                .map(|(path, doc)| (path, owned_name(doc)))
+                .collect(),
+            annotations: self
+                .annotations
+                .into_iter()
+                .map(|(path, annotations)| {
+                    (
+                        path,
+                        annotations.into_iter().map(PklValue::into_owned).collect(),
+                    )
+                })
+                .collect(),
+            module_annotations: self
+                .module_annotations
+                .into_iter()
+                .map(PklValue::into_owned)
+                .collect(),
+            warnings: self.warnings,
+            scopes: RefCell::new(Vec::new()),
+            resources: self.resources,
+            modules: self.modules,
+            imports: self.imports,
+        }
+    }
+
+    /// Shortens the lifetime of the table, which its `scopes` prevent from happening implicitly.
+    fn shorten<'b>(self) -> PklTable<'b>
+    where
+        'a: 'b,
+    {
+        PklTable {
+            variables: self.variables,
+            module_name: self.module_name,
+            modifiers: self.modifiers,
+            modifier_spans: self.modifier_spans,
+            spans: self.spans,
+            docs: self.docs,
+            annotations: self.annotations,
+            module_annotations: self.module_annotations,
+            warnings: self.warnings,
+            scopes: RefCell::new(Vec::new()),
+            resources: self.resources,
+            modules: self.modules,
+            imports: self.imports,
+        }
+    }
+
+    /// Records the modifiers of the property at `path`, and those of the members of its object body.
+    ///
+    /// When the property amends or refers to another property,
+    /// the modifiers of the members of this other property are inherited.
+    fn record_modifiers(&mut self, path: &str, modifiers: Modifiers, expr: &PklExpr<'a>) {
+        if !modifiers.is_empty() {
+            self.modifiers.insert(path.to_owned(), modifiers);
+        }
+        if modifiers.fixed || modifiers.constant {
+            self.modifier_spans.insert(path.to_owned(), expr.span());
+        }
+
+        let body = match expr {
+            PklExpr::Identifier(_) | PklExpr::MemberExpression(_, _, _) => {
+                self.inherit_modifiers(expr, path);
+                return;
             }
-            name if name.starts_with("https://") => {
-                return Err(("Web imports not yet supported!".to_owned(), rng))
+            PklExpr::Value(AstPklValue::Object(body))
+            | PklExpr::Value(AstPklValue::ClassInstance(_, body, _)) => body,
+            PklExpr::Value(AstPklValue::AmendingObject(base, body, _))
+            | PklExpr::Value(AstPklValue::AmendedObject(base, body, _)) => {
+                self.record_modifiers(path, Modifiers::default(), base);
+                body
             }
-            file_name => {
-                let file_content = fs::read_to_string(file_name)
-                    .map_err(|e| (format!("Error reading {file_name}: {}", e.to_string()), rng))?;
+            _ => return,
+        };
 
-                let mut pkl = Pkl::new();
-                pkl.parse(&file_content)?;
-                let hash = pkl.table.variables.to_owned();
+        for (name, member) in body.0.iter() {
+            self.record_modifiers(&format!("{path}.{name}"), member.modifiers, &member.value);
+        }
+    }
 
-                println!("{:?}", hash);
+    /// Records the doc comment and the annotations of the property at `path`,
+    /// and those of the members of its object body.
+    fn record_metadata(
+        &mut self,
+        path: &str,
+        doc: Option<&Cow<'a, str>>,
+        annotations: &[Annotation<'a>],
+        expr: &PklExpr<'a>,
+    ) -> PklResult<()> {
+        if let Some(doc) = doc {
+            self.docs.insert(path.to_owned(), doc.clone());
+        }
+        if !annotations.is_empty() {
+            let annotations = annotations
+                .iter()
+                .map(|annotation| self.evaluate_annotation(annotation.clone()))
+                .collect::<PklResult<Vec<_>>>()?;
+            self.annotations.insert(path.to_owned(), annotations);
+        }
+
+        let body = match expr {
+            PklExpr::Identifier(_) | PklExpr::MemberExpression(_, _, _) => {
+                // members of an amended property keep their metadata
+                if let Some(base_path) = expr_path(expr) {
+                    inherit_entries(&mut self.docs, &base_path, path);
+                    inherit_entries(&mut self.annotations, &base_path, path);
+                }
+                return Ok(());
             }
+            PklExpr::Value(AstPklValue::Object(body))
+            | PklExpr::Value(AstPklValue::ClassInstance(_, body, _)) => body,
+            PklExpr::Value(AstPklValue::AmendingObject(base, body, _))
+            | PklExpr::Value(AstPklValue::AmendedObject(base, body, _)) => {
+                self.record_metadata(path, None, &[], base)?;
+                body
+            }
+            _ => return Ok(()),
         };
 
+        for (name, member) in body.0.iter() {
+            self.record_metadata(
+                &format!("{path}.{name}"),
+                member.doc.as_ref(),
+                &member.annotations,
+                &member.value,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the modifiers of the members of the property `base` refers to, to `path`.
+    fn inherit_modifiers(&mut self, base: &PklExpr<'a>, path: &str) {
+        let Some(base_path) = expr_path(base) else {
+            return;
+        };
+
+        let prefix = format!("{base_path}.");
+        let inherited: Vec<(String, Modifiers)> = self
+            .modifiers
+            .iter()
+            .filter_map(|(member_path, modifiers)| {
+                member_path
+                    .strip_prefix(&prefix)
+                    .map(|member| (format!("{path}.{member}"), *modifiers))
+            })
+            .collect();
+
+        self.modifiers.extend(inherited);
+    }
+
+    /// Returns an error if the property at `path` is `fixed` or `const`.
+    fn check_overridable(&self, path: &str, rng: Range<usize>) -> PklResult<()> {
+        let modifiers = self.modifiers(path);
+        let modifier = match (modifiers.constant, modifiers.fixed) {
+            (true, _) => "const",
+            (false, true) => "fixed",
+            (false, false) => return Ok(()),
+        };
+
+        let mut error = PklError::constraint_violation(
+            format!("Cannot amend `{}`, it is declared as {}", path, modifier),
+            rng,
+        )
+        .with_help(format!(
+            "remove the `{}` modifier to allow amending the property",
+            modifier
+        ));
+        if let Some(span) = self.modifier_spans.get(path) {
+            error = error.with_label(
+                span.clone(),
+                format!("`{path}` is declared {modifier} here"),
+            );
+        }
+
+        Err(error)
+    }
+
+    /// Imports a module and binds it in the context.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The module to import, e.g. `"birds.pkl"`.
+    /// * `local_name` - The name given with `as`, defaults to the module file name without extension.
+    /// * `rng` - The span of the import statement.
+    pub fn import(
+        &mut self,
+        name: &'a str,
+        local_name: Option<&'a str>,
+        rng: Range<usize>,
+    ) -> PklResult<()> {
+        let module = self.load_module_table(name, rng.to_owned())?.exported();
+        let local_name = local_name.unwrap_or_else(|| {
+            let file_name = name.rsplit('/').next().unwrap_or(name);
+            file_name.strip_suffix(".pkl").unwrap_or(file_name)
+        });
+
+        // imports are not part of the module output, nor visible to its importers
+        self.modifiers.insert(
+            local_name.to_owned(),
+            Modifiers {
+                local: true,
+                ..Default::default()
+            },
+        );
+        for (path, modifiers) in module.modifiers {
+            self.modifiers
+                .insert(format!("{local_name}.{path}"), modifiers);
+        }
+        for (path, doc) in module.docs {
+            self.docs.insert(format!("{local_name}.{path}"), doc);
+        }
+        for (path, annotations) in module.annotations {
+            self.annotations
+                .insert(format!("{local_name}.{path}"), annotations);
+        }
+        self.warnings.get_mut().extend(module.warnings.into_inner());
+        self.insert(local_name, PklValue::Object(module.variables));
+        self.spans.insert(local_name.to_owned(), rng);
+
         return Ok(());
     }
 
+    /// Imports the modules matching a glob pattern and binds them in the context,
+    /// as an object mapping the resolved URI of each module to its value.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The glob pattern of the modules to import, e.g. `"birds/*.pkl"`.
+    /// * `local_name` - The name given with `as`, required for glob imports.
+    /// * `rng` - The span of the import statement.
+    pub fn import_glob(
+        &mut self,
+        pattern: &'a str,
+        local_name: Option<&'a str>,
+        rng: Range<usize>,
+    ) -> PklResult<()> {
+        let Some(local_name) = local_name else {
+            return Err(
+                PklError::syntax("Glob imports must be named with `as`", rng).with_help(format!(
+                    "name the imported modules, e.g. `import* \"{pattern}\" as modules`"
+                )),
+            );
+        };
+
+        let modules = self.load_modules(pattern, rng.to_owned())?;
+        // imports are not part of the module output, nor visible to its importers
+        self.modifiers.insert(
+            local_name.to_owned(),
+            Modifiers {
+                local: true,
+                ..Default::default()
+            },
+        );
+        self.insert(local_name, modules);
+        self.spans.insert(local_name.to_owned(), rng);
+
+        return Ok(());
+    }
+
+    /// Reads, parses and evaluates a module, returning it as an object.
+    fn load_module(&self, name: &str, rng: Range<usize>) -> PklResult<PklValue<'a>> {
+        let table = self.load_module_table(name, rng)?.exported();
+
+        return Ok(PklValue::Object(table.variables));
+    }
+
+    /// Reads, parses and evaluates the modules matching the glob `pattern`,
+    /// returning an object mapping the resolved URI of each module to its value.
+    fn load_modules(&self, pattern: &str, rng: Range<usize>) -> PklResult<PklValue<'a>> {
+        let resolver = &self.modules.resolver;
+        let to_error = |e: PklError| e.with_span(rng.to_owned());
+        let pattern = resolver
+            .resolve(pattern, self.modules.uri.as_deref())
+            .map_err(to_error)?;
+
+        let mut modules = HashMap::new();
+        for uri in resolver.list(&pattern).map_err(to_error)? {
+            let module = self.load_resolved_module(uri.clone(), rng.to_owned())?;
+            self.warnings.borrow_mut().extend(module.warnings.take());
+
+            modules.insert(uri.into(), PklValue::Object(module.exported().variables));
+        }
+
+        return Ok(PklValue::Object(modules));
+    }
+
+    /// Resolves, reads, parses and evaluates a module, returning its table.
+    fn load_module_table(&self, name: &str, rng: Range<usize>) -> PklResult<PklTable<'a>> {
+        let uri = self
+            .modules
+            .resolver
+            .resolve(name, self.modules.uri.as_deref())
+            .map_err(|e| e.with_span(rng.to_owned()))?;
+
+        return self.load_resolved_module(uri, rng);
+    }
+
+    /// Reads, parses and evaluates the module at the resolved `uri`, returning its table.
+    ///
+    /// Each module is evaluated once per evaluation, later imports reusing its table.
+    /// The table owns its values, so the source of the module is dropped once evaluated.
+    fn load_resolved_module(&self, uri: String, rng: Range<usize>) -> PklResult<PklTable<'a>> {
+        let resolver = &self.modules.resolver;
+
+        if let Some(cache) = self.modules.cache() {
+            cache.check_cycle(&uri, rng.to_owned())?;
+            if let Some(table) = cache.get(&uri) {
+                return Ok(table.shorten());
+            }
+        }
+
+        let source: Arc<str> = resolver
+            .read(&uri)
+            .map_err(|e| e.with_span(rng.to_owned()))?
+            .into();
+
+        let mut pkl = Pkl::new();
+        pkl.resources = self.resources.clone();
+        pkl.modules = ModuleOptions {
+            resolver: resolver.clone(),
+            uri: Some(uri.clone()),
+            cache: self.modules.cache.clone(),
+        };
+        pkl.set_source_name(uri.as_str());
+
+        if let Some(mut cache) = self.modules.cache() {
+            cache.enter(&uri, source.clone(), rng);
+        }
+        let parsed = pkl.parse(&source);
+        if let Some(mut cache) = self.modules.cache() {
+            cache.exit();
+        }
+        parsed?;
+
+        let table = pkl.table.into_owned();
+        if let Some(mut cache) = self.modules.cache() {
+            cache.insert(uri, table.clone());
+        }
+
+        return Ok(table.shorten());
+    }
+
     /// Evaluates an expression in the current context.
     ///
     /// # Arguments
@@ -363,12 +1063,33 @@ impl<'a> PklTable<'a> {
     ///
     /// A `PklResult` containing the evaluated value or an error message with the range.
     pub fn evaluate(&self, expr: PklExpr<'a>) -> PklResult<PklValue<'a>> {
+        if !self.annotations.is_empty() {
+            if let Some(path) = expr_path(&expr) {
+                self.warn_if_deprecated(&path, expr.span());
+            }
+        }
+
         match expr {
-            PklExpr::Identifier(Identifier(id, range)) => self
-                .variables
-                .get(id)
-                .cloned()
-                .ok_or_else(|| (format!("unknown variable `{}`", id), range)),
+            PklExpr::Identifier(Identifier(id, range)) => {
+                let local = self
+                    .scopes
+                    .borrow()
+                    .iter()
+                    .rev()
+                    .find_map(|scope| scope.get(id).cloned());
+
+                local
+                    .or_else(|| self.variables.get(id).cloned())
+                    .ok_or_else(|| {
+                        let error =
+                            PklError::unknown_variable(format!("unknown variable `{}`", id), range);
+
+                        match closest_name(id, self.variables.keys().map(|name| name.as_ref())) {
+                            Some(name) => error.with_help(format!("did you mean `{}`?", name)),
+                            None => error,
+                        }
+                    })
+            }
             PklExpr::Value(value) => self.evaluate_value(value),
             PklExpr::MemberExpression(base_expr, indexor, range) => {
                 let base = self.evaluate(*base_expr)?;
@@ -381,10 +1102,10 @@ impl<'a> PklTable<'a> {
                                 return match_float_props_api(float, property, range)
                             }
                             PklValue::Object(hashmap) => {
-                                if let Some(data) = hashmap.get(&property) {
+                                if let Some(data) = hashmap.get(property) {
                                     return Ok(data.to_owned());
                                 } else {
-                                    return Err((
+                                    return Err(PklError::unknown_variable(
                                         format!("Object does not possess a '{property}' field"),
                                         range,
                                     ));
@@ -394,10 +1115,10 @@ impl<'a> PklTable<'a> {
                                 return match_string_props_api(&s, property, range)
                             }
                             PklValue::ClassInstance(_class_name, hashmap) => {
-                                if let Some(data) = hashmap.get(&property) {
+                                if let Some(data) = hashmap.get(property) {
                                     return Ok(data.to_owned());
                                 } else {
-                                    return Err((
+                                    return Err(PklError::unknown_variable(
                                         format!("Object does not possess a '{property}' field"),
                                         range,
                                     ));
@@ -414,7 +1135,7 @@ impl<'a> PklTable<'a> {
                             }
 
                             _ => {
-                                return Err((
+                                return Err(PklError::evaluation(
                                     format!("Indexing of value '{:?}' not yet supported", base),
                                     range,
                                 ))
@@ -435,10 +1156,10 @@ impl<'a> PklTable<'a> {
                                 return match_float_props_api(float, fn_name, range)
                             }
                             PklValue::Object(hashmap) => {
-                                if let Some(data) = hashmap.get(&fn_name) {
+                                if let Some(data) = hashmap.get(fn_name) {
                                     return Ok(data.to_owned());
                                 } else {
-                                    return Err((
+                                    return Err(PklError::unknown_variable(
                                         format!("Object does not possess a '{fn_name}' field"),
                                         range,
                                     ));
@@ -448,10 +1169,10 @@ impl<'a> PklTable<'a> {
                                 return match_string_methods_api(&s, fn_name, args, range)
                             }
                             PklValue::ClassInstance(_class_name, hashmap) => {
-                                if let Some(data) = hashmap.get(&fn_name) {
+                                if let Some(data) = hashmap.get(fn_name) {
                                     return Ok(data.to_owned());
                                 } else {
-                                    return Err((
+                                    return Err(PklError::unknown_variable(
                                         format!("Object does not possess a '{fn_name}' field"),
                                         range,
                                     ));
@@ -468,7 +1189,7 @@ impl<'a> PklTable<'a> {
                             }
 
                             _ => {
-                                return Err((
+                                return Err(PklError::evaluation(
                                     format!("Indexing of value '{:?}' not yet supported", base),
                                     range,
                                 ))
@@ -480,8 +1201,46 @@ impl<'a> PklTable<'a> {
             PklExpr::FuncCall(FuncCall(Identifier(name, _), args, rng)) => {
                 // all function calls
                 match name {
-                    "List" => return Ok(self.evaluate_list(args)?),
-                    _ => todo!(),
+                    "List" => return self.evaluate_list(args),
+                    "import" | "import*" => {
+                        let args = self.evaluate_fn_args(args)?;
+
+                        return match args.as_slice() {
+                            [PklValue::String(module)] if name == "import" => {
+                                self.load_module(module, rng)
+                            }
+                            [PklValue::String(pattern)] => self.load_modules(pattern, rng),
+                            _ => Err(PklError::type_mismatch(
+                                format!("{} expects exactly one String argument", name),
+                                rng,
+                            )),
+                        };
+                    }
+                    "read" | "read?" | "read*" => {
+                        let mode = match name {
+                            "read?" => ReadMode::Nullable,
+                            "read*" => ReadMode::Glob,
+                            _ => ReadMode::Required,
+                        };
+                        let args = self.evaluate_fn_args(args)?;
+
+                        return match args.as_slice() {
+                            [PklValue::String(uri)] => {
+                                self.resources
+                                    .read(uri, self.modules.uri.as_deref(), mode, rng)
+                            }
+                            _ => Err(PklError::type_mismatch(
+                                format!("{name} expects exactly one String argument"),
+                                rng,
+                            )),
+                        };
+                    }
+                    _ => {
+                        return Err(PklError::unknown_variable(
+                            format!("unknown function `{}`", name),
+                            rng,
+                        ))
+                    }
                 }
             }
         }
@@ -503,28 +1262,97 @@ impl<'a> PklTable<'a> {
             AstPklValue::Int(i, _) => PklValue::Int(i),
             AstPklValue::Null(_) => PklValue::Null,
             AstPklValue::String(s, _) | AstPklValue::MultiLineString(s, _) => {
-                PklValue::String(s.to_owned())
+                PklValue::String(s.into_owned())
+            }
+            AstPklValue::InterpolatedString(parts, _) => {
+                let mut result = String::new();
+                for part in parts {
+                    match part {
+                        StringPart::Literal(s, _) => result.push_str(&s),
+                        StringPart::Expr(expr, _) => {
+                            result.push_str(&self.evaluate(expr)?.to_string())
+                        }
+                    }
+                }
+                PklValue::String(result)
             }
             AstPklValue::List(values, _) => self.evaluate_list(values)?,
             AstPklValue::Object(o) => self.evaluate_object(o)?,
             AstPklValue::ClassInstance(a, b, _) => self.evaluate_class_instance(a, b)?,
-            AstPklValue::AmendedObject(a, b, _) => self.evaluate_amended_object(a, b)?,
-            AstPklValue::AmendingObject(a, b, rng) => self.evaluate_amending_object(a, b, rng)?,
+            AstPklValue::AmendedObject(a, b, rng) | AstPklValue::AmendingObject(a, b, rng) => {
+                self.evaluate_amending_object(*a, b, rng)?
+            }
         };
 
         Ok(result)
     }
 
+    /// Evaluates an annotation into an instance of its class.
+    fn evaluate_annotation(&self, annotation: Annotation<'a>) -> PklResult<PklValue<'a>> {
+        let Annotation(name, body, _) = annotation;
+        let properties = match body {
+            Some(body) => match self.evaluate_object(body)? {
+                PklValue::Object(properties) => properties,
+                _ => unreachable!(),
+            },
+            None => HashMap::new(),
+        };
+
+        Ok(PklValue::ClassInstance(name.into(), properties))
+    }
+
     fn evaluate_object(&self, o: ExprHash<'a>) -> PklResult<PklValue<'a>> {
-        let new_hash: Result<HashMap<_, _>, _> =
-            o.0.into_iter()
-                .map(|(name, expr)| {
-                    let evaluated_expr = self.evaluate(expr)?;
-                    Ok((name, evaluated_expr))
+        self.evaluate_members(o).map(PklValue::Object)
+    }
+
+    /// Evaluates the members of an object body,
+    /// its `local` members being visible to the others but left out of the result.
+    fn evaluate_members(
+        &self,
+        body: ExprHash<'a>,
+    ) -> PklResult<HashMap<Cow<'a, str>, PklValue<'a>>> {
+        let (locals, members): (Vec<_>, Vec<_>) = body
+            .0
+            .into_iter()
+            .partition(|(_, member)| member.modifiers.local);
+
+        self.with_locals(locals, || {
+            members
+                .into_iter()
+                .map(|(name, member)| {
+                    let evaluated_expr = self.evaluate(member.value)?;
+                    Ok((name.into(), evaluated_expr))
                 })
-                .collect();
+                .collect()
+        })
+    }
+
+    /// Evaluates the `local` members of an object body in declaration order,
+    /// each seeing the ones before it, then calls `evaluate` with all of them in scope.
+    fn with_locals<T>(
+        &self,
+        mut locals: Vec<(&'a str, ObjectMember<'a>)>,
+        evaluate: impl FnOnce() -> PklResult<T>,
+    ) -> PklResult<T> {
+        if locals.is_empty() {
+            return evaluate();
+        }
+
+        locals.sort_by_key(|(_, member)| member.value.span().start);
+        self.scopes.borrow_mut().push(HashMap::new());
+        let result = locals
+            .into_iter()
+            .try_for_each(|(name, member)| {
+                let value = self.evaluate(member.value)?;
+                if let Some(scope) = self.scopes.borrow_mut().last_mut() {
+                    scope.insert(name, value);
+                }
+                Ok(())
+            })
+            .and_then(|()| evaluate());
+        self.scopes.borrow_mut().pop();
 
-        new_hash.map(PklValue::Object)
+        return result;
     }
 
     fn evaluate_fn_args(&self, values: Vec<PklExpr<'a>>) -> PklResult<Vec<PklValue<'a>>> {
@@ -546,79 +1374,561 @@ impl<'a> PklTable<'a> {
     }
 
     fn evaluate_class_instance(&self, a: &'a str, b: ExprHash<'a>) -> PklResult<PklValue<'a>> {
-        let new_hash: Result<HashMap<_, _>, _> =
-            b.0.into_iter()
-                .map(|(name, expr)| {
-                    let evaluated_expr = self.evaluate(expr)?;
-                    Ok((name, evaluated_expr))
-                })
-                .collect();
-
-        new_hash.map(|h| PklValue::ClassInstance(a, h))
+        self.evaluate_members(b)
+            .map(|h| PklValue::ClassInstance(a.into(), h))
     }
 
     fn evaluate_amending_object(
         &self,
-        a: &'a str,
+        a: PklExpr<'a>,
         b: ExprHash<'a>,
         rng: Range<usize>,
     ) -> PklResult<PklValue<'a>> {
-        let other_object = match self.get(a) {
-            Some(PklValue::Object(hash)) => hash,
-            _ => return Err((format!("Unknown object `{}`", a), rng)),
-        };
+        let path = expr_path(&a);
+        let other_object = self.evaluate(a)?;
 
-        let mut new_hash = other_object.clone();
-        for (name, expr) in b.0 {
-            new_hash.insert(name, self.evaluate(expr)?);
-        }
-
-        Ok(PklValue::Object(new_hash))
+        self.amend(other_object, b, path.as_deref(), rng)
     }
 
-    fn evaluate_amended_object(
+    /// Amends an object or class instance with the members of an object body.
+    ///
+    /// Members declared with an object body (`server { port = 1 }`) amend the
+    /// parent's nested object instead of replacing it, recursively.
+    /// Any other member simply overrides the parent's value.
+    ///
+    /// `path` is the dotted path of the parent, when known,
+    /// used to refuse overriding its `fixed` and `const` members.
+    fn amend(
         &self,
-        a: Box<AstPklValue<'a>>,
-        b: ExprHash<'a>,
+        parent: PklValue<'a>,
+        body: ExprHash<'a>,
+        path: Option<&str>,
+        rng: Range<usize>,
     ) -> PklResult<PklValue<'a>> {
-        let first_object = match self.evaluate_value(*a)? {
-            PklValue::Object(o) => o,
-            _ => unreachable!("should not be reached due to the parser work"),
+        let (class_name, mut hash) = match parent {
+            PklValue::Object(hash) => (None, hash),
+            PklValue::ClassInstance(class_name, hash) => (Some(class_name), hash),
+            other => {
+                return Err(PklError::type_mismatch(
+                    format!(
+                        "Cannot amend a value of type {}, expected an object",
+                        other.get_type()
+                    ),
+                    rng,
+                ))
+            }
         };
 
-        let mut new_hash = first_object;
-        for (name, expr) in b.0 {
-            new_hash.insert(name, self.evaluate(expr)?);
-        }
+        let (locals, members): (Vec<_>, Vec<_>) = body
+            .0
+            .into_iter()
+            .partition(|(_, member)| member.modifiers.local);
+
+        self.with_locals(locals, || {
+            for (name, member) in members {
+                let member_path = path.map(|path| format!("{path}.{name}"));
+                if let Some(member_path) = &member_path {
+                    if hash.contains_key(name) {
+                        self.check_overridable(member_path, member.value.span())?;
+                    }
+                }
 
-        Ok(PklValue::Object(new_hash))
+                let value = match (member.value, hash.remove(name)) {
+                    (
+                        PklExpr::Value(AstPklValue::Object(nested_body)),
+                        Some(nested_parent @ (PklValue::Object(_) | PklValue::ClassInstance(_, _))),
+                    ) => {
+                        let nested_rng = nested_body.1.to_owned();
+                        self.amend(
+                            nested_parent,
+                            nested_body,
+                            member_path.as_deref(),
+                            nested_rng,
+                        )?
+                    }
+                    (expr, _) => self.evaluate(expr)?,
+                };
+
+                hash.insert(name.into(), value);
+            }
+
+            Ok(())
+        })?;
+
+        Ok(match class_name {
+            Some(class_name) => PklValue::ClassInstance(class_name, hash),
+            None => PklValue::Object(hash),
+        })
     }
 }
 
-pub fn ast_to_table<'a>(ast: Vec<PklStatement<'a>>) -> PklResult<PklTable<'a>> {
+pub fn ast_to_table<'a>(
+    ast: Vec<PklStatement<'a>>,
+    resources: ResourceOptions,
+    modules: ModuleOptions,
+) -> PklResult<PklTable<'a>> {
     let mut table = PklTable::new();
+    table.resources = resources;
+    table.modules = modules;
+    let mut parent: Option<ParentModule<'a>> = None;
 
     let mut in_body = false;
+    let mut in_imports = false;
+    let mut is_annotated = false;
+
+    for (index, statement) in ast.into_iter().enumerate() {
+        let is_amends = matches!(statement, PklStatement::Amends(_, _));
 
-    for statement in ast {
         match statement {
-            PklStatement::Constant(name, expr, _) => {
+            PklStatement::Constant {
+                name,
+                value: expr,
+                modifiers,
+                doc,
+                annotations,
+                span: rng,
+            } => {
                 in_body = true;
-                table.insert(name, table.evaluate(expr)?);
+
+                let overrides_parent = parent
+                    .as_ref()
+                    .is_some_and(|parent| parent.declared.contains_key(name));
+                if overrides_parent {
+                    table.check_overridable(name, rng.to_owned())?;
+                }
+
+                table.record_modifiers(name, modifiers, &expr);
+                table.record_metadata(name, doc.as_ref(), &annotations, &expr)?;
+
+                let value = match expr {
+                    PklExpr::Value(AstPklValue::Object(body)) if overrides_parent => {
+                        match table.variables.get(name) {
+                            Some(value @ (PklValue::Object(_) | PklValue::ClassInstance(_, _))) => {
+                                table.amend(value.to_owned(), body, Some(name), rng.to_owned())?
+                            }
+                            _ => table.evaluate(PklExpr::Value(AstPklValue::Object(body)))?,
+                        }
+                    }
+                    expr => table.evaluate(expr)?,
+                };
+
+                if let Some(parent) = &parent {
+                    if !modifiers.local {
+                        parent.check(name, &value, rng.to_owned())?;
+                    }
+                }
+
+                table.insert(name, value);
+                table.spans.insert(name.to_owned(), rng);
             }
             PklStatement::Import(value, local_name, rng) => {
                 if in_body {
-                    return Err((
-                        "Import statements must be before document body".to_owned(),
+                    return Err(PklError::syntax(
+                        "Import statements must be before document body",
                         rng,
                     ));
                 }
 
-                // it does not import for the moment, issue with lifetimes
-                table.import(value, rng)?;
+                in_imports = true;
+                table.import(value, local_name, rng)?;
+            }
+            PklStatement::GlobImport(pattern, local_name, rng) => {
+                if in_body {
+                    return Err(PklError::syntax(
+                        "Import statements must be before document body",
+                        rng,
+                    ));
+                }
+
+                in_imports = true;
+                table.import_glob(pattern, local_name, rng)?;
+            }
+            PklStatement::ModuleClause(name, rng) => {
+                // the module clause may only follow the module annotations
+                if index != usize::from(is_annotated) {
+                    return Err(PklError::syntax(
+                        "Module clause must be the first statement of the module",
+                        rng,
+                    ));
+                }
+
+                table.module_name = Some(name.into());
+            }
+            PklStatement::Amends(uri, rng) | PklStatement::Extends(uri, rng) => {
+                if in_body || in_imports || parent.is_some() {
+                    return Err(PklError::syntax(
+                        "A module can only amend or extend one module, right after its module clause",
+                        rng,
+                    ));
+                }
+
+                let parent_table = table.load_module_table(uri, rng.to_owned())?.exported();
+                let declared = parent_table
+                    .variables
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.get_type().to_owned()))
+                    .collect();
+
+                parent = Some(ParentModule {
+                    uri,
+                    is_amends,
+                    declared,
+                });
+                table.variables = parent_table.variables;
+                table.spans = table
+                    .variables
+                    .keys()
+                    .map(|name| (name.to_string(), rng.to_owned()))
+                    .collect();
+                table.modifiers = parent_table.modifiers;
+                table.docs = parent_table.docs;
+                table.annotations = parent_table.annotations;
+                table
+                    .warnings
+                    .get_mut()
+                    .extend(parent_table.warnings.into_inner());
+            }
+            PklStatement::ModuleAnnotations(annotations, rng) => {
+                if index != 0 {
+                    return Err(PklError::syntax(
+                        "Module annotations must be the first statement of the module",
+                        rng,
+                    ));
+                }
+
+                is_annotated = true;
+                table.module_annotations = annotations
+                    .into_iter()
+                    .map(|annotation| table.evaluate_annotation(annotation))
+                    .collect::<PklResult<_>>()?;
+            }
+            PklStatement::Error(rng) => {
+                return Err(PklError::syntax(
+                    "Cannot evaluate a module containing syntax errors",
+                    rng,
+                ));
             }
         }
     }
 
     Ok(table)
 }
+
+/// Copies the entries of `map` for the members of `base_path` to the members of `path`.
+fn inherit_entries<T: Clone>(map: &mut HashMap<String, T>, base_path: &str, path: &str) {
+    let prefix = format!("{base_path}.");
+    let inherited: Vec<(String, T)> = map
+        .iter()
+        .filter_map(|(member_path, value)| {
+            member_path
+                .strip_prefix(&prefix)
+                .map(|member| (format!("{path}.{member}"), value.clone()))
+        })
+        .collect();
+
+    map.extend(inherited);
+}
+
+/// Returns the dotted path an expression refers to, e.g. `bird.taxonomy`,
+/// or `None` if it is not made of identifiers only.
+fn expr_path(expr: &PklExpr) -> Option<String> {
+    match expr {
+        PklExpr::Identifier(Identifier(id, _)) => Some((*id).to_owned()),
+        PklExpr::MemberExpression(base, ExprMember::Identifier(Identifier(id, _)), _) => {
+            expr_path(base).map(|base| format!("{base}.{id}"))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MemoryModuleResolver, Pkl, PklError, PklResult, PklValue};
+
+    const PARENT: &str =
+        "ratio = 1.5\ncount = 1\nname = \"base\"\nextra = null\nbird {\n  name = \"Pigeon\"\n}\n";
+
+    fn amend<'a>(source: &'a str) -> PklResult<Pkl<'a>> {
+        let resolver = MemoryModuleResolver::new().with_module("base.pkl", PARENT);
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.set_module_uri("main.pkl");
+        pkl.parse(source)?;
+
+        return Ok(pkl);
+    }
+
+    #[test]
+    fn amends_accepts_overrides_of_compatible_types() {
+        let pkl = amend(
+            "amends \"base.pkl\"\nratio = 2\ncount = 2.5\nname = null\nextra {\n  a = 1\n}\nbird {\n  age = 3\n}\n",
+        )
+        .unwrap();
+
+        assert_eq!(pkl.get("ratio"), Some(&PklValue::Int(2)));
+        assert_eq!(pkl.get("count"), Some(&PklValue::Float(2.5)));
+        assert_eq!(pkl.get("name"), Some(&PklValue::Null));
+        assert!(pkl.get("extra").is_some_and(PklValue::is_object));
+        let bird = pkl.get("bird").and_then(PklValue::as_object).unwrap();
+        assert_eq!(
+            bird.get("name"),
+            Some(&PklValue::String("Pigeon".to_owned()))
+        );
+        assert_eq!(bird.get("age"), Some(&PklValue::Int(3)));
+    }
+
+    #[test]
+    fn amends_rejects_overrides_of_incompatible_types() {
+        for (source, message) in [
+            (
+                "amends \"base.pkl\"\ncount = \"one\"\n",
+                "Property `count` is of type Int in module `base.pkl`, cannot assign a value of type String",
+            ),
+            (
+                "amends \"base.pkl\"\nname = 1\n",
+                "Property `name` is of type String in module `base.pkl`, cannot assign a value of type Int",
+            ),
+            (
+                "amends \"base.pkl\"\nratio {\n  a = 1\n}\n",
+                "Property `ratio` is of type Float in module `base.pkl`, cannot assign a value of type Object",
+            ),
+        ] {
+            let Err(error) = amend(source) else {
+                panic!("`{source}` should not evaluate");
+            };
+
+            assert!(matches!(error, PklError::TypeMismatch(_)), "{error}");
+            assert_eq!(error.message(), message);
+        }
+    }
+
+    #[test]
+    fn amends_rejects_new_properties() {
+        let Err(error) = amend("amends \"base.pkl\"\nsize = 1\n") else {
+            panic!("amending modules should not declare new properties");
+        };
+
+        assert!(matches!(error, PklError::UnknownVariable(_)), "{error}");
+        assert!(amend("extends \"base.pkl\"\nsize = 1\n").is_ok());
+    }
+
+    #[test]
+    fn getters_locate_their_errors() {
+        let mut pkl = Pkl::new();
+        pkl.set_source_name("first.pkl");
+        pkl.parse(
+            "a = 1
+",
+        )
+        .unwrap();
+        pkl.set_source_name("birds.pkl");
+        pkl.parse(
+            "count = 1
+
+name = \"Pigeon\"
+",
+        )
+        .unwrap();
+
+        let error = pkl.get_int("name").unwrap_err();
+        assert!(matches!(error, PklError::TypeMismatch(_)), "{error}");
+        assert_eq!(error.source_name(), Some("birds.pkl"));
+        assert_eq!((error.line(), error.column()), (Some(3), Some(1)));
+
+        let error = pkl.get_string("a").unwrap_err();
+        assert_eq!(error.source_name(), Some("first.pkl"));
+        assert_eq!((error.line(), error.column()), (Some(1), Some(1)));
+
+        let error = pkl.get_bool("missing").unwrap_err();
+        assert!(matches!(error, PklError::UnknownVariable(_)), "{error}");
+        assert_eq!(error.source_name(), Some("birds.pkl"));
+    }
+
+    #[test]
+    fn amending_merges_nested_objects() {
+        let mut pkl = Pkl::new();
+        pkl.parse(
+            "base {\n  server {\n    host = \"localhost\"\n    port = 1\n  }\n  name = \"base\"\n}\namended = (base) {\n  server {\n    port = 2\n  }\n}\nbird = new Bird {\n  taxonomy {\n    genus = \"Columba\"\n  }\n}\npigeon = (bird) {\n  taxonomy {\n    species = \"livia\"\n  }\n}\n",
+        )
+        .unwrap();
+
+        let amended = pkl.get("amended").and_then(PklValue::as_object).unwrap();
+        let server = amended.get("server").and_then(PklValue::as_object).unwrap();
+        assert_eq!(
+            server.get("host"),
+            Some(&PklValue::String("localhost".to_owned()))
+        );
+        assert_eq!(server.get("port"), Some(&PklValue::Int(2)));
+        assert_eq!(
+            amended.get("name"),
+            Some(&PklValue::String("base".to_owned()))
+        );
+
+        let Some(PklValue::ClassInstance(class_name, pigeon)) = pkl.get("pigeon") else {
+            panic!(
+                "`pigeon` should be a class instance: {:?}",
+                pkl.get("pigeon")
+            );
+        };
+        assert_eq!(class_name, "Bird");
+        let taxonomy = pigeon
+            .get("taxonomy")
+            .and_then(PklValue::as_object)
+            .unwrap();
+        assert_eq!(taxonomy.len(), 2);
+    }
+
+    #[test]
+    fn amended_objects_can_be_any_expression() {
+        let pkl = amend(
+            "bird {\n  taxonomy {\n    genus = \"Columba\"\n  }\n}\ntaxonomy = (bird.taxonomy) {\n  species = \"livia\"\n}\nimported = (import(\"base.pkl\").bird) {\n  age = 3\n}\n",
+        )
+        .unwrap();
+
+        let taxonomy = pkl.get("taxonomy").and_then(PklValue::as_object).unwrap();
+        assert_eq!(
+            taxonomy.get("genus"),
+            Some(&PklValue::String("Columba".to_owned()))
+        );
+        assert_eq!(
+            taxonomy.get("species"),
+            Some(&PklValue::String("livia".to_owned()))
+        );
+
+        let imported = pkl.get("imported").and_then(PklValue::as_object).unwrap();
+        assert_eq!(
+            imported.get("name"),
+            Some(&PklValue::String("Pigeon".to_owned()))
+        );
+        assert_eq!(imported.get("age"), Some(&PklValue::Int(3)));
+    }
+
+    #[test]
+    fn modifiers_restrict_amending_and_output() {
+        const MODIFIED: &str = "fixed kind = \"bird\"\nconst legs = 2\nlocal secret = \"seed\"\nhidden cache = secret\nname = \"Pigeon\"\n";
+        let resolver = MemoryModuleResolver::new().with_module("modified.pkl", MODIFIED);
+
+        for (source, message) in [
+            (
+                "amends \"modified.pkl\"\nkind = \"fish\"\n",
+                "Cannot amend `kind`, it is declared as fixed",
+            ),
+            (
+                "amends \"modified.pkl\"\nlegs = 4\n",
+                "Cannot amend `legs`, it is declared as const",
+            ),
+        ] {
+            let mut pkl = Pkl::new().with_module_resolver(resolver.clone());
+            let error = pkl.parse(source).unwrap_err();
+
+            assert!(matches!(error, PklError::ConstraintViolation(_)), "{error}");
+            assert_eq!(error.message(), message);
+        }
+
+        let mut pkl = Pkl::new().with_module_resolver(resolver);
+        pkl.parse("import \"modified.pkl\"\nname = modified.name\n")
+            .unwrap();
+        let imported = pkl.get("modified").and_then(PklValue::as_object).unwrap();
+        assert!(!imported.contains_key("secret"));
+        assert_eq!(
+            imported.get("cache"),
+            Some(&PklValue::String("seed".to_owned()))
+        );
+
+        let mut pkl = Pkl::new();
+        pkl.parse(MODIFIED).unwrap();
+        assert!(pkl.get("cache").is_some());
+        let output = pkl.output();
+        assert!(!output.contains_key("cache") && !output.contains_key("secret"));
+        assert_eq!(output.len(), 3);
+    }
+
+    #[test]
+    fn strings_decode_escapes_and_interpolate_expressions() {
+        let mut pkl = Pkl::new();
+        pkl.parse(
+            "name = \"Pigeon\"\nage = 3\nescaped = \"tab\\there \\\"quoted\\\" \\u{1F426} \\\\\"\ngreeting = \"\\(name) is \\(age) years old\"\nnested = \"\\(\"<\\(name)>\")!\"\nmulti = \"\"\"\n  \\(name)\n  \\tdone\n  \"\"\"\n",
+        )
+        .unwrap();
+
+        for (name, expected) in [
+            ("escaped", "tab\there \"quoted\" \u{1F426} \\"),
+            ("greeting", "Pigeon is 3 years old"),
+            ("nested", "<Pigeon>!"),
+            ("multi", "Pigeon\n\tdone"),
+        ] {
+            assert_eq!(pkl.get_string(name).unwrap(), expected, "{name}");
+        }
+
+        let error = pkl.parse("broken = \"\\(missing)\"\n").unwrap_err();
+        assert!(matches!(error, PklError::UnknownVariable(_)), "{error}");
+    }
+
+    #[test]
+    fn raw_strings_need_their_delimiter_pounds() {
+        let mut pkl = Pkl::new();
+        pkl.parse(
+            "name = \"Pigeon\"\nregex = #\"\\d+ \"quoted\" \\(name)\"#\nescaped = #\"\\#t\\#(name)\"#\ndouble = ##\"a \"# b\"##\nmulti = #\"\"\"\n  {\"name\": \"\\(name)\"}\n  \\#(name)\n  \"\"\"#\n",
+        )
+        .unwrap();
+
+        for (name, expected) in [
+            ("regex", "\\d+ \"quoted\" \\(name)"),
+            ("escaped", "\tPigeon"),
+            ("double", "a \"# b"),
+            ("multi", "{\"name\": \"\\(name)\"}\nPigeon"),
+        ] {
+            assert_eq!(pkl.get_string(name).unwrap(), expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn doc_comments_attach_to_the_following_declaration() {
+        let mut pkl = Pkl::new();
+        pkl.parse(
+            "/// A bird.\n/// Kept in a loft.\nbird {\n  /// Its name.\n  name = \"Pigeon\"\n  age = 3\n}\n/// Detached by a blank line.\n\nlonely = 1\n// Not a doc comment.\nplain = 2\n",
+        )
+        .unwrap();
+
+        assert_eq!(pkl.doc_for("bird"), Some("A bird.\nKept in a loft."));
+        assert_eq!(pkl.doc_for("bird.name"), Some("Its name."));
+        assert_eq!(pkl.doc_for("bird.age"), None);
+        assert_eq!(pkl.doc_for("lonely"), None);
+        assert_eq!(pkl.doc_for("plain"), None);
+    }
+
+    #[test]
+    fn deprecated_properties_warn_when_read() {
+        let mut pkl = Pkl::new();
+        pkl.set_source_name("birds.pkl");
+        pkl.parse(
+            "@ModuleInfo { minPklVersion = \"0.25.0\" }\nmodule birds\n\n@Deprecated { message = \"use name\", replaceWith = \"name\" }\nlabel = \"Pigeon\"\n@Custom\nname = label\nbird {\n  @Deprecated\n  old = 1\n}\ncurrent = bird.old\n",
+        )
+        .unwrap();
+
+        let Some(PklValue::ClassInstance(class_name, info)) = pkl.module_annotations().first()
+        else {
+            panic!("{:?}", pkl.module_annotations());
+        };
+        assert_eq!(class_name, "ModuleInfo");
+        assert_eq!(
+            info.get("minPklVersion"),
+            Some(&PklValue::String("0.25.0".to_owned()))
+        );
+        assert!(matches!(
+            pkl.annotations_for("name"),
+            [PklValue::ClassInstance(class_name, _)] if class_name == "Custom"
+        ));
+
+        let warnings = pkl.take_warnings();
+        let messages: Vec<_> = warnings.iter().map(|warning| warning.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "birds.pkl:7:8: `label` is deprecated: use name",
+                "birds.pkl:12:11: `bird.old` is deprecated",
+            ]
+        );
+        assert_eq!(warnings[0].info().help, ["replace it with `name`"]);
+        assert!(pkl.warnings().is_empty());
+    }
+}
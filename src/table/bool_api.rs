@@ -1,10 +1,10 @@
-use crate::{generate_method, PklResult, PklValue};
+use crate::{generate_method, PklError, PklResult, PklValue};
 use std::ops::Range;
 
 /// Based on v0.26.0
-pub fn match_bool_methods_api<'a, 'b>(
+pub fn match_bool_methods_api<'b>(
     bool_value: bool,
-    fn_name: &'a str,
+    fn_name: &str,
     args: Vec<PklValue<'b>>,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
@@ -46,7 +46,7 @@ pub fn match_bool_methods_api<'a, 'b>(
             )
         }
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("Boolean does not possess {} method", fn_name),
                 range,
             ))
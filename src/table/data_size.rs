@@ -2,14 +2,14 @@
 //     "b", "kb", "mb", "gb", "tb", "pb", "kib", "mib", "gib", "tib", "pib",
 // ];
 
-use crate::{PklResult, PklValue};
+use crate::{PklError, PklResult, PklValue};
 use std::fmt;
 use std::ops::Range;
 
 /// Based on v0.26.0
-pub fn match_data_size_props_api<'a, 'b>(
+pub fn match_data_size_props_api<'b>(
     byte: Byte<'b>,
-    property: &'a str,
+    property: &str,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
     match property {
@@ -21,19 +21,19 @@ pub fn match_data_size_props_api<'a, 'b>(
         }
         "isPositive" => return Ok(PklValue::Bool(byte.bytes >= 0)),
         "isBinaryUnit" => {
-            return Ok(PklValue::Bool(match byte.unit {
-                Unit::B | Unit::GiB | Unit::KiB | Unit::MiB | Unit::PiB | Unit::TiB => true,
-                _ => false,
-            }))
+            return Ok(PklValue::Bool(matches!(
+                byte.unit,
+                Unit::B | Unit::GiB | Unit::KiB | Unit::MiB | Unit::PiB | Unit::TiB
+            )))
         }
         "isDecimalUnit" => {
-            return Ok(PklValue::Bool(match byte.unit {
-                Unit::GiB | Unit::KiB | Unit::MiB | Unit::PiB | Unit::TiB => false,
-                _ => true,
-            }))
+            return Ok(PklValue::Bool(!matches!(
+                byte.unit,
+                Unit::GiB | Unit::KiB | Unit::MiB | Unit::PiB | Unit::TiB
+            )))
         }
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("DataSize does not possess {} property", property),
                 range,
             ))
@@ -61,6 +61,7 @@ pub enum Unit {
 impl Unit {
     /// Parses a string slice into an `Option<Unit>`.
     /// Returns `None` if the string does not correspond to a known data size unit.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "b" => Some(Unit::B),
@@ -121,6 +122,16 @@ impl<'a> Byte<'a> {
             unit,
         }
     }
+
+    /// Converts the data size into one no longer borrowing from the parsed source,
+    /// see [`PklValue::into_owned`].
+    pub fn into_owned(self) -> Byte<'static> {
+        Byte {
+            bytes: self.bytes,
+            initial_value: Box::new(self.initial_value.into_owned()),
+            unit: self.unit,
+        }
+    }
 }
 
 fn calculate_bytes(value: f64, unit: Unit) -> i64 {
@@ -141,6 +152,12 @@ fn calculate_bytes(value: f64, unit: Unit) -> i64 {
     bytes as i64
 }
 
+impl fmt::Display for Byte<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.initial_value, self.unit)
+    }
+}
+
 impl fmt::Display for Unit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let unit_str = match self {
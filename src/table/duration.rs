@@ -1,13 +1,13 @@
-use crate::{PklResult, PklValue};
+use crate::{PklError, PklResult, PklValue};
 use std::fmt;
 use std::{ops::Range, time::Duration as StdDuration};
 
 // pub const DURATION_UNITS: [&str; 7] = ["ns", "us", "ms", "s", "min", "h", "d"];
 
 /// Based on v0.26.0
-pub fn match_duration_props_api<'a, 'b>(
+pub fn match_duration_props_api<'b>(
     duration: Duration<'b>,
-    property: &'a str,
+    property: &str,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
     match property {
@@ -20,7 +20,7 @@ pub fn match_duration_props_api<'a, 'b>(
         "isPositive" => return Ok(PklValue::Bool(!duration.is_negative)),
         "isoString" => return Ok(PklValue::String(duration.to_iso_string())),
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("DataSize does not possess {} property", property),
                 range,
             ))
@@ -43,6 +43,7 @@ pub enum Unit {
 impl Unit {
     /// Parses a string slice into an `Option<Unit>`.
     /// Returns `None` if the string does not correspond to a known data size unit.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "ns" => Some(Unit::NS),
@@ -89,6 +90,17 @@ impl<'a> Duration<'a> {
         }
     }
 
+    /// Converts the duration into one no longer borrowing from the parsed source,
+    /// see [`PklValue::into_owned`].
+    pub fn into_owned(self) -> Duration<'static> {
+        Duration {
+            duration: self.duration,
+            initial_value: Box::new(self.initial_value.into_owned()),
+            unit: self.unit,
+            is_negative: self.is_negative,
+        }
+    }
+
     pub fn to_iso_string(&self) -> String {
         let seconds = self.duration.as_secs();
         let nanos = self.duration.subsec_nanos();
@@ -114,7 +126,7 @@ impl<'a> Duration<'a> {
         }
         if secs > 0 || millis > 0 {
             if millis > 0 {
-                iso_string.push_str(&format!("{}.{}S", secs, format!("{:03}", millis)));
+                iso_string.push_str(&format!("{}.{:03}S", secs, millis));
             } else {
                 iso_string.push_str(&format!("{}S", secs));
             }
@@ -154,6 +166,12 @@ impl<'a> Duration<'a> {
     }
 }
 
+impl fmt::Display for Duration<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.initial_value, self.unit)
+    }
+}
+
 impl fmt::Display for Unit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let unit_str = match self {
@@ -2,13 +2,13 @@ use super::{
     data_size,
     duration::{self, Duration},
 };
-use crate::{values::Byte, PklResult, PklValue};
+use crate::{values::Byte, PklError, PklResult, PklValue};
 use std::ops::Range;
 
 /// Based on v0.26.0
-pub fn match_float_props_api<'a, 'b>(
+pub fn match_float_props_api<'b>(
     float: f64,
-    property: &'a str,
+    property: &str,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
     if let Some(unit) = duration::Unit::from_str(property) {
@@ -43,16 +43,26 @@ pub fn match_float_props_api<'a, 'b>(
         "isNaN" => return Ok(PklValue::Bool(float.is_nan())),
         "isNonZero" => return Ok(PklValue::Bool(float != 0.0)),
 
-        "isEven" => return Err(("Float does not possess isEven property".to_owned(), range)),
-        "isOdd" => return Err(("Float does not possess isOdd property".to_owned(), range)),
+        "isEven" => {
+            return Err(PklError::unknown_variable(
+                "Float does not possess isEven property",
+                range,
+            ))
+        }
+        "isOdd" => {
+            return Err(PklError::unknown_variable(
+                "Float does not possess isOdd property",
+                range,
+            ))
+        }
         "inv" => {
-            return Err((
-                "Cannot apply bitwise NOT operator on floats".to_owned(),
+            return Err(PklError::evaluation(
+                "Cannot apply bitwise NOT operator on floats",
                 range,
             ))
         }
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("Float does not possess {} property", property),
                 range,
             ))
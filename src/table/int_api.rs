@@ -2,13 +2,13 @@ use super::{
     data_size,
     duration::{self, Duration},
 };
-use crate::{values::Byte, PklResult, PklValue};
+use crate::{values::Byte, PklError, PklResult, PklValue};
 use std::ops::Range;
 
 /// Based on v0.26.0
-pub fn match_int_props_api<'a, 'b>(
+pub fn match_int_props_api<'b>(
     int: i64,
-    property: &'a str,
+    property: &str,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
     if let Some(unit) = duration::Unit::from_str(property) {
@@ -42,6 +42,11 @@ pub fn match_int_props_api<'a, 'b>(
         "inv" => return Ok(PklValue::Int(!int)),
         "isEven" => return Ok(PklValue::Bool(int % 2 == 0)),
         "isOdd" => return Ok(PklValue::Bool(int % 2 == 1)),
-        _ => return Err((format!("Int does not possess {} property", property), range)),
+        _ => {
+            return Err(PklError::unknown_variable(
+                format!("Int does not possess {} property", property),
+                range,
+            ))
+        }
     }
 }
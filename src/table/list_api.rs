@@ -1,10 +1,10 @@
-use crate::{PklResult, PklValue};
+use crate::{PklError, PklResult, PklValue};
 use std::ops::Range;
 
 /// Based on v0.26.0
-pub fn match_list_props_api<'a, 'b>(
+pub fn match_list_props_api<'b>(
     mut list: Vec<PklValue<'b>>,
-    property: &'a str,
+    property: &str,
     range: Range<usize>,
 ) -> PklResult<PklValue<'b>> {
     match property {
@@ -16,8 +16,8 @@ pub fn match_list_props_api<'a, 'b>(
         }
         "first" => {
             if list.is_empty() {
-                return Err((
-                    "Cannot get first element of an empty list!".to_owned(),
+                return Err(PklError::evaluation(
+                    "Cannot get first element of an empty list!",
                     range,
                 ));
             }
@@ -33,7 +33,10 @@ pub fn match_list_props_api<'a, 'b>(
         }
         "rest" => {
             if list.is_empty() {
-                return Err(("Cannot get the rest of an empty list!".to_owned(), range));
+                return Err(PklError::evaluation(
+                    "Cannot get the rest of an empty list!",
+                    range,
+                ));
             }
 
             return Ok(PklValue::List(list.split_at(1).1.to_vec()));
@@ -47,7 +50,10 @@ pub fn match_list_props_api<'a, 'b>(
         }
         "last" => {
             if list.is_empty() {
-                return Err(("Cannot get last element of empty list!".to_owned(), range));
+                return Err(PklError::evaluation(
+                    "Cannot get last element of empty list!",
+                    range,
+                ));
             }
 
             return Ok(list.last().unwrap().to_owned());
@@ -61,8 +67,8 @@ pub fn match_list_props_api<'a, 'b>(
         }
         "single" => {
             if list.is_empty() || list.len() != 1 {
-                return Err((
-                    "Cannot get single element of a list with length != 1!".to_owned(),
+                return Err(PklError::evaluation(
+                    "Cannot get single element of a list with length != 1!",
                     range,
                 ));
             }
@@ -85,16 +91,46 @@ pub fn match_list_props_api<'a, 'b>(
             return Ok(PklValue::Int((list.len() - 1) as i64));
         }
 
-        "min" => return Err((format!("min property is not yet implemented"), range)),
-        "minOrNull" => return Err((format!("minOrNull property is not yet implemented"), range)),
-        "max" => return Err((format!("max property is not yet implemented"), range)),
-        "maxOrNull" => return Err((format!("maxOrNull property is not yet implemented"), range)),
+        "min" => {
+            return Err(PklError::evaluation(
+                "min property is not yet implemented",
+                range,
+            ))
+        }
+        "minOrNull" => {
+            return Err(PklError::evaluation(
+                "minOrNull property is not yet implemented",
+                range,
+            ))
+        }
+        "max" => {
+            return Err(PklError::evaluation(
+                "max property is not yet implemented",
+                range,
+            ))
+        }
+        "maxOrNull" => {
+            return Err(PklError::evaluation(
+                "maxOrNull property is not yet implemented",
+                range,
+            ))
+        }
 
-        "isDistinct" => return Err((format!("isDistinct property is not yet implemented"), range)),
-        "distinct" => return Err((format!("distinct property is not yet implemented"), range)),
+        "isDistinct" => {
+            return Err(PklError::evaluation(
+                "isDistinct property is not yet implemented",
+                range,
+            ))
+        }
+        "distinct" => {
+            return Err(PklError::evaluation(
+                "distinct property is not yet implemented",
+                range,
+            ))
+        }
 
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("List does not possess {} property", property),
                 range,
             ))
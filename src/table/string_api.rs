@@ -1,5 +1,5 @@
 use crate::generate_method;
-use crate::{PklResult, PklValue};
+use crate::{PklError, PklResult, PklValue};
 use base64::prelude::*;
 use std::ops::Range;
 
@@ -13,53 +13,60 @@ pub fn match_string_props_api<'a, 'b>(
         "length" => return Ok(PklValue::Int(s.len() as i64)),
         "lastIndex" => {
             return Ok(PklValue::Int({
-                if s.len() == 0 {
+                if s.is_empty() {
                     -1
                 } else {
                     (s.len() - 1) as i64
                 }
             }))
         }
-        "isEmpty" => return Ok(PklValue::Bool(s.len() == 0)),
-        "isBlank" => return Ok(PklValue::Bool(s.trim().len() == 0)),
+        "isEmpty" => return Ok(PklValue::Bool(s.is_empty())),
+        "isBlank" => return Ok(PklValue::Bool(s.trim().is_empty())),
         "isRegex" => {
-            return Err((
-                "isRegex String API method not yet supported".to_owned(),
+            return Err(PklError::evaluation(
+                "isRegex String API method not yet supported",
+                range,
+            ))
+        }
+        "md5" => {
+            return Err(PklError::evaluation(
+                "md5 String API method not yet supported",
+                range,
+            ))
+        }
+        "sha1" => {
+            return Err(PklError::evaluation(
+                "sha1 String API method not yet supported",
                 range,
             ))
         }
-        "md5" => return Err(("md5 String API method not yet supported".to_owned(), range)),
-        "sha1" => return Err(("sha1 String API method not yet supported".to_owned(), range)),
         "sha256" => {
-            return Err((
-                "sha256 String API method not yet supported".to_owned(),
+            return Err(PklError::evaluation(
+                "sha256 String API method not yet supported",
                 range,
             ))
         }
         "sha256Int" => {
-            return Err((
-                "sha256Int String API method not yet supported".to_owned(),
+            return Err(PklError::evaluation(
+                "sha256Int String API method not yet supported",
                 range,
             ))
         }
         "base64" => return Ok(PklValue::String(BASE64_STANDARD.encode(s))),
         "base64Decoded" => {
             let buf: Vec<u8> = BASE64_STANDARD.decode(s).map_err(|e| {
-                (
-                    format!("Failed to decode base64: {}", e.to_string()),
-                    range.to_owned(),
-                )
+                PklError::evaluation(format!("Failed to decode base64: {}", e), range.to_owned())
             })?;
 
-            let s = std::str::from_utf8(&buf)
-                .map_err(|e| (format!("Invalid UTF-8 sequence: {}", e.to_string()), range))?;
+            let s = std::str::from_utf8(&buf).map_err(|e| {
+                PklError::evaluation(format!("Invalid UTF-8 sequence: {}", e), range)
+            })?;
 
             return Ok(PklValue::String(s.to_owned()));
         }
         "chars" => {
             let chars = s
                 .chars()
-                .into_iter()
                 .map(|c| PklValue::String(c.to_string()))
                 .collect::<Vec<_>>();
 
@@ -70,14 +77,13 @@ pub fn match_string_props_api<'a, 'b>(
             // would be better to have the Int as an u32
             let codepoints = s
                 .chars()
-                .into_iter()
                 .map(|c| PklValue::Int(c as i64))
                 .collect::<Vec<_>>();
 
             return Ok(PklValue::List(codepoints));
         }
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!("String does not possess {} property", property),
                 range,
             ))
@@ -113,10 +119,10 @@ pub fn match_string_methods_api<'a, 'b>(
                 0: Int, 1: Int;
                 |(start, exclusive_end): (i64, i64)| {
                     if start < 0 || start as usize >= s.len() {
-                        return Err(("start index is out of bound".to_owned(), range))
+                        return Err(PklError::evaluation("start index is out of bound", range))
                     }
                     if exclusive_end < start || exclusive_end as usize >= s.len() {
-                        return Err(("exclusiveEnd index is out of bound".to_owned(), range))
+                        return Err(PklError::evaluation("exclusiveEnd index is out of bound", range))
                     }
 
                     if let Some(s) = s.get(start as usize..exclusive_end as usize) {
@@ -132,12 +138,12 @@ pub fn match_string_methods_api<'a, 'b>(
             generate_method!(
                 "substringOrNull", &args;
                 0: Int, 1: Int;
-                |(start, exclusiveEnd): (i64, i64)| {
-                    if start < 0 || start as usize >= s.len() || exclusiveEnd < start || exclusiveEnd as usize >= s.len() {
+                |(start, exclusive_end): (i64, i64)| {
+                    if start < 0 || start as usize >= s.len() || exclusive_end < start || exclusive_end as usize >= s.len() {
                         return Ok(().into())
                     }
 
-                    if let Some(s) = s.get(start as usize..exclusiveEnd as usize) {
+                    if let Some(s) = s.get(start as usize..exclusive_end as usize) {
                         return Ok(s.to_owned().into())
                     }
 
@@ -201,7 +207,7 @@ pub fn match_string_methods_api<'a, 'b>(
                 "indexOf", &args;
                 0: String;
                 |pattern: String| {
-                    let result = s.find(&pattern).ok_or((format!("Cannot use indexOf to index pattern '{pattern}', it is not present in the string"), range))?;
+                    let result = s.find(&pattern).ok_or(PklError::evaluation(format!("Cannot use indexOf to index pattern '{pattern}', it is not present in the string"), range))?;
                     Ok((result as i64).into())
                 };
                 range
@@ -218,7 +224,7 @@ pub fn match_string_methods_api<'a, 'b>(
             )
         }
         _ => {
-            return Err((
+            return Err(PklError::unknown_variable(
                 format!(
                     "String does not possess {} method (or method not yet implemented)",
                     fn_name
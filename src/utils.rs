@@ -14,11 +14,11 @@
 ///
 /// Returns a `Result` containing either:
 /// * `Ok((&str, Range<usize>))` - A tuple with the identifier string and its span.
-/// * `Err((String, Range<usize>))` - A tuple with an error message and the error span.
+/// * `Err(PklError)` - A syntax or lexing error, with its span.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// // Using default error messages
 /// let result = parse_identifier!(lexer);
 ///
@@ -44,8 +44,8 @@ macro_rules! parse_identifier {
     };
     // Pattern 2: Lexer with custom error messages
     ($lexer:expr, $default_unexpected:expr, $eof_error:expr) => {{
-        use crate::lexer::PklToken;
-        use crate::parser::Identifier;
+        use $crate::lexer::PklToken;
+        use $crate::parser::Identifier;
         let start = $lexer.span().start;
         while let Some(token) = $lexer.next() {
             match token {
@@ -56,14 +56,23 @@ macro_rules! parse_identifier {
                     // Skip spaces and newlines
                 }
                 Err(e) => {
-                    return Err((e.to_string(), $lexer.span()));
+                    return Err($crate::PklError::lexing(
+                        e.to_string(),
+                        e.span($lexer.span()),
+                    ));
                 }
                 _ => {
-                    return Err(($default_unexpected.to_owned(), $lexer.span()));
+                    return Err($crate::PklError::syntax(
+                        $default_unexpected.to_owned(),
+                        $lexer.span(),
+                    ));
                 }
             }
         }
-        Err(($eof_error.to_owned(), $lexer.span()))
+        Err($crate::PklError::syntax(
+            $eof_error.to_owned(),
+            $lexer.span(),
+        ))
     }};
 }
 
@@ -83,11 +92,11 @@ macro_rules! parse_identifier {
 ///
 /// Returns a `PklResult` containing either:
 /// * `Ok((&str, Range<usize>))` - A tuple with the string content and its span.
-/// * `Err((String, Range<usize>))` - A tuple with an error message and the error span.
+/// * `Err(PklError)` - A syntax or lexing error, with its span.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// // Using default error messages
 /// let result = parse_string!(lexer);
 ///
@@ -121,14 +130,23 @@ macro_rules! parse_string {
                     // Skip spaces and newlines
                 }
                 Err(e) => {
-                    return Err((e.to_string(), $lexer.span()));
+                    return Err($crate::PklError::lexing(
+                        e.to_string(),
+                        e.span($lexer.span()),
+                    ));
                 }
                 _ => {
-                    return Err(($default_unexpected.to_owned(), $lexer.span()));
+                    return Err($crate::PklError::syntax(
+                        $default_unexpected.to_owned(),
+                        $lexer.span(),
+                    ));
                 }
             }
         }
-        Err(($eof_error.to_owned(), $lexer.span()))
+        Err($crate::PklError::syntax(
+            $eof_error.to_owned(),
+            $lexer.span(),
+        ))
     }};
 }
 
@@ -176,14 +194,14 @@ macro_rules! count_args {
 #[macro_export]
 macro_rules! generate_method {
     ($name:expr,$args:expr; $($arg_index:tt : $arg_type:ident),+; $action:expr; $range:expr) => {{
-        use crate::count_args;
+        use $crate::count_args;
 
         let name: &str = $name;
         let number_of_args: usize = count_args!($($arg_index),+);
         let args: &Vec<PklValue<'_>> = $args;
 
         if $args.len() != number_of_args {
-            return Err((
+            return Err($crate::PklError::evaluation(
                 format!(
                     "Boolean expects '{}' method to take exactly {} argument(s)",
                     name, number_of_args
@@ -194,7 +212,7 @@ macro_rules! generate_method {
 
         $(
             if args[$arg_index].get_type() != stringify!($arg_type) {
-                return Err((
+                return Err($crate::PklError::type_mismatch(
                     format!(
                         "{} method expects argument at index {} to be of type {}, but found {}",
                         name, $arg_index, stringify!($arg_type), args[$arg_index].get_type()
@@ -209,7 +227,7 @@ macro_rules! generate_method {
                 if let PklValue::$arg_type(value) = &args[$arg_index] {
                     value.to_owned()
                 } else {
-                    return Err((
+                    return Err($crate::PklError::type_mismatch(
                         format!(
                             "{} method expects argument at index {} to be of type {}, but found {}",
                             name, $arg_index, stringify!($arg_type), args[$arg_index].get_type()